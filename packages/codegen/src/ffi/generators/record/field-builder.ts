@@ -130,7 +130,8 @@ export class FieldBuilder {
                             writer.write(`write(this.handle, `);
                             this.writers.ffiTypeWriter.toWriter(nestedTypeMapping.ffi)(writer);
                             writer.writeLine(
-                                `, ${nestedOffset}, init.${fieldName}.get${capitalizedNestedFieldName}());`,
+                                `, ${nestedOffset}, init.${fieldName}.get${capitalizedNestedFieldName}(), ` +
+                                    "{ allowBorrowed: true });",
                             );
                         }
                     });
@@ -141,7 +142,7 @@ export class FieldBuilder {
 
                     writer.write(`if (init.${fieldName} !== undefined) write(this.handle, `);
                     this.writers.ffiTypeWriter.toWriter(typeMapping.ffi)(writer);
-                    writer.writeLine(`, ${offset}, init.${fieldName});`);
+                    writer.writeLine(`, ${offset}, init.${fieldName}, { allowBorrowed: true });`);
                 }
             }
         };
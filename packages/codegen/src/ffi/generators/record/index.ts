@@ -518,7 +518,7 @@ export class RecordGenerator {
                         statements: (writer) => {
                             writer.write("write(this.handle, ");
                             this.writers.ffiTypeWriter.toWriter(typeMapping.ffi)(writer);
-                            writer.writeLine(`, ${offset}, value);`);
+                            writer.writeLine(`, ${offset}, value, { allowBorrowed: true });`);
                         },
                     });
                 }
@@ -585,7 +585,8 @@ export class RecordGenerator {
                 statements: (writer) => {
                     writer.writeLine(`const elementOffset = index * ${elementSize};`);
                     writer.writeLine(
-                        `writePointer(this.handle, ${ptrOffset}, elementOffset, value.handle, ${elementSize});`,
+                        `writePointer(this.handle, ${ptrOffset}, elementOffset, value.handle, ${elementSize}, ` +
+                            "{ allowBorrowed: true });",
                     );
                 },
             });
@@ -669,7 +670,9 @@ export class RecordGenerator {
 
                         writer.write(`write(this.handle, `);
                         this.writers.ffiTypeWriter.toWriter(nestedTypeMapping.ffi)(writer);
-                        writer.writeLine(`, ${nestedOffset}, value.get${capitalizedNestedFieldName}());`);
+                        writer.writeLine(
+                            `, ${nestedOffset}, value.get${capitalizedNestedFieldName}(), { allowBorrowed: true });`,
+                        );
                     }
                 },
             });
@@ -208,6 +208,27 @@ describe("RecordGenerator", () => {
             expect(code).toContain("setX(value");
         });
 
+        it("lets setters write into borrowed records", () => {
+            const { generator, sourceFile } = createTestSetup();
+            const record = createNormalizedRecord({
+                name: "Rectangle",
+                glibTypeName: "GdkRectangle",
+                fields: [
+                    createNormalizedField({
+                        name: "x",
+                        type: createNormalizedType({ name: "gint" }),
+                        readable: true,
+                        writable: true,
+                    }),
+                ],
+            });
+
+            generator.generateToSourceFile(record, sourceFile);
+
+            const code = getGeneratedCode(sourceFile);
+            expect(code).toContain("value, { allowBorrowed: true });");
+        });
+
         it("converts field names to camelCase", () => {
             const { generator, sourceFile } = createTestSetup();
             const record = createNormalizedRecord({
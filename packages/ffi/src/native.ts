@@ -7,6 +7,7 @@ import {
     write as nativeWrite,
     writePointer as nativeWritePointer,
     type Type,
+    type WriteOptions,
 } from "@gtkx/native";
 import type { GError } from "./generated/glib/error.js";
 import { typeCheckInstanceIsA, typeFromName } from "./generated/gobject/functions.js";
@@ -174,11 +175,12 @@ export const readPointer = (handle: unknown, ptrOffset: number, elementOffset: n
  * @param type - Type descriptor for marshaling
  * @param offset - Byte offset within the memory region
  * @param value - Value to write
+ * @param options - Write options
  * @throws If runtime not started
  */
-export const write = (handle: unknown, type: Type, offset: number, value: unknown): void => {
+export const write = (handle: unknown, type: Type, offset: number, value: unknown, options?: WriteOptions): void => {
     ensureIsStarted("attempted write");
-    nativeWrite(handle, type, offset, value);
+    nativeWrite(handle, type, offset, value, options);
 };
 
 /**
@@ -189,6 +191,7 @@ export const write = (handle: unknown, type: Type, offset: number, value: unknow
  * @param elementOffset - Byte offset from the dereferenced pointer
  * @param sourceHandle - Handle to the source data
  * @param size - Number of bytes to copy
+ * @param options - Write options
  * @throws If runtime not started
  */
 export const writePointer = (
@@ -197,7 +200,8 @@ export const writePointer = (
     elementOffset: number,
     sourceHandle: unknown,
     size: number,
+    options?: WriteOptions,
 ): void => {
     ensureIsStarted("attempted writePointer");
-    nativeWritePointer(destHandle, ptrOffset, elementOffset, sourceHandle, size);
+    nativeWritePointer(destHandle, ptrOffset, elementOffset, sourceHandle, size, options);
 };
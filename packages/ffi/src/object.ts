@@ -1,10 +1,12 @@
-import type { NativeHandle } from "@gtkx/native";
+import { type NativeHandle, release } from "@gtkx/native";
 
 /**
  * Base class for all GTK/GLib object wrappers.
  *
  * Provides common functionality for native object representation including
- * type metadata and equality comparison.
+ * type metadata and equality comparison. Wrappers are disposable, so a
+ * `using` declaration releases the native handle when the block exits
+ * instead of waiting for garbage collection.
  *
  * @see {@link getNativeObject} for creating wrapper instances
  */
@@ -22,6 +24,15 @@ export abstract class NativeObject {
     constructor(..._args: any[]) {
         this.handle = undefined as unknown as NativeHandle;
     }
+
+    /**
+     * Releases the underlying native handle immediately.
+     *
+     * Any later use of this wrapper throws a "has been released" error.
+     */
+    [Symbol.dispose](): void {
+        release(this.handle);
+    }
 }
 
 /**
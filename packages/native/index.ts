@@ -1,6 +1,26 @@
 import { createRequire } from "node:module";
 import { arch, platform } from "node:os";
-import type { Arg, CallbackType, NativeHandle, Ref, Type } from "./types.js";
+import type {
+    Arg,
+    CallbackType,
+    CallOptions,
+    EnumValues,
+    FunctionParameter,
+    FunctionSignature,
+    HandleDiff,
+    HandleInfo,
+    HandleSnapshot,
+    NativeHandle,
+    Ref,
+    StopOptions,
+    StructField,
+    StructLayout,
+    TraceFormat,
+    TracingOptions,
+    Type,
+    WatchdogOptions,
+    WriteOptions,
+} from "./types.js";
 
 const require = createRequire(import.meta.url);
 
@@ -57,14 +77,162 @@ export function createRef<T>(value: T): Ref<T> {
  * This is the core FFI mechanism. Most code should use the generated
  * bindings in `@gtkx/ffi` instead of calling this directly.
  *
+ * Called with a namespace and function name instead of a library and symbol,
+ * the signature is read from the namespace's GObject Introspection typelib
+ * (see {@link describeFunction}) and `values` are the in and inout arguments
+ * in order, starting with the instance for methods. Array lengths are filled
+ * in, a `GError` is thrown as an `Error` with its `domain` and `code`, and
+ * out-parameters are returned: alone when the function returns nothing,
+ * otherwise as `[returnValue, ...outValues]`.
+ *
  * @param library - Shared library name (e.g., "libgtk-4.so.1")
  * @param symbol - Function symbol name
  * @param args - Function arguments with type information
  * @param returnType - Expected return type
- * @returns The function return value
+ * @param options - Optional call options, e.g. `{ thread: "worker" }`
+ * @returns The function return value, or a Promise of the `_finish` result when
+ * `options.finish` is set
+ *
+ * @example
+ * ```typescript
+ * const info = await call(
+ *     GIO_LIB,
+ *     "g_file_query_info_async",
+ *     [fileArg, attributesArg, flagsArg, priorityArg],
+ *     { type: "undefined" },
+ *     { finish: { symbol: "g_file_query_info_finish", resultType: FILE_INFO }, signal },
+ * );
+ *
+ * call("Gtk-4.0", "Widget.set_halign", [button, GTK_ALIGN_CENTER]);
+ * const [contents, etag] = call("Gio", "File.load_contents", [file, null]);
+ * ```
+ */
+export function call(library: string, symbol: string, args: Arg[], returnType: Type, options?: CallOptions): unknown;
+export function call(namespace: string, name: string, values?: unknown[], options?: CallOptions): unknown;
+export function call(
+    library: string,
+    symbol: string,
+    args: unknown[] = [],
+    returnType?: Type | CallOptions,
+    options?: CallOptions,
+): unknown {
+    if (returnType === undefined || !("type" in returnType)) {
+        return callIntrospected(library, symbol, args, returnType);
+    }
+
+    return native.call(library, symbol, args, returnType, options);
+}
+
+const signatures = new Map<string, FunctionSignature>();
+
+const GERROR_TYPE: Type = {
+    type: "ref",
+    innerType: {
+        type: "boxed",
+        ownership: "full",
+        innerType: "GError",
+        library: "libglib-2.0.so.0",
+        getTypeFn: "g_error_get_type",
+    },
+};
+
+let gerrorLayout: StructLayout | undefined;
+
+function callIntrospected(namespace: string, name: string, values: unknown[], options?: CallOptions): unknown {
+    const key = `${namespace}:${name}`;
+    let signature = signatures.get(key);
+    if (!signature) {
+        signature = describeFunction(namespace, name);
+        signatures.set(key, signature);
+    }
+
+    const { library, symbol, parameters, returnType, throws } = signature;
+    const args: Arg[] = [];
+    const outs: Ref<unknown>[] = [];
+    let next = 0;
+
+    for (const parameter of parameters) {
+        let value: unknown;
+
+        if (isDerivedLength(parameter, parameters)) {
+            value = parameter.direction === "in" ? 0 : createRef(0);
+        } else if (parameter.direction === "in") {
+            value = values[next++];
+        } else {
+            const ref = createRef(parameter.direction === "inout" ? values[next++] : null);
+            outs.push(ref);
+            value = ref;
+        }
+
+        args.push({ type: parameter.type, value, optional: parameter.nullable });
+    }
+
+    for (const [index, parameter] of parameters.entries()) {
+        const arrayIndex = parameter.lengthOf ?? -1;
+        if (arrayIndex < 0 || parameters[arrayIndex]?.direction !== "in") {
+            continue;
+        }
+
+        const items = (args[arrayIndex] as Arg).value as ArrayLike<unknown> | null | undefined;
+        const length = items?.length ?? 0;
+        (args[index] as Arg).value = parameter.direction === "in" ? length : createRef(length);
+    }
+
+    const error = createRef<unknown>(null);
+    if (throws) {
+        args.push({ type: GERROR_TYPE, value: error });
+    }
+
+    const result = native.call(library, symbol, args, returnType, options);
+
+    if (error.value) {
+        gerrorLayout ??= defineStruct(
+            [
+                { name: "domain", type: { type: "int", size: 32, unsigned: true } },
+                { name: "code", type: { type: "int", size: 32, unsigned: false } },
+                { name: "message", type: { type: "string", ownership: "borrowed" } },
+            ],
+            "GError",
+        );
+        throw toError(error.value, gerrorLayout);
+    }
+
+    const results: unknown[] = returnType.type === "undefined" ? [] : [result];
+    results.push(...outs.map((out) => out.value));
+    return results.length > 1 ? results : results[0];
+}
+
+/**
+ * Converts a `GError` into an `Error` carrying its `domain` and `code`, as
+ * rejected async calls do, then releases the `GError`.
  */
-export function call(library: string, symbol: string, args: Arg[], returnType: Type): unknown {
-    return native.call(library, symbol, args, returnType);
+function toError(gerror: unknown, layout: StructLayout): Error {
+    const quark = read(gerror, layout, "domain");
+    const error = Object.assign(new Error(read(gerror, layout, "message") as string), {
+        domain: native.call(
+            "libglib-2.0.so.0",
+            "g_quark_to_string",
+            [{ type: { type: "int", size: 32, unsigned: true }, value: quark }],
+            { type: "string", ownership: "borrowed" },
+        ) as string,
+        code: read(gerror, layout, "code") as number,
+    });
+    release(gerror);
+    return error;
+}
+
+/**
+ * Whether a length parameter is filled in rather than taken from the caller.
+ * Lengths of input arrays are derived from the array, and output lengths are
+ * hidden behind the array they describe. The length of an output buffer, as
+ * in `g_input_stream_read`, is the caller's to choose.
+ */
+function isDerivedLength(parameter: FunctionParameter, parameters: FunctionParameter[]): boolean {
+    if (parameter.lengthOf === undefined) {
+        return false;
+    }
+
+    return parameter.direction === "out" || parameters[parameter.lengthOf]?.direction === "in";
 }
 
 /**
@@ -83,34 +251,193 @@ export function start(appId: string, flags?: number): unknown {
 /**
  * Stops the GTK runtime.
  *
+ * By default native handles and loaded libraries are leaked and reclaimed at
+ * process exit. With `teardown: true`, every handle is released on the GTK
+ * thread while the main loop still runs, and libraries are closed after it
+ * exits, except those whose name contains an entry of `keepLibraries`
+ * (WebKit and JavaScriptCore are always kept).
+ *
+ * @param options - Optional shutdown options
+ *
  * @internal Use `@gtkx/ffi` stop() instead
  */
-export function stop(): void {
-    native.stop();
+export function stop(options?: StopOptions): void {
+    native.stop(options);
 }
 
 /**
  * Reads a value from native memory.
  *
+ * A `struct` type with a `size` reads an embedded struct as a borrowed handle
+ * that keeps the parent alive, and a `fixed` array reads an inline C array.
+ * Throws if the field extends past the end of an allocation whose size is known.
+ *
+ * Fields of a {@link defineStruct} layout can be addressed by name instead of
+ * by type and offset.
+ *
  * @param handle - Native handle pointing to the memory
- * @param type - Type of value to read
- * @param offset - Byte offset from the handle pointer
+ * @param type - Type of value to read, or a struct layout
+ * @param offset - Byte offset from the handle pointer, or a field name of the layout
  * @returns The read value
  */
-export function read(handle: unknown, type: Type, offset: number): unknown {
-    return native.read(handle, type, offset);
+export function read(handle: unknown, type: Type, offset: number): unknown;
+export function read(handle: unknown, layout: StructLayout, field: string): unknown;
+export function read(handle: unknown, type: Type | StructLayout, offset: number | string): unknown {
+    const field = resolveField(type, offset);
+    return native.read(handle, field.type, field.offset);
 }
 
 /**
  * Writes a value to native memory.
  *
+ * Strings, GObjects, boxed values and fundamentals are stored as pointers. With
+ * `full` ownership the struct keeps its own copy or reference and the previous
+ * value is released; `borrowed` values are stored as is, except strings, which
+ * can only be written as `null`. A `struct` type with a `size` is copied into the field inline,
+ * and a `fixed` array is written element by element from a JS array.
+ *
+ * Throws if the field extends past the end of an allocation whose size is known,
+ * or if the handle borrows memory owned elsewhere and `allowBorrowed` is not set.
+ *
+ * Fields of a {@link defineStruct} layout can be addressed by name instead of
+ * by type and offset.
+ *
  * @param handle - Native handle pointing to the memory
- * @param type - Type of value to write
- * @param offset - Byte offset from the handle pointer
+ * @param type - Type of value to write, or a struct layout
+ * @param offset - Byte offset from the handle pointer, or a field name of the layout
  * @param value - Value to write
+ * @param options - Write options
+ */
+export function write(handle: unknown, type: Type, offset: number, value: unknown, options?: WriteOptions): void;
+export function write(
+    handle: unknown,
+    layout: StructLayout,
+    field: string,
+    value: unknown,
+    options?: WriteOptions,
+): void;
+export function write(
+    handle: unknown,
+    type: Type | StructLayout,
+    offset: number | string,
+    value: unknown,
+    options?: WriteOptions,
+): void {
+    const field = resolveField(type, offset);
+    native.write(handle, field.type, field.offset, value, options);
+}
+
+/**
+ * Computes the C layout of a struct for the current platform.
+ *
+ * Offsets, padding and the total size follow the C ABI, so they are correct on
+ * every supported architecture. Pass the layout's `size` to {@link alloc}, its
+ * field names to {@link read} and {@link write}, and its `type` as the type of
+ * a field to embed it in another struct.
+ *
+ * A field with `bits` is a C bitfield of that many bits, packed with its
+ * neighbors as the C compiler does. Its layout `type` carries the `bitOffset`
+ * and `bitWidth` within the storage unit at its `offset`.
+ *
+ * @example
+ * ```ts
+ * const Rectangle = defineStruct([
+ *     { name: "x", type: { type: "int", size: 32, unsigned: false } },
+ *     { name: "y", type: { type: "int", size: 32, unsigned: false } },
+ *     { name: "label", type: { type: "string", ownership: "full" } },
+ * ], "Rectangle");
+ *
+ * const rect = alloc(Rectangle.size);
+ * write(rect, Rectangle, "y", 10);
+ * ```
+ *
+ * @param fields - Fields in declaration order
+ * @param name - Struct name, used in error messages and the layout's `type`
+ * @returns The computed layout
+ */
+export function defineStruct(fields: StructField[], name?: string): StructLayout {
+    return native.defineStruct(fields, name);
+}
+
+/**
+ * Computes the C layout of a union for the current platform.
+ *
+ * Every field starts at offset 0, and the union is as large as its largest
+ * field rounded up to its alignment. Embed the layout's `type` in a struct
+ * and read that field to get a view whose variants are read and written by
+ * name.
+ *
+ * @example
+ * ```ts
+ * const Data = defineUnion([
+ *     { name: "v_int", type: { type: "int", size: 32, unsigned: false } },
+ *     { name: "v_double", type: { type: "float", size: 64 } },
+ * ], "Data");
+ * const Value = defineStruct([
+ *     { name: "g_type", type: { type: "int", size: 64, unsigned: true } },
+ *     { name: "data", type: Data.type },
+ * ]);
+ * ```
+ *
+ * @param fields - Variants of the union
+ * @param name - Union name, used in error messages and the layout's `type`
+ * @returns The computed layout
+ */
+export function defineUnion(fields: StructField[], name?: string): StructLayout {
+    return native.defineUnion(fields, name);
+}
+
+/**
+ * Lists the members of an enum or flags type.
+ *
+ * Useful for tooling that shows or generates the nicknames accepted by
+ * `symbolic` integer types.
+ *
+ * @example
+ * ```ts
+ * const align = enumValues("libgtk-4.so.1", "gtk_align_get_type");
+ * // { typeName: "GtkAlign", flags: false, values: [{ name: "GTK_ALIGN_FILL", nick: "fill", value: 0 }, ...] }
+ * ```
+ *
+ * @param library - Library containing the get-type function
+ * @param getTypeFn - Name of the type's `*_get_type` function
+ * @returns The type name and its members in declaration order
+ */
+export function enumValues(library: string, getTypeFn: string): EnumValues {
+    return native.enumValues(library, getTypeFn);
+}
+
+/**
+ * Reads a function's signature from its GObject Introspection typelib.
+ *
+ * Requires libgirepository and the namespace's typelib at runtime. `call`
+ * uses it when given a namespace instead of a library, caching the result.
+ *
+ * @example
+ * ```ts
+ * const signature = describeFunction("Gtk-4.0", "Widget.set_halign");
+ * // { library: "libgtk-4.so.1", symbol: "gtk_widget_set_halign", parameters: [...], ... }
+ * ```
+ *
+ * @param namespace - Typelib namespace, optionally versioned: `Gtk` or `Gtk-4.0`
+ * @param name - Function name, `Type.method`, or C symbol
+ * @returns The function's library, symbol and argument types
  */
-export function write(handle: unknown, type: Type, offset: number, value: unknown): void {
-    native.write(handle, type, offset, value);
+export function describeFunction(namespace: string, name: string): FunctionSignature {
+    return native.describeFunction(namespace, name);
+}
+
+function resolveField(type: Type | StructLayout, offset: number | string): { type: Type; offset: number } {
+    if (typeof offset === "number") {
+        return { type: type as Type, offset };
+    }
+
+    const { fields } = type as StructLayout;
+    if (!Object.hasOwn(fields, offset)) {
+        throw new Error(`Unknown struct field '${offset}'`);
+    }
+
+    return fields[offset] as { type: Type; offset: number };
 }
 
 /**
@@ -137,6 +464,43 @@ export function getNativeId(handle: unknown): number {
     return native.getNativeId(handle);
 }
 
+/**
+ * Releases a native handle immediately instead of waiting for garbage collection.
+ *
+ * Drops the underlying GObject reference or frees the boxed memory on the GTK
+ * thread. Any later use of the handle throws a "has been released" error.
+ * Releasing an already-released handle is a no-op.
+ *
+ * @param handle - Native handle to release
+ * @returns `true` if the handle was live and has been released, `false` otherwise
+ */
+export function release(handle: unknown): boolean {
+    return native.release(handle);
+}
+
+/**
+ * Wraps a native handle in a disposable so it is released at the end of a `using` block.
+ *
+ * @example
+ * ```tsx
+ * {
+ *   using texture = disposable(call(GDK_LIB, "gdk_texture_new_from_file", args, type));
+ *   draw(texture.handle);
+ * } // texture released here
+ * ```
+ *
+ * @param handle - Native handle to wrap
+ * @returns A disposable object exposing the handle
+ */
+export function disposable<T>(handle: T): { readonly handle: T } & Disposable {
+    return {
+        handle,
+        [Symbol.dispose]() {
+            release(handle);
+        },
+    };
+}
+
 /**
  * Reads a value from memory pointed to by a pointer field.
  *
@@ -146,7 +510,7 @@ export function getNativeId(handle: unknown): number {
  * @param handle - Native handle pointing to the parent struct
  * @param ptrOffset - Byte offset of the pointer field in the parent
  * @param elementOffset - Byte offset from the dereferenced pointer
- * @returns Native handle pointing to the element (borrowed, non-owning)
+ * @returns Native handle pointing to the element (borrowed, keeps `handle` alive)
  */
 export function readPointer(handle: unknown, ptrOffset: number, elementOffset: number): unknown {
     return native.readPointer(handle, ptrOffset, elementOffset);
@@ -156,13 +520,15 @@ export function readPointer(handle: unknown, ptrOffset: number, elementOffset: n
  * Writes a struct value to memory pointed to by a pointer field.
  *
  * Used for setting array elements. Copies the data from source to the
- * destination array element.
+ * destination array element. The same bounds and ownership checks as
+ * {@link write} apply to the parent struct and the source.
  *
  * @param destHandle - Native handle pointing to the parent struct containing the pointer
  * @param ptrOffset - Byte offset of the pointer field in the parent
  * @param elementOffset - Byte offset from the dereferenced pointer (index * elementSize)
  * @param sourceHandle - Native handle of the struct to copy from
  * @param size - Size in bytes of the struct to copy
+ * @param options - Write options
  */
 export function writePointer(
     destHandle: unknown,
@@ -170,8 +536,198 @@ export function writePointer(
     elementOffset: number,
     sourceHandle: unknown,
     size: number,
+    options?: WriteOptions,
 ): void {
-    native.writePointer(destHandle, ptrOffset, elementOffset, sourceHandle, size);
+    native.writePointer(destHandle, ptrOffset, elementOffset, sourceHandle, size, options);
+}
+
+/**
+ * Adds a GLib timeout source that runs on the GTK main loop.
+ *
+ * Unlike `setTimeout`, the callback is dispatched from the GLib main context,
+ * in step with the frame clock.
+ *
+ * @param ms - Interval in milliseconds
+ * @param callback - Invoked on each interval; return `true` to keep the source
+ * @param priority - GLib source priority (defaults to `G_PRIORITY_DEFAULT`)
+ * @returns The GLib source ID
+ */
+export function addTimeout(ms: number, callback: () => boolean | undefined, priority?: number): number {
+    return native.addTimeout(ms, callback, priority);
+}
+
+/**
+ * Adds a GLib idle source that runs when the GTK main loop has nothing
+ * of higher priority to do.
+ *
+ * @param callback - Invoked when idle; return `true` to keep the source
+ * @param priority - GLib source priority (defaults to `G_PRIORITY_DEFAULT_IDLE`)
+ * @returns The GLib source ID
+ */
+export function addIdle(callback: () => boolean | undefined, priority?: number): number {
+    return native.addIdle(callback, priority);
+}
+
+/**
+ * Watches a Unix file descriptor from the GTK main loop.
+ *
+ * @param fd - File descriptor to watch
+ * @param conditions - `GIOCondition` bits to watch for (`G_IO_IN` = 1, `G_IO_OUT` = 4, ...)
+ * @param callback - Invoked with the fd and the ready conditions; return `true` to keep watching
+ * @param priority - GLib source priority (defaults to `G_PRIORITY_DEFAULT`)
+ * @returns The GLib source ID
+ */
+export function addUnixFdWatch(
+    fd: number,
+    conditions: number,
+    callback: (fd: number, conditions: number) => boolean | undefined,
+    priority?: number,
+): number {
+    return native.addUnixFdWatch(fd, conditions, callback, priority);
+}
+
+/**
+ * Removes a source added with {@link addTimeout}, {@link addIdle} or
+ * {@link addUnixFdWatch}.
+ *
+ * @param id - The GLib source ID
+ * @returns `true` if the source was still installed and has been removed
+ */
+export function removeSource(id: number): boolean {
+    return native.removeSource(id);
+}
+
+/**
+ * Configures the JS ⇄ GTK deadlock watchdog.
+ *
+ * When both the JS and GTK threads have been blocked on each other for longer
+ * than `timeoutMs`, the pending call and signal chain is printed to stderr.
+ * With `abort: true` the process then aborts instead of hanging. Defaults come
+ * from the `GTKX_WATCHDOG_MS` and `GTKX_WATCHDOG_ABORT` environment variables.
+ *
+ * @param options - Watchdog options; a missing or zero `timeoutMs` disables it
+ */
+export function configureWatchdog(options: WatchdogOptions): void {
+    native.configureWatchdog(options);
+}
+
+/**
+ * Enables or disables FFI call tracing.
+ *
+ * While enabled, each {@link call} is recorded in a ring buffer with its
+ * library, symbol, arguments, return value or error, queue wait and native
+ * duration. The buffer is written to stderr if the process receives a fatal
+ * signal. Tracing can also be enabled with the `GTKX_TRACE` environment
+ * variable, set to `1` or to the buffer capacity.
+ *
+ * @param options - Tracing options; `enabled` defaults to `true`
+ */
+export function configureTracing(options: TracingOptions): void {
+    native.configureTracing(options);
+}
+
+/**
+ * Exports the recorded calls, oldest first.
+ *
+ * @param format - `"json"` for an array of entries, or `"chrome"` for the
+ * Trace Event Format understood by Perfetto and `chrome://tracing`
+ * @returns The serialized trace
+ */
+export function dumpTrace(format: TraceFormat = "json"): string {
+    return native.dumpTrace(format);
+}
+
+/**
+ * Discards all recorded calls.
+ */
+export function clearTrace(): void {
+    native.clearTrace();
+}
+
+/**
+ * Enables or disables crash reports for fatal signals.
+ *
+ * While enabled, a `SIGSEGV`, `SIGBUS`, `SIGILL`, `SIGFPE` or `SIGABRT` prints
+ * the native call, field access and callback in flight on the crashing thread,
+ * with a backtrace of that thread, before the signal is re-raised. Can also be enabled
+ * with the `GTKX_CRASH_HANDLER` environment variable.
+ *
+ * @param enabled - Whether crash reports are printed
+ */
+export function configureCrashHandler(enabled: boolean): void {
+    native.configureCrashHandler(enabled);
+}
+
+/**
+ * Captures a snapshot of every live native handle.
+ *
+ * Reports per-kind counts (GObjects and boxed values by type name, fundamentals
+ * in total) and, for each handle, its type, GObject reference count and age.
+ * When the `GTKX_DEBUG_HANDLES` environment variable is set, each handle also
+ * carries the JavaScript stack that created it.
+ *
+ * @returns Snapshot of the handle map
+ */
+export function debugHandles(): HandleSnapshot {
+    return native.debugHandles();
+}
+
+/**
+ * Compares two handle snapshots.
+ *
+ * Handles are matched by ID, so `added` lists handles created after `before`
+ * was taken that are still alive, and `removed` lists handles released or
+ * garbage collected in between. `counts` holds the per-type change for every
+ * type whose count differs.
+ *
+ * @param before - Earlier snapshot
+ * @param after - Later snapshot
+ * @returns Difference between the snapshots
+ */
+export function diffHandles(before: HandleSnapshot, after: HandleSnapshot): HandleDiff {
+    const beforeIds = new Set(before.handles.map((handle) => handle.id));
+    const afterIds = new Set(after.handles.map((handle) => handle.id));
+
+    const diffCounts = (a: Record<string, number>, b: Record<string, number>): Record<string, number> => {
+        const result: Record<string, number> = {};
+        for (const typeName of new Set([...Object.keys(a), ...Object.keys(b)])) {
+            const delta = (b[typeName] ?? 0) - (a[typeName] ?? 0);
+            if (delta !== 0) {
+                result[typeName] = delta;
+            }
+        }
+        return result;
+    };
+
+    return {
+        added: after.handles.filter((handle) => !beforeIds.has(handle.id)),
+        removed: before.handles.filter((handle) => !afterIds.has(handle.id)),
+        counts: {
+            gobject: diffCounts(before.counts.gobject, after.counts.gobject),
+            boxed: diffCounts(before.counts.boxed, after.counts.boxed),
+            fundamental: after.counts.fundamental - before.counts.fundamental,
+        },
+    };
 }
 
-export type { NativeHandle, Ref, Arg, Type, CallbackType };
+export type {
+    NativeHandle,
+    Ref,
+    Arg,
+    Type,
+    CallbackType,
+    HandleInfo,
+    HandleSnapshot,
+    HandleDiff,
+    StopOptions,
+    StructField,
+    StructLayout,
+    EnumValues,
+    FunctionParameter,
+    FunctionSignature,
+    CallOptions,
+    WatchdogOptions,
+    TracingOptions,
+    TraceFormat,
+    WriteOptions,
+};
@@ -12,10 +12,19 @@
 //!
 //! The `optional` flag allows null/undefined values for otherwise required types,
 //! converting them to appropriate defaults (null pointers, zero values).
+//!
+//! An `AbortSignal` passed for a `cancellable` argument adds an `abort`
+//! listener, which the caller collects to remove once the call completes.
 
 use neon::{object::Object as _, prelude::*};
 
-use crate::{types::Type, value::Value};
+use crate::{
+    types::{
+        Type,
+        cancellable::{self, AbortListener},
+    },
+    value::Value,
+};
 
 #[derive(Debug, Clone)]
 pub struct Arg {
@@ -33,26 +42,40 @@ impl Arg {
         }
     }
 
+    /// Parses a JS array of arguments, pushing the `abort` listeners of their
+    /// `AbortSignal`s to `listeners`.
     pub fn from_js_array(
         cx: &mut FunctionContext,
         value: Handle<JsArray>,
+        listeners: &mut Vec<AbortListener>,
     ) -> NeonResult<Vec<Self>> {
         let array = value.to_vec(cx)?;
         let mut args = Vec::with_capacity(array.len());
 
         for item in array {
-            args.push(Self::from_js_value(cx, item)?);
+            args.push(Self::from_js_value(cx, item, listeners)?);
         }
 
         Ok(args)
     }
 
-    pub fn from_js_value(cx: &mut FunctionContext, value: Handle<JsValue>) -> NeonResult<Self> {
+    pub fn from_js_value(
+        cx: &mut FunctionContext,
+        value: Handle<JsValue>,
+        listeners: &mut Vec<AbortListener>,
+    ) -> NeonResult<Self> {
         let obj = value.downcast::<JsObject, _>(cx).or_throw(cx)?;
         let type_prop: Handle<'_, JsValue> = obj.prop(cx, "type").get()?;
         let value_prop: Handle<'_, JsValue> = obj.prop(cx, "value").get()?;
         let ty = Type::from_js_value(cx, type_prop)?;
-        let value = Value::from_js_value(cx, value_prop)?;
+        let value = match ty {
+            Type::Cancellable => {
+                let (value, listener) = cancellable::value_from_js(cx, value_prop)?;
+                listeners.extend(listener);
+                value
+            }
+            _ => Value::from_js_value(cx, value_prop)?,
+        };
 
         let optional = {
             let optional_prop: Result<Handle<JsValue>, _> = obj.prop(cx, "optional").get();
@@ -0,0 +1,296 @@
+//! Promise-based completion of GIO `_async`/`_finish` function pairs.
+//!
+//! A call made with the `finish` option passes an [`AsyncCompletion`] as the
+//! `user_data` of its `GAsyncReadyCallback`. When the operation completes,
+//! [`crate::trampoline::async_finish_trampoline`] calls the matching `_finish`
+//! function on the GTK thread, decodes its result and settles a JavaScript
+//! Promise through a [`Channel`].
+//!
+//! ## Calling Convention
+//!
+//! The `_async` function must follow the GIO convention of ending with
+//! `(GCancellable *cancellable, GAsyncReadyCallback callback, gpointer user_data)`.
+//! These three arguments are appended automatically. The `_finish` function is
+//! called as
+//!
+//! ```text
+//! finish([GObject *source,] GAsyncResult *result, ...args, GError **error)
+//! ```
+//!
+//! The source object is omitted for static finish functions such as
+//! `g_bus_get_finish`, and `args` are the declared finish arguments, usually
+//! `Ref` out-parameters such as the contents and length filled in by
+//! `g_file_load_contents_finish`. Their refs are updated before the Promise
+//! resolves, and a `sizeParamIndex` among them counts from the first declared
+//! argument.
+//!
+//! ## Errors and Cancellation
+//!
+//! A `GError` set by `_finish` rejects the Promise with an `Error` carrying the
+//! error `domain` and `code`. `G_IO_ERROR_CANCELLED` rejects with an error named
+//! `AbortError`, matching what `AbortSignal` consumers expect.
+
+use std::ffi::{CStr, c_void};
+use std::ops::Deref as _;
+use std::sync::{Arc, Mutex};
+
+use gtk4::gio;
+use gtk4::glib::{self, translate::ToGlibPtr as _};
+use libffi::middle as libffi;
+use neon::prelude::*;
+use neon::types::Deferred;
+
+use crate::{
+    arg::Arg,
+    ffi,
+    state::GtkThreadState,
+    types::{Type, cancellable::AbortListener},
+    value::{RefUpdate, Value},
+};
+
+/// A Promise and the `abort` listeners of the call's signals, which are
+/// removed when the Promise settles.
+pub struct Settlement {
+    pub deferred: Deferred,
+    pub abort_listeners: Vec<AbortListener>,
+}
+
+/// A Promise that is settled by whichever side finishes first: the completion
+/// trampoline, or the JS thread when the `_async` call itself fails.
+pub type PendingPromise = Arc<Mutex<Option<Settlement>>>;
+
+#[derive(Debug, Clone)]
+pub struct FinishOptions {
+    pub symbol_name: String,
+    pub result_type: Type,
+    /// Whether the `_finish` function takes the source object first.
+    pub has_instance: bool,
+    /// Arguments between the `GAsyncResult` and the `GError **`.
+    pub args: Vec<Arg>,
+    pub cancellable: Option<gio::Cancellable>,
+}
+
+#[derive(Debug)]
+pub enum AsyncError {
+    Cancelled(String),
+    GError {
+        domain: String,
+        code: i32,
+        message: String,
+    },
+    Other(String),
+}
+
+impl AsyncError {
+    fn to_js_error<'a, C: Context<'a>>(&self, cx: &mut C) -> JsResult<'a, JsError> {
+        match self {
+            AsyncError::Cancelled(message) => {
+                let error = cx.error(message)?;
+                let name = cx.string("AbortError");
+                error.set(cx, "name", name)?;
+                Ok(error)
+            }
+            AsyncError::GError {
+                domain,
+                code,
+                message,
+            } => {
+                let error = cx.error(message)?;
+                let domain = cx.string(domain);
+                error.set(cx, "domain", domain)?;
+                let code = cx.number(*code);
+                error.set(cx, "code", code)?;
+                Ok(error)
+            }
+            AsyncError::Other(message) => cx.error(message),
+        }
+    }
+}
+
+pub struct AsyncCompletion {
+    library_name: String,
+    finish: FinishOptions,
+    promise: PendingPromise,
+    channel: Channel,
+}
+
+impl AsyncCompletion {
+    pub fn new(
+        library_name: String,
+        finish: FinishOptions,
+        promise: PendingPromise,
+        channel: Channel,
+    ) -> Self {
+        Self {
+            library_name,
+            finish,
+            promise,
+            channel,
+        }
+    }
+
+    pub fn cancellable_ptr(&self) -> *mut c_void {
+        self.finish
+            .cancellable
+            .as_ref()
+            .map_or(std::ptr::null_mut(), |cancellable| {
+                let ptr: *mut gio::ffi::GCancellable = cancellable.to_glib_none().0;
+                ptr as *mut c_void
+            })
+    }
+
+    /// Calls the `_finish` function, returning its result and the updates for
+    /// its `Ref` arguments. Must be called on the GTK thread.
+    pub fn finish(
+        &self,
+        source: *mut c_void,
+        result: *mut c_void,
+    ) -> Result<(Value, Vec<RefUpdate>), AsyncError> {
+        let other = |err: anyhow::Error| AsyncError::Other(err.to_string());
+        let finish = &self.finish;
+
+        let symbol_ptr = GtkThreadState::with(|state| {
+            let library = state.library(&self.library_name)?;
+            // SAFETY: The symbol name comes from the FFI binding definitions.
+            let symbol = unsafe {
+                library.get::<unsafe extern "C" fn() -> ()>(finish.symbol_name.as_bytes())?
+            };
+            anyhow::Ok(libffi::CodePtr(*symbol.deref() as *mut c_void))
+        })
+        .map_err(other)?;
+
+        let mut arg_types = Vec::with_capacity(finish.args.len() + 3);
+        if finish.has_instance {
+            arg_types.push(libffi::Type::pointer());
+        }
+        arg_types.push(libffi::Type::pointer());
+        for arg in &finish.args {
+            arg.ty.append_ffi_arg_types(&mut arg_types);
+        }
+        arg_types.push(libffi::Type::pointer());
+
+        let cif = libffi::Builder::new()
+            .res((&finish.result_type).into())
+            .args(arg_types)
+            .into_cif();
+
+        let ffi_values = finish
+            .args
+            .iter()
+            .cloned()
+            .map(TryInto::<ffi::FfiValue>::try_into)
+            .collect::<anyhow::Result<Vec<ffi::FfiValue>>>()
+            .map_err(other)?;
+
+        let mut error: *mut glib::ffi::GError = std::ptr::null_mut();
+        let error_ptr = &mut error as *mut *mut glib::ffi::GError;
+
+        let mut args = Vec::with_capacity(ffi_values.len() + 3);
+        if finish.has_instance {
+            args.push(libffi::arg(&source));
+        }
+        args.push(libffi::arg(&result));
+        for ffi_value in &ffi_values {
+            ffi_value.append_libffi_args(&mut args);
+        }
+        args.push(libffi::arg(&error_ptr));
+
+        // SAFETY: The CIF was built from the finish descriptor: the optional
+        // source object, the GAsyncResult, the declared arguments and a GError
+        // out-parameter.
+        let value = unsafe { finish.result_type.call_cif(&cif, symbol_ptr, &args) };
+
+        if !error.is_null() {
+            // SAFETY: `_finish` set a valid GError that we now own.
+            return Err(unsafe { Self::take_error(error) });
+        }
+
+        let mut ref_updates = Vec::new();
+        for (i, arg) in finish.args.iter().enumerate() {
+            if let Value::Ref(ref_val) = &arg.value {
+                let new_value = Value::from_ffi_value_with_args(
+                    &ffi_values[i],
+                    &arg.ty,
+                    &ffi_values,
+                    &finish.args,
+                )
+                .map_err(other)?;
+                ref_updates.push((ref_val.js_obj.clone(), new_value));
+            }
+        }
+
+        let value =
+            Value::from_ffi_value_with_args(&value, &finish.result_type, &ffi_values, &finish.args)
+                .map_err(other)?;
+
+        Ok((value, ref_updates))
+    }
+
+    unsafe fn take_error(error: *mut glib::ffi::GError) -> AsyncError {
+        unsafe {
+            let domain = (*error).domain;
+            let code = (*error).code;
+            let message = if (*error).message.is_null() {
+                String::new()
+            } else {
+                CStr::from_ptr((*error).message)
+                    .to_string_lossy()
+                    .into_owned()
+            };
+
+            let cancelled =
+                domain == gio::ffi::g_io_error_quark() && code == gio::ffi::G_IO_ERROR_CANCELLED;
+            let domain = CStr::from_ptr(glib::ffi::g_quark_to_string(domain))
+                .to_string_lossy()
+                .into_owned();
+
+            glib::ffi::g_error_free(error);
+
+            if cancelled {
+                AsyncError::Cancelled(message)
+            } else {
+                AsyncError::GError {
+                    domain,
+                    code,
+                    message,
+                }
+            }
+        }
+    }
+
+    /// Updates the `Ref` arguments of `_finish` and resolves the Promise, or
+    /// rejects it, on the JS thread.
+    pub fn settle(self, outcome: Result<(Value, Vec<RefUpdate>), AsyncError>) {
+        let Some(Settlement {
+            deferred,
+            abort_listeners,
+        }) = self.promise.lock().unwrap().take()
+        else {
+            return;
+        };
+
+        deferred.settle_with(&self.channel, move |mut cx| {
+            for listener in abort_listeners {
+                listener.detach(&mut cx)?;
+            }
+
+            match outcome {
+                Ok((value, ref_updates)) => {
+                    for (js_obj, new_value) in ref_updates {
+                        let js_obj = js_obj.to_inner(&mut cx);
+                        let new_js_value = new_value.to_js_value(&mut cx)?;
+                        let mut prop = js_obj.prop(&mut cx, "value");
+
+                        prop.set(new_js_value)?;
+                    }
+
+                    value.to_js_value(&mut cx)
+                }
+                Err(err) => {
+                    let error = err.to_js_error(&mut cx)?;
+                    cx.throw(error)
+                }
+            }
+        });
+    }
+}
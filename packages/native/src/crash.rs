@@ -0,0 +1,396 @@
+//! Crash reporting for fatal signals.
+//!
+//! A bad pointer passed through `call` or `readPointer` otherwise kills the
+//! process with a bare segfault. When crash reporting is enabled, each thread
+//! keeps a stack of [`CrashScope`]s describing the native call or callback it
+//! is running, and the fatal signal handler prints that stack and a backtrace
+//! of the crashing thread before re-raising the signal.
+//!
+//! ## Configuration
+//!
+//! - `GTKX_CRASH_HANDLER`: When set to a non-empty value other than `0`, enable
+//!   crash reports at startup
+//!
+//! Crash reports can also be toggled at runtime through the
+//! `configureCrashHandler` export.
+//!
+//! ## Signal Handling
+//!
+//! Handlers for `SIGSEGV`, `SIGBUS`, `SIGILL`, `SIGFPE` and `SIGABRT` are
+//! installed the first time crash reports or [`crate::trace`] are enabled, and
+//! stay installed afterwards. On a fatal signal the handler writes the crash
+//! report and the trace buffer (each only if enabled), restores the previously
+//! installed handler and re-raises the signal, so Node's own handling and core
+//! dumps are unaffected.
+//!
+//! The handler must stay async-signal-safe, so each scope is rendered to text
+//! when it is entered and the handler only hands that text to `write(2)`.
+//! Frames are collected with glibc's `backtrace`, which is called once when
+//! the handlers are installed so the unwinder is already loaded, and written
+//! with `backtrace_symbols_fd`, which does not allocate. The handler runs on
+//! an alternate signal stack so stack overflows are reported too: one is
+//! registered for the installing thread and for each thread the first time it
+//! enters a scope, unless the thread already has one.
+
+use std::cell::RefCell;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::{arg::Arg, trace, types::Type};
+
+const CRASH_HANDLER_ENV: &str = "GTKX_CRASH_HANDLER";
+
+const FATAL_SIGNALS: [libc::c_int; 5] = [
+    libc::SIGSEGV,
+    libc::SIGBUS,
+    libc::SIGILL,
+    libc::SIGFPE,
+    libc::SIGABRT,
+];
+
+static ENABLED: OnceLock<AtomicBool> = OnceLock::new();
+static PREVIOUS_SIGNAL_ACTIONS: OnceLock<Vec<(libc::c_int, libc::sigaction)>> = OnceLock::new();
+static REPORTED: AtomicBool = AtomicBool::new(false);
+
+/// Space for a thread's rendered scope stack. Scopes that do not fit are
+/// reported as truncated.
+const SCOPE_TEXT_LEN: usize = 8192;
+/// Size of the alternate signal stacks registered for the handler.
+const ALT_STACK_SIZE: usize = 64 * 1024;
+
+/// Maximum number of frames in a crash backtrace.
+const BACKTRACE_FRAMES: usize = 64;
+
+const TRUNCATED: &[u8] = b"    ...\n";
+
+unsafe extern "C" {
+    fn backtrace(buffer: *mut *mut libc::c_void, size: libc::c_int) -> libc::c_int;
+    fn backtrace_symbols_fd(buffer: *const *mut libc::c_void, size: libc::c_int, fd: libc::c_int);
+}
+
+/// A thread's in-flight scopes, one line each, rendered ahead of time so the
+/// signal handler does not have to format anything.
+struct ScopeText {
+    bytes: [u8; SCOPE_TEXT_LEN],
+    len: usize,
+}
+
+impl ScopeText {
+    /// Appends a rendered line, cutting it short if the buffer is full.
+    fn push_line(&mut self, line: &str) {
+        let available = SCOPE_TEXT_LEN - self.len;
+
+        if line.len() < available {
+            self.bytes[self.len..self.len + line.len()].copy_from_slice(line.as_bytes());
+            self.bytes[self.len + line.len()] = b'\n';
+            self.len += line.len() + 1;
+        } else if TRUNCATED.len() <= available {
+            self.bytes[self.len..self.len + TRUNCATED.len()].copy_from_slice(TRUNCATED);
+            self.len += TRUNCATED.len();
+        }
+    }
+}
+
+/// An alternate signal stack registered for the current thread. `memory` is
+/// `None` when the thread already had one of its own.
+struct AltStack {
+    memory: Option<Box<[u8]>>,
+}
+
+impl AltStack {
+    fn register() -> Self {
+        // SAFETY: `sigaltstack` only reads the new stack description and
+        // writes the current one into `current`.
+        unsafe {
+            let mut current: libc::stack_t = std::mem::zeroed();
+            if libc::sigaltstack(std::ptr::null(), &mut current) == 0
+                && current.ss_flags & libc::SS_DISABLE == 0
+            {
+                return Self { memory: None };
+            }
+
+            let mut memory = vec![0u8; ALT_STACK_SIZE].into_boxed_slice();
+            let stack = libc::stack_t {
+                ss_sp: memory.as_mut_ptr().cast(),
+                ss_flags: 0,
+                ss_size: memory.len(),
+            };
+
+            if libc::sigaltstack(&stack, std::ptr::null_mut()) != 0 {
+                return Self { memory: None };
+            }
+
+            Self {
+                memory: Some(memory),
+            }
+        }
+    }
+}
+
+impl Drop for AltStack {
+    fn drop(&mut self) {
+        if self.memory.is_none() {
+            return;
+        }
+
+        // SAFETY: Disabling the stack before its memory is freed keeps the
+        // kernel from delivering signals onto freed memory.
+        unsafe {
+            let disable = libc::stack_t {
+                ss_sp: std::ptr::null_mut(),
+                ss_flags: libc::SS_DISABLE,
+                ss_size: 0,
+            };
+            libc::sigaltstack(&disable, std::ptr::null_mut());
+        }
+    }
+}
+
+thread_local! {
+    static SCOPE_TEXT: RefCell<ScopeText> = const {
+        RefCell::new(ScopeText {
+            bytes: [0; SCOPE_TEXT_LEN],
+            len: 0,
+        })
+    };
+    static ALT_STACK: RefCell<Option<AltStack>> = const { RefCell::new(None) };
+}
+
+/// Registers an alternate signal stack for the current thread, once.
+fn ensure_alt_stack() {
+    ALT_STACK.with(|stack| {
+        let mut stack = stack.borrow_mut();
+        if stack.is_none() {
+            *stack = Some(AltStack::register());
+        }
+    });
+}
+
+/// Removes its entry from the current thread's crash scope stack when dropped.
+#[must_use]
+pub struct CrashScope {
+    /// Length of the thread's scope text before this scope was entered.
+    previous_len: Option<usize>,
+}
+
+impl Drop for CrashScope {
+    fn drop(&mut self) {
+        if let Some(previous_len) = self.previous_len {
+            SCOPE_TEXT.with(|text| text.borrow_mut().len = previous_len);
+        }
+    }
+}
+
+fn enabled_flag() -> &'static AtomicBool {
+    ENABLED.get_or_init(|| {
+        let enabled =
+            std::env::var(CRASH_HANDLER_ENV).is_ok_and(|value| !value.is_empty() && value != "0");
+
+        if enabled {
+            install_signal_handlers();
+        }
+
+        AtomicBool::new(enabled)
+    })
+}
+
+pub fn is_enabled() -> bool {
+    enabled_flag().load(Ordering::Acquire)
+}
+
+/// Enables or disables crash reports.
+pub fn configure(enabled: bool) {
+    enabled_flag().store(enabled, Ordering::Release);
+
+    if enabled {
+        install_signal_handlers();
+    }
+}
+
+fn enter(line: impl FnOnce() -> String) -> CrashScope {
+    if !is_enabled() {
+        return CrashScope { previous_len: None };
+    }
+
+    ensure_alt_stack();
+    let line = line();
+
+    let previous_len = SCOPE_TEXT.with(|text| {
+        let mut text = text.borrow_mut();
+        let previous_len = text.len;
+        text.push_line(&line);
+        previous_len
+    });
+
+    CrashScope {
+        previous_len: Some(previous_len),
+    }
+}
+
+/// Records a native call on the current thread until the scope is dropped.
+pub fn enter_call(library: &str, symbol: &str, args: &[Arg], result_type: &Type) -> CrashScope {
+    enter(|| {
+        let arg_types: Vec<String> = args.iter().map(|arg| arg.ty.to_string()).collect();
+        format!(
+            "    call {library}::{symbol}({}) -> {result_type}",
+            arg_types.join(", ")
+        )
+    })
+}
+
+/// Records a native memory access, e.g. a field read, until the scope is dropped.
+pub fn enter_access(description: impl FnOnce() -> String) -> CrashScope {
+    enter(|| format!("    access {}", description()))
+}
+
+/// Records a trampoline invocation on the current thread until the scope is
+/// dropped.
+pub fn enter_callback(kind: &'static str) -> CrashScope {
+    enter(|| format!("    callback {kind}"))
+}
+
+/// Writes the crash report for the current thread to `out`. Only copies
+/// pre-rendered text, so it is safe to call from the signal handler.
+fn write_report(signal: libc::c_int, out: &mut dyn FnMut(&[u8])) {
+    out(b"[gtkx] CRASH: fatal signal ");
+    write_number(signal as u64, out);
+    out(b"\n");
+
+    let written = SCOPE_TEXT.try_with(|text| {
+        let Ok(text) = text.try_borrow() else {
+            out(b"  scope stack was being updated\n");
+            return;
+        };
+
+        if text.len == 0 {
+            out(b"  no native call in flight on this thread\n");
+        } else {
+            out(b"  in flight (innermost last):\n");
+            out(&text.bytes[..text.len]);
+        }
+    });
+
+    if written.is_err() {
+        out(b"  no native call in flight on this thread\n");
+    }
+}
+
+/// Formats the crash report for the current thread as the signal handler
+/// would write it, without the backtrace.
+pub fn report(signal: libc::c_int) -> String {
+    let mut report = Vec::new();
+    write_report(signal, &mut |bytes| report.extend_from_slice(bytes));
+    String::from_utf8_lossy(&report).into_owned()
+}
+
+pub(crate) fn install_signal_handlers() {
+    PREVIOUS_SIGNAL_ACTIONS.get_or_init(|| {
+        // The first `backtrace` call loads the unwinder, which is not
+        // async-signal-safe, so it must not happen in the handler.
+        let mut frames = [std::ptr::null_mut(); 1];
+        // SAFETY: `frames` has room for the one frame requested.
+        unsafe { backtrace(frames.as_mut_ptr(), 1) };
+
+        FATAL_SIGNALS
+            .iter()
+            .map(|&signal| {
+                // SAFETY: The action is fully initialized before being installed,
+                // and the handler only reads statics initialized beforehand.
+                unsafe {
+                    let mut action: libc::sigaction = std::mem::zeroed();
+                    action.sa_sigaction = handle_fatal_signal as libc::sighandler_t;
+                    action.sa_flags = libc::SA_ONSTACK;
+                    libc::sigemptyset(&mut action.sa_mask);
+
+                    let mut previous: libc::sigaction = std::mem::zeroed();
+                    libc::sigaction(signal, &action, &mut previous);
+                    (signal, previous)
+                }
+            })
+            .collect()
+    });
+
+    ensure_alt_stack();
+}
+
+/// Writes a backtrace of the current thread to stderr without allocating.
+fn write_backtrace() {
+    let mut frames = [std::ptr::null_mut(); BACKTRACE_FRAMES];
+
+    // SAFETY: `frames` has room for `BACKTRACE_FRAMES` frames, and only the
+    // `count` filled in are symbolized.
+    unsafe {
+        let count = backtrace(frames.as_mut_ptr(), BACKTRACE_FRAMES as libc::c_int);
+        if count > 0 {
+            write_stderr(b"  backtrace:\n");
+            backtrace_symbols_fd(frames.as_ptr(), count, libc::STDERR_FILENO);
+        }
+    }
+}
+
+/// Writes `bytes` to stderr using only `write(2)`, so it is safe to call from
+/// a signal handler.
+pub(crate) fn write_stderr(bytes: &[u8]) {
+    let mut rest = bytes;
+
+    while !rest.is_empty() {
+        // SAFETY: `rest` is valid for reads of `rest.len()` bytes.
+        let written = unsafe { libc::write(libc::STDERR_FILENO, rest.as_ptr().cast(), rest.len()) };
+
+        if written > 0 {
+            rest = &rest[written as usize..];
+        } else if written == 0
+            || std::io::Error::last_os_error().raw_os_error() != Some(libc::EINTR)
+        {
+            return;
+        }
+    }
+}
+
+/// Writes `n` in decimal to stderr without allocating.
+pub(crate) fn write_stderr_number(n: u64) {
+    write_number(n, &mut write_stderr);
+}
+
+fn write_number(mut n: u64, out: &mut dyn FnMut(&[u8])) {
+    let mut digits = [0u8; 20];
+    let mut start = digits.len();
+
+    loop {
+        start -= 1;
+        digits[start] = b'0' + (n % 10) as u8;
+        n /= 10;
+
+        if n == 0 {
+            break;
+        }
+    }
+
+    out(&digits[start..]);
+}
+
+extern "C" fn handle_fatal_signal(signal: libc::c_int) {
+    if !REPORTED.swap(true, Ordering::AcqRel) {
+        if ENABLED
+            .get()
+            .is_some_and(|enabled| enabled.load(Ordering::Acquire))
+        {
+            write_report(signal, &mut write_stderr);
+            write_backtrace();
+        }
+
+        trace::dump_on_signal(signal);
+    }
+
+    // Hand the signal back to whoever handled it before. It is blocked while
+    // this handler runs, so the re-raised signal is delivered on return.
+    if let Some(previous) = PREVIOUS_SIGNAL_ACTIONS.get()
+        && let Some((_, action)) = previous.iter().find(|(sig, _)| *sig == signal)
+    {
+        // SAFETY: `action` was filled in by `sigaction` when installing.
+        unsafe {
+            libc::sigaction(signal, action, std::ptr::null_mut());
+            libc::raise(signal);
+        }
+    }
+}
@@ -0,0 +1,242 @@
+//! Handle map diagnostics.
+//!
+//! This module inspects the contents of [`GtkThreadState::handle_map`] to help
+//! track down leaks. A [`HandleSnapshot`] lists every live handle with its kind,
+//! type name, GObject reference count and age, along with per-kind counts.
+//!
+//! ## Creation Stacks
+//!
+//! When the `GTKX_DEBUG_HANDLES` environment variable is set to a non-empty
+//! value other than `0`, the JavaScript stack is captured every time a handle is
+//! handed to JavaScript and reported alongside the handle. Stack capture is
+//! expensive and is meant for debugging sessions only.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{LazyLock, Mutex};
+use std::time::Instant;
+
+use gtk4::glib::prelude::ObjectExt as _;
+use neon::prelude::*;
+
+use crate::managed::NativeValue;
+use crate::state::GtkThreadState;
+
+const DEBUG_HANDLES_ENV: &str = "GTKX_DEBUG_HANDLES";
+
+static STACK_CAPTURE_ENABLED: LazyLock<bool> = LazyLock::new(|| {
+    std::env::var(DEBUG_HANDLES_ENV).is_ok_and(|value| !value.is_empty() && value != "0")
+});
+
+/// JS stacks keyed by handle ID. Written on the JS thread when a handle is boxed
+/// and read on the GTK thread when a snapshot is taken.
+static CREATION_STACKS: LazyLock<Mutex<HashMap<usize, String>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+pub fn is_stack_capture_enabled() -> bool {
+    *STACK_CAPTURE_ENABLED
+}
+
+pub fn capture_creation_stack<'a, C: Context<'a>>(cx: &mut C, handle_id: usize) {
+    if !is_stack_capture_enabled() {
+        return;
+    }
+
+    let Ok(error) = JsError::error(cx, "") else {
+        return;
+    };
+
+    let Ok(stack) = error.prop(cx, "stack").get::<Handle<JsValue>>() else {
+        return;
+    };
+
+    let Ok(stack) = stack.downcast::<JsString, _>(cx) else {
+        return;
+    };
+
+    let stack = stack.value(cx);
+    let stack = stack
+        .split_once('\n')
+        .map_or(stack.as_str(), |(_, frames)| frames)
+        .to_string();
+
+    CREATION_STACKS.lock().unwrap().insert(handle_id, stack);
+}
+
+pub fn forget_creation_stack(handle_id: usize) {
+    if !is_stack_capture_enabled() {
+        return;
+    }
+
+    CREATION_STACKS.lock().unwrap().remove(&handle_id);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HandleKind {
+    GObject,
+    Boxed,
+    Fundamental,
+}
+
+impl HandleKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            HandleKind::GObject => "gobject",
+            HandleKind::Boxed => "boxed",
+            HandleKind::Fundamental => "fundamental",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct HandleInfo {
+    pub id: usize,
+    pub kind: HandleKind,
+    pub type_name: Option<String>,
+    pub ref_count: Option<u32>,
+    pub owned: bool,
+    pub age_ms: f64,
+    pub stack: Option<String>,
+}
+
+impl HandleInfo {
+    fn from_native_value(id: usize, value: &NativeValue, created_at: Option<&Instant>) -> Self {
+        let (kind, type_name, ref_count, owned) = match value {
+            NativeValue::GObject(object) => (
+                HandleKind::GObject,
+                Some(object.type_().name().to_string()),
+                Some(object.ref_count()),
+                true,
+            ),
+            NativeValue::Boxed(boxed) => (
+                HandleKind::Boxed,
+                boxed.gtype().map(|gtype| gtype.name().to_string()),
+                None,
+                boxed.is_owned(),
+            ),
+            NativeValue::Fundamental(fundamental) => {
+                (HandleKind::Fundamental, None, None, fundamental.is_owned())
+            }
+        };
+
+        HandleInfo {
+            id,
+            kind,
+            type_name,
+            ref_count,
+            owned,
+            age_ms: created_at.map_or(0.0, |at| at.elapsed().as_secs_f64() * 1000.0),
+            stack: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct HandleSnapshot {
+    pub handles: Vec<HandleInfo>,
+    pub gobject_counts: BTreeMap<String, usize>,
+    pub boxed_counts: BTreeMap<String, usize>,
+    pub fundamental_count: usize,
+}
+
+impl HandleSnapshot {
+    /// Captures the current handle map. Must be called on the GTK thread.
+    pub fn capture() -> Self {
+        let mut handles: Vec<HandleInfo> = GtkThreadState::with(|state| {
+            state
+                .handle_map
+                .iter()
+                .map(|(id, value)| {
+                    HandleInfo::from_native_value(*id, value, state.handle_created_at.get(id))
+                })
+                .collect()
+        });
+
+        handles.sort_by_key(|info| info.id);
+
+        if is_stack_capture_enabled() {
+            let stacks = CREATION_STACKS.lock().unwrap();
+            for info in &mut handles {
+                info.stack = stacks.get(&info.id).cloned();
+            }
+        }
+
+        let mut snapshot = HandleSnapshot::default();
+
+        for info in &handles {
+            let type_name = info.type_name.clone().unwrap_or_else(|| "unknown".into());
+            match info.kind {
+                HandleKind::GObject => *snapshot.gobject_counts.entry(type_name).or_default() += 1,
+                HandleKind::Boxed => *snapshot.boxed_counts.entry(type_name).or_default() += 1,
+                HandleKind::Fundamental => snapshot.fundamental_count += 1,
+            }
+        }
+
+        snapshot.handles = handles;
+        snapshot
+    }
+
+    pub fn to_js_value<'a, C: Context<'a>>(&self, cx: &mut C) -> JsResult<'a, JsObject> {
+        let result = cx.empty_object();
+
+        let total = cx.number(self.handles.len() as f64);
+        result.set(cx, "total", total)?;
+
+        let counts = cx.empty_object();
+        let gobject_counts = Self::counts_to_js(cx, &self.gobject_counts)?;
+        counts.set(cx, "gobject", gobject_counts)?;
+        let boxed_counts = Self::counts_to_js(cx, &self.boxed_counts)?;
+        counts.set(cx, "boxed", boxed_counts)?;
+        let fundamental_count = cx.number(self.fundamental_count as f64);
+        counts.set(cx, "fundamental", fundamental_count)?;
+        result.set(cx, "counts", counts)?;
+
+        let handles = cx.empty_array();
+        for (i, info) in self.handles.iter().enumerate() {
+            let js_info = cx.empty_object();
+
+            let id = cx.number(info.id as f64);
+            js_info.set(cx, "id", id)?;
+            let kind = cx.string(info.kind.as_str());
+            js_info.set(cx, "kind", kind)?;
+
+            let type_name: Handle<JsValue> = match &info.type_name {
+                Some(name) => cx.string(name).upcast(),
+                None => cx.null().upcast(),
+            };
+            js_info.set(cx, "type", type_name)?;
+
+            let ref_count: Handle<JsValue> = match info.ref_count {
+                Some(count) => cx.number(count).upcast(),
+                None => cx.null().upcast(),
+            };
+            js_info.set(cx, "refCount", ref_count)?;
+
+            let owned = cx.boolean(info.owned);
+            js_info.set(cx, "owned", owned)?;
+            let age_ms = cx.number(info.age_ms);
+            js_info.set(cx, "ageMs", age_ms)?;
+
+            if let Some(stack) = &info.stack {
+                let stack = cx.string(stack);
+                js_info.set(cx, "stack", stack)?;
+            }
+
+            handles.set(cx, i as u32, js_info)?;
+        }
+        result.set(cx, "handles", handles)?;
+
+        Ok(result)
+    }
+
+    fn counts_to_js<'a, C: Context<'a>>(
+        cx: &mut C,
+        counts: &BTreeMap<String, usize>,
+    ) -> JsResult<'a, JsObject> {
+        let obj = cx.empty_object();
+        for (type_name, count) in counts {
+            let count = cx.number(*count as f64);
+            obj.set(cx, type_name.as_str(), count)?;
+        }
+        Ok(obj)
+    }
+}
@@ -0,0 +1,195 @@
+//! Symbolic names for enum and flags values.
+//!
+//! An integer type with a `get_type_fn` whose GType is an enum or flags type
+//! can exchange its values by nickname instead of by number when it is marked
+//! `symbolic`. [`EnumInfo`] reads the members of the type's `GEnumClass` or
+//! `GFlagsClass` and converts in both directions:
+//!
+//! | Kind | JavaScript | Native |
+//! |------|------------|--------|
+//! | Enum | `"center"` | `GTK_ALIGN_CENTER` |
+//! | Flags | `["bold", "italic"]` | `BOLD \| ITALIC` |
+//!
+//! Full C names (`"GTK_ALIGN_CENTER"`) are accepted as well as nicknames, and
+//! numbers always pass through unchanged. A native value that has no name, such
+//! as an out-of-range enum or flags with undeclared bits, decodes to its number.
+//!
+//! Looking up a class requires the GType system, so conversions run on the GTK
+//! thread. Registered classes never change, so [`EnumInfo::cached`] reads each
+//! one only once per thread.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use anyhow::bail;
+use gtk4::glib;
+
+use crate::value::Value;
+
+thread_local! {
+    /// Members of the types looked up so far, by library and `get_type_fn`.
+    static CACHE: RefCell<HashMap<(String, String), Rc<EnumInfo>>> =
+        RefCell::new(HashMap::new());
+}
+
+/// A named value of an enum or flags type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnumMember {
+    pub value: i64,
+    pub name: String,
+    pub nick: String,
+}
+
+/// The members of an enum or flags GType.
+#[derive(Debug, Clone)]
+pub struct EnumInfo {
+    pub type_name: String,
+    pub is_flags: bool,
+    /// In declaration order.
+    pub members: Vec<EnumMember>,
+}
+
+impl EnumInfo {
+    /// Reads the class of `gtype`, failing if it is neither an enum nor a
+    /// flags type.
+    pub fn lookup(gtype: glib::Type) -> anyhow::Result<Self> {
+        let type_name = gtype.name().to_string();
+
+        if let Some(class) = glib::EnumClass::with_type(gtype) {
+            let members = class
+                .values()
+                .iter()
+                .map(|value| EnumMember {
+                    value: i64::from(value.value()),
+                    name: value.name().to_string(),
+                    nick: value.nick().to_string(),
+                })
+                .collect();
+
+            return Ok(Self {
+                type_name,
+                is_flags: false,
+                members,
+            });
+        }
+
+        if let Some(class) = glib::FlagsClass::with_type(gtype) {
+            let members = class
+                .values()
+                .iter()
+                .map(|value| EnumMember {
+                    value: i64::from(value.value()),
+                    name: value.name().to_string(),
+                    nick: value.nick().to_string(),
+                })
+                .collect();
+
+            return Ok(Self {
+                type_name,
+                is_flags: true,
+                members,
+            });
+        }
+
+        bail!("{type_name} is not an enum or flags type")
+    }
+
+    /// Looks up the type returned by `get_type_fn` in `library`.
+    pub fn from_lib(library: &str, get_type_fn: &str) -> anyhow::Result<Self> {
+        Self::lookup(crate::ffi::get_gtype_from_lib(library, get_type_fn)?)
+    }
+
+    /// Like [`EnumInfo::from_lib`], but reuses the members read by an earlier
+    /// call on this thread.
+    pub fn cached(library: &str, get_type_fn: &str) -> anyhow::Result<Rc<Self>> {
+        let key = (library.to_string(), get_type_fn.to_string());
+
+        if let Some(info) = CACHE.with(|cache| cache.borrow().get(&key).cloned()) {
+            return Ok(info);
+        }
+
+        let info = Rc::new(Self::from_lib(library, get_type_fn)?);
+        CACHE.with(|cache| cache.borrow_mut().insert(key, info.clone()));
+        Ok(info)
+    }
+
+    fn member(&self, name: &str) -> anyhow::Result<&EnumMember> {
+        self.members
+            .iter()
+            .find(|member| member.nick == name || member.name == name)
+            .ok_or_else(|| {
+                let nicks: Vec<&str> = self.members.iter().map(|m| m.nick.as_str()).collect();
+                anyhow::anyhow!(
+                    "Unknown {} value '{name}'; expected one of: {}",
+                    self.type_name,
+                    nicks.join(", ")
+                )
+            })
+    }
+
+    /// The number a JavaScript value stands for: a number as is, a name, or
+    /// for flags an array of names and numbers to combine.
+    pub fn to_number(&self, value: &Value) -> anyhow::Result<f64> {
+        match value {
+            Value::Number(n) => Ok(*n),
+            Value::String(name) => Ok(self.member(name)?.value as f64),
+            Value::Array(items) if self.is_flags => {
+                let mut bits = 0u32;
+
+                for item in items {
+                    bits |= match item {
+                        Value::Number(n) => *n as u32,
+                        Value::String(name) => self.member(name)?.value as u32,
+                        _ => bail!(
+                            "Expected a name or Number in {} flags, got {:?}",
+                            self.type_name,
+                            item
+                        ),
+                    };
+                }
+
+                Ok(f64::from(bits))
+            }
+            _ => bail!(
+                "Expected a name or Number for {}, got {:?}",
+                self.type_name,
+                value
+            ),
+        }
+    }
+
+    /// The symbolic form of `number`: the nickname of an enum value, or the
+    /// nicknames of the flags set in it. Falls back to the number when some of
+    /// it has no name.
+    pub fn to_symbolic(&self, number: f64) -> Value {
+        if !self.is_flags {
+            return self
+                .members
+                .iter()
+                .find(|member| member.value as f64 == number)
+                .map_or(Value::Number(number), |member| {
+                    Value::String(member.nick.clone())
+                });
+        }
+
+        // Like `g_flags_to_string`, repeatedly take the first member whose bits
+        // are all set, so multi-bit members win over their single bits.
+        let mut remaining = number as u32;
+        let mut nicks = Vec::new();
+
+        while remaining != 0 {
+            let Some(member) = self.members.iter().find(|member| {
+                let bits = member.value as u32;
+                bits != 0 && remaining & bits == bits
+            }) else {
+                return Value::Number(number);
+            };
+
+            remaining &= !(member.value as u32);
+            nicks.push(Value::String(member.nick.clone()));
+        }
+
+        Value::Array(nicks)
+    }
+}
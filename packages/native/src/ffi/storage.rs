@@ -39,6 +39,8 @@ pub enum FfiStorageKind {
     PtrStorage(Box<*mut c_void>),
     HashTable(HashTableData),
     Callback(*mut c_void),
+    /// An instance created for a converted argument, released after the call.
+    Instance(crate::managed::NativeValue),
 }
 
 #[derive(Debug)]
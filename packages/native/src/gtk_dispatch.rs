@@ -7,10 +7,25 @@
 //! ## Scheduling Modes
 //!
 //! - [`GtkDispatcher::schedule`]: Queue a task for execution during the next GTK idle cycle.
-//!   Uses `glib::idle_add_once` for integration with the GLib event loop.
+//!   Uses a GLib idle source for integration with the GLib event loop.
+//! - [`GtkDispatcher::schedule_with_priority`]: Queue a task at a [`TaskPriority`].
 //! - [`GtkDispatcher::dispatch_pending`]: Manually execute all queued tasks immediately.
 //!   Used during blocking FFI calls to prevent deadlocks.
 //!
+//! ## Priorities
+//!
+//! Each [`TaskPriority`] has its own queue drained by an idle source at the
+//! matching GLib priority:
+//!
+//! | Priority | GLib priority | Runs relative to input and redraws |
+//! |----------|---------------|------------------------------------|
+//! | `High` | `G_PRIORITY_DEFAULT` | Alongside input, before redraws |
+//! | `Default` | `G_PRIORITY_DEFAULT_IDLE` | After redraws |
+//! | `Low` | `G_PRIORITY_LOW` | After all other idle work |
+//!
+//! Tasks within a queue run in FIFO order. [`GtkDispatcher::dispatch_pending`]
+//! drains every queue, highest priority first.
+//!
 //! ## JS Wait Tracking
 //!
 //! When JavaScript is blocking waiting for a GTK operation to complete,
@@ -18,6 +33,11 @@
 //! ([`GtkDispatcher::enter_js_wait`], [`GtkDispatcher::exit_js_wait`],
 //! [`GtkDispatcher::is_js_waiting`]) coordinate this.
 //!
+//! ## Watchdog
+//!
+//! While JS is blocked in [`GtkDispatcher::wait_for_gtk_result`], a wait frame
+//! is recorded with the [`crate::watchdog`] so stalls can be reported.
+//!
 //! ## Shutdown
 //!
 //! [`GtkDispatcher::mark_stopped`] signals that the application is shutting down. After this,
@@ -38,12 +58,59 @@ use neon::prelude::*;
 use crate::js_dispatch;
 use crate::state::GtkThreadState;
 use crate::wait_signal::WaitSignal;
+use crate::watchdog::{FrameKind, WaitThread, Watchdog};
 
 type Task = Box<dyn FnOnce() + Send + 'static>;
 
-pub struct GtkDispatcher {
-    queue: Mutex<VecDeque<Task>>,
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TaskPriority {
+    High,
+    #[default]
+    Default,
+    Low,
+}
+
+impl TaskPriority {
+    /// All priorities, highest first.
+    const ALL: [TaskPriority; 3] = [TaskPriority::High, TaskPriority::Default, TaskPriority::Low];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    fn glib_priority(self) -> glib::Priority {
+        match self {
+            TaskPriority::High => glib::Priority::DEFAULT,
+            TaskPriority::Default => glib::Priority::DEFAULT_IDLE,
+            TaskPriority::Low => glib::Priority::LOW,
+        }
+    }
+}
+
+impl std::str::FromStr for TaskPriority {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "high" => Ok(TaskPriority::High),
+            "default" => Ok(TaskPriority::Default),
+            "low" => Ok(TaskPriority::Low),
+            _ => Err(format!(
+                "'priority' must be one of: 'high', 'default', 'low'; got '{}'",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Default)]
+struct TaskQueue {
+    tasks: Mutex<VecDeque<Task>>,
     dispatch_scheduled: AtomicBool,
+}
+
+pub struct GtkDispatcher {
+    queues: [TaskQueue; 3],
     started: AtomicBool,
     stopped: AtomicBool,
     js_wait_depth: AtomicUsize,
@@ -60,8 +127,7 @@ impl GtkDispatcher {
 
     fn new() -> Self {
         Self {
-            queue: Mutex::new(VecDeque::new()),
-            dispatch_scheduled: AtomicBool::new(false),
+            queues: Default::default(),
             started: AtomicBool::new(false),
             stopped: AtomicBool::new(false),
             js_wait_depth: AtomicUsize::new(0),
@@ -70,17 +136,43 @@ impl GtkDispatcher {
         }
     }
 
-    fn push_task(&self, task: Task) {
-        self.queue.lock().unwrap().push_back(task);
+    fn queue(&self, priority: TaskPriority) -> &TaskQueue {
+        &self.queues[priority.index()]
+    }
+
+    fn push_task(&self, priority: TaskPriority, task: Task) {
+        self.queue(priority).tasks.lock().unwrap().push_back(task);
         js_dispatch::JsDispatcher::global().wake.notify();
     }
 
-    fn pop_task(&self) -> Option<Task> {
-        self.queue.lock().unwrap().pop_front()
+    fn pop_task(&self, priority: TaskPriority) -> Option<Task> {
+        self.queue(priority).tasks.lock().unwrap().pop_front()
+    }
+
+    /// Pops the oldest task from the highest-priority non-empty queue.
+    fn pop_any_task(&self) -> Option<Task> {
+        TaskPriority::ALL
+            .into_iter()
+            .find_map(|priority| self.pop_task(priority))
     }
 
-    fn is_queue_empty(&self) -> bool {
-        self.queue.lock().unwrap().is_empty()
+    fn is_queue_empty(&self, priority: TaskPriority) -> bool {
+        self.queue(priority).tasks.lock().unwrap().is_empty()
+    }
+
+    /// Installs an idle source draining `priority`'s queue unless one is pending.
+    fn ensure_dispatch_scheduled(&self, priority: TaskPriority) {
+        if self
+            .queue(priority)
+            .dispatch_scheduled
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .is_ok()
+        {
+            glib::idle_add_full(priority.glib_priority(), move || {
+                Self::global().drain_queue(priority);
+                glib::ControlFlow::Break
+            });
+        }
     }
 
     pub fn run_on_gtk_thread<F, T>(&self, task: F) -> mpsc::Receiver<T>
@@ -97,6 +189,19 @@ impl GtkDispatcher {
         rx
     }
 
+    /// Number of tasks queued at each priority, highest first.
+    pub fn pending_task_counts(&self) -> [usize; 3] {
+        TaskPriority::ALL.map(|priority| self.queue(priority).tasks.lock().unwrap().len())
+    }
+
+    pub fn js_wait_depth(&self) -> usize {
+        self.js_wait_depth.load(Ordering::Acquire)
+    }
+
+    pub fn callback_depth(&self) -> usize {
+        self.callback_depth.load(Ordering::Acquire)
+    }
+
     pub fn is_js_waiting(&self) -> bool {
         self.js_wait_depth.load(Ordering::Acquire) > 0
     }
@@ -134,7 +239,7 @@ impl GtkDispatcher {
     pub fn exit_callback(&self) {
         let prev = self.callback_depth.fetch_sub(1, Ordering::AcqRel);
         if prev == 1 {
-            self.process_deferred_closure_unrefs();
+            self.flush_deferred_closure_unrefs();
         }
     }
 
@@ -148,7 +253,9 @@ impl GtkDispatcher {
         });
     }
 
-    fn process_deferred_closure_unrefs(&self) {
+    /// Unrefs closures deferred while a callback was running. Must be called on
+    /// the GTK thread.
+    pub fn flush_deferred_closure_unrefs(&self) {
         GtkThreadState::with(|state| {
             let closures: Vec<_> = state.deferred_closure_unrefs.drain(..).collect();
             for closure in closures {
@@ -189,6 +296,13 @@ impl GtkDispatcher {
     }
 
     pub fn schedule<F>(&self, task: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.schedule_with_priority(TaskPriority::Default, task);
+    }
+
+    pub fn schedule_with_priority<F>(&self, priority: TaskPriority, task: F)
     where
         F: FnOnce() + Send + 'static,
     {
@@ -196,71 +310,56 @@ impl GtkDispatcher {
             return;
         }
 
-        self.push_task(Box::new(task));
-
-        if self
-            .dispatch_scheduled
-            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
-            .is_ok()
-        {
-            glib::idle_add_once(Self::idle_dispatch_callback);
-        }
+        self.push_task(priority, Box::new(task));
+        self.ensure_dispatch_scheduled(priority);
     }
 
     pub fn dispatch_pending(&self) -> bool {
         let mut dispatched = false;
 
-        while let Some(task) = self.pop_task() {
+        while let Some(task) = self.pop_any_task() {
             task();
             dispatched = true;
         }
 
         if dispatched {
-            self.dispatch_scheduled.store(false, Ordering::Release);
             self.wake.notify();
-            if !self.is_queue_empty()
-                && self
-                    .dispatch_scheduled
-                    .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
-                    .is_ok()
-            {
-                glib::idle_add_once(Self::idle_dispatch_callback);
-            }
         }
 
         dispatched
     }
 
-    fn drain_queue(&self) {
-        self.dispatch_scheduled.store(false, Ordering::Release);
+    fn drain_queue(&self, priority: TaskPriority) {
+        self.queue(priority)
+            .dispatch_scheduled
+            .store(false, Ordering::Release);
 
-        while let Some(task) = self.pop_task() {
+        while let Some(task) = self.pop_task(priority) {
             task();
         }
 
         self.wake.notify();
 
-        if !self.is_queue_empty()
-            && self
-                .dispatch_scheduled
-                .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
-                .is_ok()
-        {
-            glib::idle_add_once(Self::idle_dispatch_callback);
+        if !self.is_queue_empty(priority) {
+            self.ensure_dispatch_scheduled(priority);
         }
     }
 
-    fn idle_dispatch_callback() {
-        Self::global().drain_queue();
-    }
-
     pub fn wait_for_gtk_result<'a, R, C: Context<'a>>(
         &self,
         cx: &mut C,
         rx: &mpsc::Receiver<R>,
     ) -> Result<R, GtkDisconnectedError> {
+        let frame = Watchdog::global().track(WaitThread::Js, FrameKind::Wait, || {
+            "waiting for GTK thread".to_string()
+        });
+        let js_dispatcher = js_dispatch::JsDispatcher::global();
+
         let result = loop {
-            js_dispatch::JsDispatcher::global().process_pending(cx);
+            if js_dispatcher.pending_count() > 0 {
+                let _pumping = frame.pump();
+                js_dispatcher.process_pending(cx);
+            }
 
             match rx.try_recv() {
                 Ok(result) => break result,
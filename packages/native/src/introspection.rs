@@ -0,0 +1,1113 @@
+//! Function signatures from GObject Introspection typelibs.
+//!
+//! Calls normally carry a signature generated offline from `.gir` files. For
+//! libraries that were not generated, [`FunctionSignature::lookup`] reads the
+//! signature from the installed typelib through libgirepository instead, and
+//! derives the same type descriptors the generator would emit: ownership,
+//! `ref` types for out and inout parameters, arrays with their length
+//! parameters, enums and flags with their `get_type` functions, and boxed,
+//! struct, object and fundamental types.
+//!
+//! libgirepository is loaded on first use, so it is only required by
+//! applications that look up signatures at runtime. The 2.0 library shipped
+//! with GLib is preferred, falling back to the older 1.0 library. Its entry
+//! points are resolved once per GTK thread and reused by later lookups, which
+//! go through the default repository and run on the GTK thread.
+//!
+//! ## Names
+//!
+//! A function is looked up within a namespace, optionally versioned as
+//! `Gtk-4.0`, by one of:
+//!
+//! - Its name, for namespace-level functions: `init`
+//! - `Type.method`, for methods, constructors and static functions: `Widget.set_halign`
+//! - Its C symbol: `gtk_widget_set_halign`
+//!
+//! Callback parameters are not supported.
+
+use std::{
+    cell::OnceCell,
+    ffi::{CStr, CString, c_char, c_int, c_uint, c_void},
+    rc::Rc,
+};
+
+use anyhow::{Context as _, bail};
+use gtk4::glib::{self, translate::FromGlibPtrFull as _};
+use libloading::os::unix::Library;
+use neon::prelude::*;
+
+use crate::state::GtkThreadState;
+
+/// libgirepository builds to try, newest first. The 2.0 API ships with GLib
+/// itself since 2.80; older systems only have the 1.0 library.
+const GIREPOSITORY_LIBS: [&str; 2] = ["libgirepository-2.0.so.0", "libgirepository-1.0.so.1"];
+
+type Info = *mut c_void;
+
+macro_rules! gi_functions {
+    (
+        $($name:ident | $name_v2:ident($($arg:ty),*) $(-> $ret:ty)?;)*
+        v1 { $($v1:ident($($v1_arg:ty),*) $(-> $v1_ret:ty)?;)* }
+        v2 { $($v2:ident($($v2_arg:ty),*) $(-> $v2_ret:ty)?;)* }
+    ) => {
+        /// libgirepository entry points, named after their 1.0 symbols.
+        struct Gi {
+            api: Api,
+            $($name: unsafe extern "C" fn($($arg),*) $(-> $ret)?,)*
+        }
+
+        /// Entry points that only exist in one API version, or whose
+        /// signatures differ between them.
+        enum Api {
+            V1(GiV1),
+            V2(GiV2),
+        }
+
+        struct GiV1 {
+            $($v1: unsafe extern "C" fn($($v1_arg),*) $(-> $v1_ret)?,)*
+        }
+
+        struct GiV2 {
+            $($v2: unsafe extern "C" fn($($v2_arg),*) $(-> $v2_ret)?,)*
+        }
+
+        impl Gi {
+            fn load() -> anyhow::Result<Self> {
+                GtkThreadState::with(|state| {
+                    let mut errors = Vec::new();
+
+                    for lib_name in GIREPOSITORY_LIBS {
+                        let loaded = state
+                            .library(lib_name)
+                            .and_then(|lib| unsafe { Self::from_library(lib, lib_name) });
+                        match loaded {
+                            Ok(gi) => return Ok(gi),
+                            Err(err) => errors.push(format!("{err:#}")),
+                        }
+                    }
+
+                    bail!(
+                        "GObject Introspection is not available: {}",
+                        errors.join("; ")
+                    )
+                })
+            }
+
+            /// # Safety
+            ///
+            /// `lib` must be the libgirepository named `lib_name`, and must
+            /// stay loaded for the life of the returned entry points.
+            unsafe fn from_library(lib: &Library, lib_name: &str) -> anyhow::Result<Self> {
+                // SAFETY: The signatures match the libgirepository API of the
+                // library's version.
+                unsafe {
+                    let api = if lib_name.contains("-1.0") {
+                        Api::V1(GiV1 {
+                            $($v1: symbol(lib, lib_name, stringify!($v1))?,)*
+                        })
+                    } else {
+                        Api::V2(GiV2 {
+                            $($v2: symbol(lib, lib_name, stringify!($v2))?,)*
+                        })
+                    };
+                    let v1 = matches!(api, Api::V1(_));
+
+                    Ok(Gi {
+                        $($name: symbol(
+                            lib,
+                            lib_name,
+                            if v1 { stringify!($name) } else { stringify!($name_v2) },
+                        )?,)*
+                        api,
+                    })
+                }
+            }
+        }
+    };
+}
+
+/// # Safety
+///
+/// `T` must be a function pointer type matching the symbol's signature.
+unsafe fn symbol<T: Copy>(lib: &Library, lib_name: &str, name: &str) -> anyhow::Result<T> {
+    let symbol = unsafe { lib.get::<T>(name.as_bytes()) }
+        .with_context(|| format!("Missing {name} in {lib_name}"))?;
+    Ok(*symbol)
+}
+
+gi_functions! {
+    g_irepository_require | gi_repository_require(*mut c_void, *const c_char, *const c_char, c_int, *mut *mut glib::ffi::GError) -> *mut c_void;
+    g_irepository_find_by_name | gi_repository_find_by_name(*mut c_void, *const c_char, *const c_char) -> Info;
+    g_irepository_get_n_infos | gi_repository_get_n_infos(*mut c_void, *const c_char) -> c_int;
+    g_irepository_get_info | gi_repository_get_info(*mut c_void, *const c_char, c_int) -> Info;
+    g_base_info_ref | gi_base_info_ref(Info) -> Info;
+    g_base_info_unref | gi_base_info_unref(Info);
+    g_base_info_get_container | gi_base_info_get_container(Info) -> Info;
+    g_base_info_get_name | gi_base_info_get_name(Info) -> *const c_char;
+    g_base_info_get_namespace | gi_base_info_get_namespace(Info) -> *const c_char;
+    g_function_info_get_symbol | gi_function_info_get_symbol(Info) -> *const c_char;
+    g_function_info_get_flags | gi_function_info_get_flags(Info) -> c_int;
+    g_callable_info_can_throw_gerror | gi_callable_info_can_throw_gerror(Info) -> glib::ffi::gboolean;
+    g_callable_info_get_n_args | gi_callable_info_get_n_args(Info) -> c_int;
+    g_callable_info_get_arg | gi_callable_info_get_arg(Info, c_int) -> Info;
+    g_callable_info_get_return_type | gi_callable_info_get_return_type(Info) -> Info;
+    g_callable_info_get_caller_owns | gi_callable_info_get_caller_owns(Info) -> c_int;
+    g_arg_info_get_direction | gi_arg_info_get_direction(Info) -> c_int;
+    g_arg_info_get_ownership_transfer | gi_arg_info_get_ownership_transfer(Info) -> c_int;
+    g_arg_info_may_be_null | gi_arg_info_may_be_null(Info) -> glib::ffi::gboolean;
+    g_arg_info_is_caller_allocates | gi_arg_info_is_caller_allocates(Info) -> glib::ffi::gboolean;
+    g_arg_info_get_type | gi_arg_info_get_type_info(Info) -> Info;
+    g_type_info_get_tag | gi_type_info_get_tag(Info) -> c_int;
+    g_type_info_is_pointer | gi_type_info_is_pointer(Info) -> glib::ffi::gboolean;
+    g_type_info_get_param_type | gi_type_info_get_param_type(Info, c_int) -> Info;
+    g_type_info_get_interface | gi_type_info_get_interface(Info) -> Info;
+    g_type_info_get_array_type | gi_type_info_get_array_type(Info) -> c_int;
+    g_registered_type_info_get_type_name | gi_registered_type_info_get_type_name(Info) -> *const c_char;
+    g_registered_type_info_get_type_init | gi_registered_type_info_get_type_init(Info) -> *const c_char;
+    g_struct_info_get_size | gi_struct_info_get_size(Info) -> usize;
+    g_struct_info_find_method | gi_struct_info_find_method(Info, *const c_char) -> Info;
+    g_struct_info_get_n_methods | gi_struct_info_get_n_methods(Info) -> c_int;
+    g_struct_info_get_method | gi_struct_info_get_method(Info, c_int) -> Info;
+    g_union_info_get_size | gi_union_info_get_size(Info) -> usize;
+    g_union_info_find_method | gi_union_info_find_method(Info, *const c_char) -> Info;
+    g_union_info_get_n_methods | gi_union_info_get_n_methods(Info) -> c_int;
+    g_union_info_get_method | gi_union_info_get_method(Info, c_int) -> Info;
+    g_object_info_find_method | gi_object_info_find_method(Info, *const c_char) -> Info;
+    g_object_info_get_n_methods | gi_object_info_get_n_methods(Info) -> c_int;
+    g_object_info_get_method | gi_object_info_get_method(Info, c_int) -> Info;
+    g_object_info_get_fundamental | gi_object_info_get_fundamental(Info) -> glib::ffi::gboolean;
+    g_object_info_get_ref_function | gi_object_info_get_ref_function_name(Info) -> *const c_char;
+    g_object_info_get_unref_function | gi_object_info_get_unref_function_name(Info) -> *const c_char;
+    g_interface_info_find_method | gi_interface_info_find_method(Info, *const c_char) -> Info;
+    g_interface_info_get_n_methods | gi_interface_info_get_n_methods(Info) -> c_int;
+    g_interface_info_get_method | gi_interface_info_get_method(Info, c_int) -> Info;
+
+    v1 {
+        g_irepository_get_default() -> *mut c_void;
+        g_irepository_get_shared_library(*mut c_void, *const c_char) -> *const c_char;
+        g_base_info_get_type(Info) -> c_int;
+        g_type_info_get_array_length(Info) -> c_int;
+        g_type_info_get_array_fixed_size(Info) -> c_int;
+    }
+
+    v2 {
+        gi_repository_dup_default() -> *mut c_void;
+        gi_repository_get_shared_libraries(*mut c_void, *const c_char, *mut usize) -> *const *const c_char;
+        gi_type_info_get_array_length_index(Info, *mut c_uint) -> glib::ffi::gboolean;
+        gi_type_info_get_array_fixed_size(Info, *mut usize) -> glib::ffi::gboolean;
+        gi_function_info_get_type() -> glib::ffi::GType;
+        gi_callback_info_get_type() -> glib::ffi::GType;
+        gi_struct_info_get_type() -> glib::ffi::GType;
+        gi_enum_info_get_type() -> glib::ffi::GType;
+        gi_flags_info_get_type() -> glib::ffi::GType;
+        gi_object_info_get_type() -> glib::ffi::GType;
+        gi_interface_info_get_type() -> glib::ffi::GType;
+        gi_union_info_get_type() -> glib::ffi::GType;
+    }
+}
+
+thread_local! {
+    /// Entry points resolved by the first lookup on the GTK thread. They point
+    /// into a library held by [`GtkThreadState`], which stays loaded until the
+    /// thread exits.
+    static GI: OnceCell<Rc<Gi>> = const { OnceCell::new() };
+}
+
+impl Gi {
+    /// The entry points of this thread, loading libgirepository on first use.
+    /// A failed load is not cached, so a later lookup tries again.
+    fn get() -> anyhow::Result<Rc<Self>> {
+        GI.with(|cell| {
+            if let Some(gi) = cell.get() {
+                return Ok(gi.clone());
+            }
+
+            let gi = Rc::new(Self::load()?);
+            Ok(cell.get_or_init(|| gi).clone())
+        })
+    }
+
+    /// The default repository. With the 2.0 API the caller owns a reference.
+    fn default_repository(&self) -> *mut c_void {
+        match &self.api {
+            Api::V1(v1) => unsafe { (v1.g_irepository_get_default)() },
+            Api::V2(v2) => unsafe { (v2.gi_repository_dup_default)() },
+        }
+    }
+
+    /// The comma-separated shared libraries of a loaded namespace.
+    fn shared_library(&self, repository: *mut c_void, namespace: &CStr) -> Option<String> {
+        match &self.api {
+            Api::V1(v1) => unsafe {
+                cstr((v1.g_irepository_get_shared_library)(
+                    repository,
+                    namespace.as_ptr(),
+                ))
+            },
+            Api::V2(v2) => {
+                let mut n_libraries: usize = 0;
+                let libraries = unsafe {
+                    (v2.gi_repository_get_shared_libraries)(
+                        repository,
+                        namespace.as_ptr(),
+                        &mut n_libraries,
+                    )
+                };
+                if libraries.is_null() {
+                    return None;
+                }
+
+                let names: Vec<String> = (0..n_libraries)
+                    .filter_map(|index| unsafe { cstr(*libraries.add(index)) })
+                    .collect();
+                (!names.is_empty()).then(|| names.join(","))
+            }
+        }
+    }
+
+    /// The `GIInfoType` of an info. The 2.0 API dropped the enum in favour of
+    /// GTypes, which are mapped back onto it.
+    fn info_type(&self, info: Info) -> c_int {
+        let v2 = match &self.api {
+            Api::V1(v1) => return unsafe { (v1.g_base_info_get_type)(info) },
+            Api::V2(v2) => v2,
+        };
+
+        // SAFETY: A 2.0 `GIBaseInfo` is a `GTypeInstance`.
+        let gtype = unsafe { (*(*info.cast::<glib::gobject_ffi::GTypeInstance>()).g_class).g_type };
+        let types = [
+            (v2.gi_function_info_get_type, info_type::FUNCTION),
+            (v2.gi_callback_info_get_type, info_type::CALLBACK),
+            (v2.gi_struct_info_get_type, info_type::STRUCT),
+            (v2.gi_enum_info_get_type, info_type::ENUM),
+            (v2.gi_flags_info_get_type, info_type::FLAGS),
+            (v2.gi_object_info_get_type, info_type::OBJECT),
+            (v2.gi_interface_info_get_type, info_type::INTERFACE),
+            (v2.gi_union_info_get_type, info_type::UNION),
+        ];
+
+        types
+            .into_iter()
+            .find(|(get_type, _)| unsafe { get_type() } == gtype)
+            .map_or(info_type::INVALID, |(_, kind)| kind)
+    }
+
+    /// The index of the parameter holding an array's length, if it has one.
+    fn array_length(&self, type_info: Info) -> Option<usize> {
+        match &self.api {
+            Api::V1(v1) => {
+                usize::try_from(unsafe { (v1.g_type_info_get_array_length)(type_info) }).ok()
+            }
+            Api::V2(v2) => {
+                let mut index: c_uint = 0;
+                let found =
+                    unsafe { (v2.gi_type_info_get_array_length_index)(type_info, &mut index) };
+                (found != 0).then_some(index as usize)
+            }
+        }
+    }
+
+    /// The fixed size of an array, if it has one.
+    fn array_fixed_size(&self, type_info: Info) -> Option<usize> {
+        match &self.api {
+            Api::V1(v1) => {
+                usize::try_from(unsafe { (v1.g_type_info_get_array_fixed_size)(type_info) }).ok()
+            }
+            Api::V2(v2) => {
+                let mut size: usize = 0;
+                let found = unsafe { (v2.gi_type_info_get_array_fixed_size)(type_info, &mut size) };
+                (found != 0).then_some(size)
+            }
+        }
+    }
+}
+
+mod info_type {
+    pub const INVALID: i32 = 0;
+    pub const FUNCTION: i32 = 1;
+    pub const CALLBACK: i32 = 2;
+    pub const STRUCT: i32 = 3;
+    pub const BOXED: i32 = 4;
+    pub const ENUM: i32 = 5;
+    pub const FLAGS: i32 = 6;
+    pub const OBJECT: i32 = 7;
+    pub const INTERFACE: i32 = 8;
+    pub const UNION: i32 = 11;
+}
+
+mod type_tag {
+    pub const VOID: i32 = 0;
+    pub const BOOLEAN: i32 = 1;
+    pub const INT8: i32 = 2;
+    pub const UINT8: i32 = 3;
+    pub const INT16: i32 = 4;
+    pub const UINT16: i32 = 5;
+    pub const INT32: i32 = 6;
+    pub const UINT32: i32 = 7;
+    pub const INT64: i32 = 8;
+    pub const UINT64: i32 = 9;
+    pub const FLOAT: i32 = 10;
+    pub const DOUBLE: i32 = 11;
+    pub const GTYPE: i32 = 12;
+    pub const UTF8: i32 = 13;
+    pub const FILENAME: i32 = 14;
+    pub const ARRAY: i32 = 15;
+    pub const INTERFACE: i32 = 16;
+    pub const GLIST: i32 = 17;
+    pub const GSLIST: i32 = 18;
+    pub const GHASH: i32 = 19;
+    pub const ERROR: i32 = 20;
+    pub const UNICHAR: i32 = 21;
+}
+
+mod array_type {
+    pub const C: i32 = 0;
+    pub const ARRAY: i32 = 1;
+    pub const PTR_ARRAY: i32 = 2;
+    pub const BYTE_ARRAY: i32 = 3;
+}
+
+const FUNCTION_IS_METHOD: c_int = 1 << 0;
+
+const TRANSFER_NOTHING: c_int = 0;
+const TRANSFER_EVERYTHING: c_int = 2;
+
+/// A type descriptor in the shape `call` accepts from JavaScript.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Descriptor {
+    String(String),
+    Number(f64),
+    Boolean(bool),
+    Object(Vec<(&'static str, Descriptor)>),
+}
+
+impl Descriptor {
+    fn object<const N: usize>(fields: [(&'static str, Descriptor); N]) -> Self {
+        Descriptor::Object(fields.into())
+    }
+
+    fn with(mut self, key: &'static str, value: impl Into<Descriptor>) -> Self {
+        if let Descriptor::Object(fields) = &mut self {
+            fields.retain(|(existing, _)| *existing != key);
+            fields.push((key, value.into()));
+        }
+        self
+    }
+
+    /// The value of `key`, if this is an object that has it.
+    pub fn get(&self, key: &str) -> Option<&Descriptor> {
+        match self {
+            Descriptor::Object(fields) => fields
+                .iter()
+                .find_map(|(existing, value)| (*existing == key).then_some(value)),
+            _ => None,
+        }
+    }
+
+    pub fn to_js_value<'a, C: Context<'a>>(&self, cx: &mut C) -> JsResult<'a, JsValue> {
+        match self {
+            Descriptor::String(s) => Ok(cx.string(s).upcast()),
+            Descriptor::Number(n) => Ok(cx.number(*n).upcast()),
+            Descriptor::Boolean(b) => Ok(cx.boolean(*b).upcast()),
+            Descriptor::Object(fields) => {
+                let obj = cx.empty_object();
+                for (key, value) in fields {
+                    let js_value = value.to_js_value(cx)?;
+                    obj.set(cx, *key, js_value)?;
+                }
+                Ok(obj.upcast())
+            }
+        }
+    }
+}
+
+impl From<&str> for Descriptor {
+    fn from(s: &str) -> Self {
+        Descriptor::String(s.to_string())
+    }
+}
+
+impl From<String> for Descriptor {
+    fn from(s: String) -> Self {
+        Descriptor::String(s)
+    }
+}
+
+impl From<bool> for Descriptor {
+    fn from(b: bool) -> Self {
+        Descriptor::Boolean(b)
+    }
+}
+
+impl From<usize> for Descriptor {
+    fn from(n: usize) -> Self {
+        Descriptor::Number(n as f64)
+    }
+}
+
+fn int(size: usize, unsigned: bool) -> Descriptor {
+    Descriptor::object([
+        ("type", "int".into()),
+        ("size", size.into()),
+        ("unsigned", unsigned.into()),
+    ])
+}
+
+/// An untyped pointer, as the generator's `FFI_POINTER`: an unsigned integer
+/// of the target's pointer width, which also accepts handles.
+fn pointer() -> Descriptor {
+    int(size_of::<*mut c_void>() * 8, true)
+}
+
+fn ownership(full: bool) -> Descriptor {
+    Descriptor::from(if full { "full" } else { "borrowed" })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    In,
+    Out,
+    InOut,
+}
+
+impl Direction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Direction::In => "in",
+            Direction::Out => "out",
+            Direction::InOut => "inout",
+        }
+    }
+}
+
+/// Marks a parameter that carries the length of an array.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LengthOf {
+    Parameter(usize),
+    Return,
+}
+
+#[derive(Debug, Clone)]
+pub struct Parameter {
+    pub name: String,
+    pub direction: Direction,
+    /// The argument type; a `ref` for out and inout parameters.
+    pub arg_type: Descriptor,
+    pub nullable: bool,
+    pub length_of: Option<LengthOf>,
+}
+
+/// A function's C symbol, library and argument types.
+#[derive(Debug, Clone)]
+pub struct FunctionSignature {
+    pub library: String,
+    pub symbol: String,
+    /// In C order, starting with the instance for methods and excluding the
+    /// trailing `GError **` of throwing functions.
+    pub parameters: Vec<Parameter>,
+    pub return_type: Descriptor,
+    pub throws: bool,
+}
+
+impl FunctionSignature {
+    /// Looks up `name` in `namespace` (`Gtk` or `Gtk-4.0`); see the module
+    /// documentation for the accepted names.
+    pub fn lookup(namespace: &str, name: &str) -> anyhow::Result<Self> {
+        let gi = Gi::get()?;
+        let repository = Repository::require(&gi, namespace)?;
+        let function = repository.find_function(name)?;
+        repository.signature(&function)
+    }
+}
+
+/// An owned reference to a `GIBaseInfo`.
+struct InfoRef<'gi> {
+    gi: &'gi Gi,
+    ptr: Info,
+}
+
+impl<'gi> InfoRef<'gi> {
+    fn new(gi: &'gi Gi, ptr: Info) -> Option<Self> {
+        (!ptr.is_null()).then_some(InfoRef { gi, ptr })
+    }
+
+    fn info_type(&self) -> c_int {
+        self.gi.info_type(self.ptr)
+    }
+
+    fn name(&self) -> String {
+        unsafe { cstr((self.gi.g_base_info_get_name)(self.ptr)) }.unwrap_or_default()
+    }
+
+    fn namespace(&self) -> String {
+        unsafe { cstr((self.gi.g_base_info_get_namespace)(self.ptr)) }.unwrap_or_default()
+    }
+
+    fn symbol(&self) -> Option<String> {
+        unsafe { cstr((self.gi.g_function_info_get_symbol)(self.ptr)) }
+    }
+}
+
+impl Drop for InfoRef<'_> {
+    fn drop(&mut self) {
+        unsafe { (self.gi.g_base_info_unref)(self.ptr) };
+    }
+}
+
+/// # Safety
+///
+/// `ptr` must be null or a valid NUL-terminated string.
+unsafe fn cstr(ptr: *const c_char) -> Option<String> {
+    (!ptr.is_null()).then(|| {
+        unsafe { CStr::from_ptr(ptr) }
+            .to_string_lossy()
+            .into_owned()
+    })
+}
+
+/// Selects the method accessor for the kind of a container info, if it has methods.
+macro_rules! methods_of {
+    ($gi:expr, $info:expr, $object:ident, $interface:ident, $struct_:ident, $union:ident) => {
+        match $info.info_type() {
+            info_type::OBJECT => Some($gi.$object),
+            info_type::INTERFACE => Some($gi.$interface),
+            info_type::STRUCT | info_type::BOXED => Some($gi.$struct_),
+            info_type::UNION => Some($gi.$union),
+            _ => None,
+        }
+    };
+}
+
+struct Repository<'gi> {
+    gi: &'gi Gi,
+    ptr: *mut c_void,
+    namespace: CString,
+}
+
+impl Drop for Repository<'_> {
+    fn drop(&mut self) {
+        if matches!(self.gi.api, Api::V2(_)) {
+            unsafe { glib::gobject_ffi::g_object_unref(self.ptr.cast()) };
+        }
+    }
+}
+
+impl<'gi> Repository<'gi> {
+    fn require(gi: &'gi Gi, namespace: &str) -> anyhow::Result<Self> {
+        let (name, version) = match namespace.split_once('-') {
+            Some((name, version)) => (name, Some(version)),
+            None => (namespace, None),
+        };
+
+        let c_version = version.map(CString::new).transpose()?;
+        let repository = Repository {
+            gi,
+            ptr: gi.default_repository(),
+            namespace: CString::new(name)?,
+        };
+
+        let mut error = std::ptr::null_mut();
+        let typelib = unsafe {
+            (gi.g_irepository_require)(
+                repository.ptr,
+                repository.namespace.as_ptr(),
+                c_version.as_ref().map_or(std::ptr::null(), |v| v.as_ptr()),
+                0,
+                &mut error,
+            )
+        };
+
+        if typelib.is_null() {
+            let message = if error.is_null() {
+                "unknown error".to_string()
+            } else {
+                unsafe { glib::Error::from_glib_full(error) }.to_string()
+            };
+            bail!("Failed to load typelib {namespace}: {message}");
+        }
+
+        Ok(repository)
+    }
+
+    fn namespace(&self) -> &str {
+        self.namespace.to_str().unwrap_or_default()
+    }
+
+    fn find(&self, name: &str) -> Option<InfoRef<'gi>> {
+        let c_name = CString::new(name).ok()?;
+        let ptr = unsafe {
+            (self.gi.g_irepository_find_by_name)(self.ptr, self.namespace.as_ptr(), c_name.as_ptr())
+        };
+        InfoRef::new(self.gi, ptr)
+    }
+
+    fn find_function(&self, name: &str) -> anyhow::Result<InfoRef<'gi>> {
+        let ns = self.namespace();
+
+        if let Some((type_name, method)) = name.split_once('.') {
+            let container = self
+                .find(type_name)
+                .with_context(|| format!("{ns}.{type_name} not found"))?;
+            let find = methods_of!(
+                self.gi,
+                container,
+                g_object_info_find_method,
+                g_interface_info_find_method,
+                g_struct_info_find_method,
+                g_union_info_find_method
+            )
+            .with_context(|| format!("{ns}.{type_name} has no methods"))?;
+
+            let c_method = CString::new(method)?;
+            let ptr = unsafe { find(container.ptr, c_method.as_ptr()) };
+            return InfoRef::new(self.gi, ptr).with_context(|| format!("{ns}.{name} not found"));
+        }
+
+        if let Some(info) = self.find(name)
+            && info.info_type() == info_type::FUNCTION
+        {
+            return Ok(info);
+        }
+
+        self.find_by_symbol(name)
+            .with_context(|| format!("{ns}.{name} not found"))
+    }
+
+    /// Scans the namespace's functions and the methods of its types for a C
+    /// symbol.
+    fn find_by_symbol(&self, symbol: &str) -> Option<InfoRef<'gi>> {
+        let gi = self.gi;
+        let n_infos = unsafe { (gi.g_irepository_get_n_infos)(self.ptr, self.namespace.as_ptr()) };
+
+        for index in 0..n_infos {
+            let ptr =
+                unsafe { (gi.g_irepository_get_info)(self.ptr, self.namespace.as_ptr(), index) };
+            let Some(info) = InfoRef::new(gi, ptr) else {
+                continue;
+            };
+
+            if info.info_type() == info_type::FUNCTION {
+                if info.symbol().as_deref() == Some(symbol) {
+                    return Some(info);
+                }
+                continue;
+            }
+
+            let n_methods = methods_of!(
+                gi,
+                info,
+                g_object_info_get_n_methods,
+                g_interface_info_get_n_methods,
+                g_struct_info_get_n_methods,
+                g_union_info_get_n_methods
+            );
+            let get_method = methods_of!(
+                gi,
+                info,
+                g_object_info_get_method,
+                g_interface_info_get_method,
+                g_struct_info_get_method,
+                g_union_info_get_method
+            );
+
+            let (Some(n_methods), Some(get_method)) = (n_methods, get_method) else {
+                continue;
+            };
+
+            for method_index in 0..unsafe { n_methods(info.ptr) } {
+                let method = InfoRef::new(gi, unsafe { get_method(info.ptr, method_index) });
+                if let Some(method) = method
+                    && method.symbol().as_deref() == Some(symbol)
+                {
+                    return Some(method);
+                }
+            }
+        }
+
+        None
+    }
+
+    fn shared_library(&self, namespace: &str) -> anyhow::Result<String> {
+        let c_namespace = CString::new(namespace)?;
+        let library = self.gi.shared_library(self.ptr, &c_namespace);
+        library.with_context(|| format!("Typelib {namespace} has no shared library"))
+    }
+
+    fn signature(&self, function: &InfoRef<'gi>) -> anyhow::Result<FunctionSignature> {
+        let gi = self.gi;
+        let symbol = function.symbol().context("Function has no C symbol")?;
+        let flags = unsafe { (gi.g_function_info_get_flags)(function.ptr) };
+        let is_method = flags & FUNCTION_IS_METHOD != 0;
+        let offset = usize::from(is_method);
+
+        let mut parameters = Vec::new();
+
+        if is_method {
+            let instance = self
+                .container_of(function)
+                .with_context(|| format!("Cannot find the instance type of {symbol}"))?;
+            parameters.push(Parameter {
+                name: "self".to_string(),
+                direction: Direction::In,
+                arg_type: self.interface_type(&instance, false)?,
+                nullable: false,
+                length_of: None,
+            });
+        }
+
+        let n_args = unsafe { (gi.g_callable_info_get_n_args)(function.ptr) };
+        let mut lengths = Vec::new();
+
+        for index in 0..n_args {
+            let arg = InfoRef::new(gi, unsafe {
+                (gi.g_callable_info_get_arg)(function.ptr, index)
+            })
+            .context("Missing argument info")?;
+            let type_info = InfoRef::new(gi, unsafe { (gi.g_arg_info_get_type)(arg.ptr) })
+                .context("Missing argument type")?;
+
+            let direction = match unsafe { (gi.g_arg_info_get_direction)(arg.ptr) } {
+                0 => Direction::In,
+                1 => Direction::Out,
+                _ => Direction::InOut,
+            };
+            let transfer = unsafe { (gi.g_arg_info_get_ownership_transfer)(arg.ptr) };
+            let caller_allocates = unsafe { (gi.g_arg_info_is_caller_allocates)(arg.ptr) } != 0;
+            let name = arg.name();
+
+            let mapped = self
+                .map_type(&type_info, transfer, offset)
+                .with_context(|| format!("Parameter '{name}' of {symbol}"))?;
+
+            if let Some(length) = self.array_length(&type_info) {
+                lengths.push((length + offset, LengthOf::Parameter(parameters.len())));
+            }
+
+            let arg_type = match direction {
+                Direction::In => mapped,
+                Direction::Out | Direction::InOut => {
+                    let ref_type =
+                        Descriptor::object([("type", "ref".into()), ("innerType", mapped)]);
+                    match (caller_allocates, self.struct_size(&type_info)) {
+                        (true, Some(size)) => {
+                            ref_type.with("callerAllocates", true).with("size", size)
+                        }
+                        _ => ref_type,
+                    }
+                }
+            };
+
+            parameters.push(Parameter {
+                name,
+                direction,
+                arg_type,
+                nullable: unsafe { (gi.g_arg_info_may_be_null)(arg.ptr) } != 0,
+                length_of: None,
+            });
+        }
+
+        let return_info = InfoRef::new(gi, unsafe {
+            (gi.g_callable_info_get_return_type)(function.ptr)
+        })
+        .context("Missing return type")?;
+        let caller_owns = unsafe { (gi.g_callable_info_get_caller_owns)(function.ptr) };
+        let return_type = self
+            .map_type(&return_info, caller_owns, offset)
+            .with_context(|| format!("Return type of {symbol}"))?;
+
+        if let Some(length) = self.array_length(&return_info) {
+            lengths.push((length + offset, LengthOf::Return));
+        }
+
+        for (index, length_of) in lengths {
+            if let Some(parameter) = parameters.get_mut(index) {
+                parameter.length_of = Some(length_of);
+            }
+        }
+
+        Ok(FunctionSignature {
+            library: self.shared_library(&function.namespace())?,
+            symbol,
+            parameters,
+            return_type,
+            throws: unsafe { (gi.g_callable_info_can_throw_gerror)(function.ptr) } != 0,
+        })
+    }
+
+    /// The object, interface, struct or union a method belongs to.
+    fn container_of(&self, function: &InfoRef<'gi>) -> Option<InfoRef<'gi>> {
+        let ptr = unsafe { (self.gi.g_base_info_get_container)(function.ptr) };
+        if ptr.is_null() {
+            return None;
+        }
+        InfoRef::new(self.gi, unsafe { (self.gi.g_base_info_ref)(ptr) })
+    }
+
+    fn array_length(&self, type_info: &InfoRef<'gi>) -> Option<usize> {
+        let gi = self.gi;
+        if unsafe { (gi.g_type_info_get_tag)(type_info.ptr) } != type_tag::ARRAY {
+            return None;
+        }
+        gi.array_length(type_info.ptr)
+    }
+
+    fn struct_size(&self, type_info: &InfoRef<'gi>) -> Option<usize> {
+        let gi = self.gi;
+        if unsafe { (gi.g_type_info_get_tag)(type_info.ptr) } != type_tag::INTERFACE {
+            return None;
+        }
+        let interface = InfoRef::new(gi, unsafe { (gi.g_type_info_get_interface)(type_info.ptr) })?;
+        match interface.info_type() {
+            info_type::STRUCT | info_type::BOXED => {
+                Some(unsafe { (gi.g_struct_info_get_size)(interface.ptr) })
+            }
+            info_type::UNION => Some(unsafe { (gi.g_union_info_get_size)(interface.ptr) }),
+            _ => None,
+        }
+    }
+
+    /// Derives the descriptor of a type with the given ownership transfer.
+    /// `offset` shifts array length indices past the instance parameter.
+    fn map_type(
+        &self,
+        type_info: &InfoRef<'gi>,
+        transfer: c_int,
+        offset: usize,
+    ) -> anyhow::Result<Descriptor> {
+        let gi = self.gi;
+        let tag = unsafe { (gi.g_type_info_get_tag)(type_info.ptr) };
+        let is_pointer = unsafe { (gi.g_type_info_is_pointer)(type_info.ptr) } != 0;
+        let full = transfer != TRANSFER_NOTHING;
+        let items_full = transfer == TRANSFER_EVERYTHING;
+
+        let param_type = |index| {
+            InfoRef::new(gi, unsafe {
+                (gi.g_type_info_get_param_type)(type_info.ptr, index)
+            })
+            .context("Missing element type")
+        };
+
+        let descriptor = match tag {
+            type_tag::VOID if is_pointer => pointer(),
+            type_tag::VOID => Descriptor::object([("type", "undefined".into())]),
+            type_tag::BOOLEAN => Descriptor::object([("type", "boolean".into())]),
+            type_tag::INT8 => int(8, false),
+            type_tag::UINT8 => int(8, true),
+            type_tag::INT16 => int(16, false),
+            type_tag::UINT16 => int(16, true),
+            type_tag::INT32 => int(32, false),
+            type_tag::UINT32 | type_tag::UNICHAR => int(32, true),
+            type_tag::INT64 => int(64, false),
+            type_tag::UINT64 | type_tag::GTYPE => int(64, true),
+            type_tag::FLOAT => {
+                Descriptor::object([("type", "float".into()), ("size", Descriptor::Number(32.0))])
+            }
+            type_tag::DOUBLE => {
+                Descriptor::object([("type", "float".into()), ("size", Descriptor::Number(64.0))])
+            }
+            type_tag::UTF8 | type_tag::FILENAME => {
+                let string =
+                    Descriptor::object([("type", "string".into()), ("ownership", ownership(full))]);
+                if tag == type_tag::FILENAME {
+                    string.with("encoding", "filename")
+                } else {
+                    string
+                }
+            }
+            type_tag::ARRAY => {
+                let array_type = unsafe { (gi.g_type_info_get_array_type)(type_info.ptr) };
+                let item = param_type(0)?;
+                let item_type = if array_type == array_type::BYTE_ARRAY {
+                    int(8, true)
+                } else {
+                    self.map_type(
+                        &item,
+                        if items_full {
+                            transfer
+                        } else {
+                            TRANSFER_NOTHING
+                        },
+                        offset,
+                    )?
+                };
+
+                let fixed_size = gi.array_fixed_size(type_info.ptr);
+                let length = self.array_length(type_info);
+
+                let kind = match array_type {
+                    array_type::C if fixed_size.is_some() => "fixed",
+                    array_type::C if length.is_some() => "sized",
+                    array_type::C => "array",
+                    array_type::ARRAY | array_type::BYTE_ARRAY => "garray",
+                    array_type::PTR_ARRAY => "gptrarray",
+                    other => bail!("Unsupported array type {other}"),
+                };
+
+                let mut array = Descriptor::object([
+                    ("type", "array".into()),
+                    ("itemType", item_type),
+                    ("kind", kind.into()),
+                    ("ownership", ownership(full)),
+                ]);
+
+                if let Some(fixed_size) = fixed_size.filter(|_| kind == "fixed") {
+                    array = array.with("fixedSize", fixed_size);
+                }
+                if let Some(length) = length.filter(|_| kind == "sized") {
+                    array = array.with("sizeParamIndex", length + offset);
+                }
+                if unsafe { (gi.g_type_info_is_pointer)(item.ptr) } == 0
+                    && let Some(size) = self.struct_size(&item)
+                {
+                    array = array.with("elementSize", size);
+                }
+
+                array
+            }
+            type_tag::GLIST | type_tag::GSLIST => {
+                let item = param_type(0)?;
+                let item_transfer = if items_full {
+                    transfer
+                } else {
+                    TRANSFER_NOTHING
+                };
+                Descriptor::object([
+                    ("type", "array".into()),
+                    ("itemType", self.map_type(&item, item_transfer, offset)?),
+                    (
+                        "kind",
+                        if tag == type_tag::GLIST {
+                            "glist"
+                        } else {
+                            "gslist"
+                        }
+                        .into(),
+                    ),
+                    ("ownership", ownership(full)),
+                ])
+            }
+            type_tag::GHASH => {
+                let item_transfer = if items_full {
+                    transfer
+                } else {
+                    TRANSFER_NOTHING
+                };
+                Descriptor::object([
+                    ("type", "hashtable".into()),
+                    (
+                        "keyType",
+                        self.map_type(&param_type(0)?, item_transfer, offset)?,
+                    ),
+                    (
+                        "valueType",
+                        self.map_type(&param_type(1)?, item_transfer, offset)?,
+                    ),
+                    ("ownership", ownership(full)),
+                ])
+            }
+            type_tag::ERROR => Descriptor::object([
+                ("type", "boxed".into()),
+                ("ownership", ownership(full)),
+                ("innerType", "GError".into()),
+                ("library", self.shared_library("GLib")?.into()),
+                ("getTypeFn", "g_error_get_type".into()),
+            ]),
+            type_tag::INTERFACE => {
+                let interface =
+                    InfoRef::new(gi, unsafe { (gi.g_type_info_get_interface)(type_info.ptr) })
+                        .context("Missing interface type")?;
+                self.interface_type(&interface, full)?
+            }
+            other => bail!("Unsupported type tag {other}"),
+        };
+
+        Ok(descriptor)
+    }
+
+    /// Derives the descriptor of an object, interface, struct, union, enum or
+    /// flags type.
+    fn interface_type(&self, interface: &InfoRef<'gi>, full: bool) -> anyhow::Result<Descriptor> {
+        let gi = self.gi;
+        let type_name = unsafe { cstr((gi.g_registered_type_info_get_type_name)(interface.ptr)) };
+        let type_init = unsafe { cstr((gi.g_registered_type_info_get_type_init)(interface.ptr)) }
+            .filter(|type_init| type_init != "intern");
+        let library = || self.shared_library(&interface.namespace());
+
+        let descriptor = match interface.info_type() {
+            info_type::ENUM | info_type::FLAGS => {
+                let base = int(32, interface.info_type() == info_type::FLAGS);
+                match type_init {
+                    Some(type_init) => base
+                        .with("library", library()?)
+                        .with("getTypeFn", type_init),
+                    None => base,
+                }
+            }
+            info_type::OBJECT
+                if unsafe { (gi.g_object_info_get_fundamental)(interface.ptr) } != 0 =>
+            {
+                let ref_fn = unsafe { cstr((gi.g_object_info_get_ref_function)(interface.ptr)) };
+                let unref_fn =
+                    unsafe { cstr((gi.g_object_info_get_unref_function)(interface.ptr)) };
+                let (Some(ref_fn), Some(unref_fn)) = (ref_fn, unref_fn) else {
+                    bail!(
+                        "Fundamental type {} has no ref and unref functions",
+                        interface.name()
+                    );
+                };
+                let fundamental = Descriptor::object([
+                    ("type", "fundamental".into()),
+                    ("ownership", ownership(full)),
+                    ("library", library()?.into()),
+                    ("refFn", ref_fn.into()),
+                    ("unrefFn", unref_fn.into()),
+                ]);
+                match type_name {
+                    Some(type_name) => fundamental.with("typeName", type_name),
+                    None => fundamental,
+                }
+            }
+            info_type::OBJECT | info_type::INTERFACE => {
+                let object = Descriptor::object([
+                    ("type", "gobject".into()),
+                    ("ownership", ownership(full)),
+                ]);
+                match type_name {
+                    Some(type_name) => object.with("typeName", type_name),
+                    None => object,
+                }
+            }
+            info_type::STRUCT | info_type::BOXED | info_type::UNION => {
+                match (type_name, type_init) {
+                    (Some(type_name), Some(type_init)) => Descriptor::object([
+                        ("type", "boxed".into()),
+                        ("ownership", ownership(full)),
+                        ("innerType", type_name.into()),
+                        ("library", library()?.into()),
+                        ("getTypeFn", type_init.into()),
+                    ]),
+                    _ => {
+                        let size = if interface.info_type() == info_type::UNION {
+                            unsafe { (gi.g_union_info_get_size)(interface.ptr) }
+                        } else {
+                            unsafe { (gi.g_struct_info_get_size)(interface.ptr) }
+                        };
+                        Descriptor::object([
+                            ("type", "struct".into()),
+                            ("ownership", ownership(full)),
+                            ("innerType", interface.name().into()),
+                            ("size", size.into()),
+                        ])
+                    }
+                }
+            }
+            info_type::CALLBACK => bail!(
+                "Callback parameters ({}) are not supported",
+                interface.name()
+            ),
+            other => bail!("Unsupported interface type {other} ({})", interface.name()),
+        };
+
+        Ok(descriptor)
+    }
+}
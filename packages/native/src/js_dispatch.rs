@@ -21,7 +21,12 @@ use std::sync::{Arc, Mutex, OnceLock, mpsc};
 
 use neon::prelude::*;
 
-use crate::{gtk_dispatch, value::Value, wait_signal::WaitSignal};
+use crate::{
+    gtk_dispatch,
+    value::Value,
+    wait_signal::WaitSignal,
+    watchdog::{FrameKind, WaitThread, Watchdog},
+};
 
 struct PendingCallback {
     callback: Arc<Root<JsFunction>>,
@@ -95,6 +100,10 @@ impl JsDispatcher {
         }
     }
 
+    pub fn pending_count(&self) -> usize {
+        self.queue.lock().unwrap().len()
+    }
+
     pub fn invoke_and_wait<T, F>(
         &self,
         channel: &Channel,
@@ -106,6 +115,10 @@ impl JsDispatcher {
     where
         F: FnOnce(Result<Value, ()>) -> T,
     {
+        let _frame = Watchdog::global().track(WaitThread::Gtk, FrameKind::Wait, || {
+            "waiting for JS callback".to_string()
+        });
+
         gtk_dispatch::GtkDispatcher::global().enter_callback();
         let rx = self.queue(channel, callback.clone(), args, capture_result);
         let result = self.wait_for_result(rx, on_result);
@@ -0,0 +1,298 @@
+//! C struct and union layout computation.
+//!
+//! [`StructLayout::compute`] lays out named fields the way the C compiler does
+//! on the target platform, so JavaScript does not have to hard-code offsets and
+//! sizes that differ between architectures. Sizes and alignments come from
+//! libffi: each field becomes an element of a libffi struct type, which libffi
+//! initializes when a CIF is prepared with it. Each field is then placed at the
+//! next offset that satisfies its alignment, as the C ABI does.
+//!
+//! [`StructLayout::compute_union`] places every field at offset 0 instead; the
+//! union is as large as its largest member, rounded up to its alignment.
+//!
+//! ## Field Types
+//!
+//! | Type | Layout |
+//! |------|--------|
+//! | `Integer`, `Float` | The C scalar |
+//! | `Boolean` | One byte |
+//! | `GBoolean` | A C `gboolean`, i.e. an `int` |
+//! | `String`, `GObject`, `Boxed`, `Fundamental` | A pointer |
+//! | `Struct` | Embedded; requires `size` and `alignment` |
+//! | `Array` (`fixed`) | `fixedSize` consecutive items |
+//!
+//! ## Bitfields
+//!
+//! A field with a bit width must have an `Integer` type, which is its storage
+//! unit. Following the System V and AAPCS rules, a bitfield continues right
+//! after the previous field's bits unless it would cross a boundary of its
+//! unit's alignment, in which case it starts at the next boundary. The field's
+//! offset is the start of its aligned unit and its [`BitField`] gives the bits
+//! within that unit. Zero-width bitfields are not supported.
+
+use std::collections::HashSet;
+
+use anyhow::{Context as _, bail};
+use libffi::middle as libffi;
+
+use crate::types::{ArrayKind, BitField, Type};
+
+/// Strictest alignment accepted for an embedded struct.
+const MAX_ALIGNMENT: usize = 64;
+
+/// A field to lay out.
+#[derive(Debug, Clone)]
+pub struct FieldSpec {
+    pub name: String,
+    pub field_type: Type,
+    /// Width in bits, when the field is a bitfield.
+    pub bits: Option<u32>,
+}
+
+impl FieldSpec {
+    pub fn new(name: impl Into<String>, field_type: Type) -> Self {
+        Self {
+            name: name.into(),
+            field_type,
+            bits: None,
+        }
+    }
+
+    pub fn bitfield(name: impl Into<String>, field_type: Type, bits: u32) -> Self {
+        Self {
+            bits: Some(bits),
+            ..Self::new(name, field_type)
+        }
+    }
+}
+
+/// Where a field lives within its struct.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldLayout {
+    pub name: String,
+    pub offset: usize,
+    pub size: usize,
+    /// The bits of the storage unit at `offset`, for bitfields.
+    pub bit_field: Option<BitField>,
+}
+
+/// The computed layout of a C struct or union.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StructLayout {
+    /// Fields in declaration order.
+    pub fields: Vec<FieldLayout>,
+    /// Total size in bytes, including trailing padding.
+    pub size: usize,
+    pub alignment: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LayoutKind {
+    Struct,
+    Union,
+}
+
+impl StructLayout {
+    pub fn compute(fields: &[FieldSpec]) -> anyhow::Result<Self> {
+        Self::lay_out(fields, LayoutKind::Struct)
+    }
+
+    pub fn compute_union(fields: &[FieldSpec]) -> anyhow::Result<Self> {
+        Self::lay_out(fields, LayoutKind::Union)
+    }
+
+    pub fn field(&self, name: &str) -> Option<&FieldLayout> {
+        self.fields.iter().find(|field| field.name == name)
+    }
+
+    fn lay_out(fields: &[FieldSpec], kind: LayoutKind) -> anyhow::Result<Self> {
+        if fields.is_empty() {
+            bail!("A {} needs at least one field", kind.name());
+        }
+
+        let mut names = HashSet::new();
+        for field in fields {
+            if !names.insert(field.name.as_str()) {
+                bail!("Duplicate field '{}'", field.name);
+            }
+        }
+
+        let element_types = fields
+            .iter()
+            .map(|field| {
+                ffi_type(&field.field_type)
+                    .with_context(|| format!("Invalid field '{}'", field.name))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        // Preparing a CIF initializes the size and alignment of the struct
+        // type and of every element, recursively.
+        let cif = libffi::Builder::new()
+            .res(libffi::Type::structure(element_types))
+            .into_cif();
+
+        // SAFETY: The CIF was prepared above, so its return type is an
+        // initialized struct type with one element per field.
+        let struct_type = unsafe { &*(*cif.as_raw_ptr()).rtype };
+
+        // Positions are tracked in bits so that bitfields can share bytes.
+        let mut end_bits = 0;
+        let mut alignment = 1;
+        let mut layouts = Vec::with_capacity(fields.len());
+
+        for (i, field) in fields.iter().enumerate() {
+            // SAFETY: `elements` holds one initialized type per field.
+            let element = unsafe { &**struct_type.elements.add(i) };
+            let size = element.size;
+            let field_alignment = usize::from(element.alignment);
+            alignment = alignment.max(field_alignment);
+
+            let start_bits = match kind {
+                LayoutKind::Struct => end_bits,
+                LayoutKind::Union => 0,
+            };
+
+            let (layout, field_end_bits) = match field.bits {
+                None => {
+                    let offset = start_bits.div_ceil(8).next_multiple_of(field_alignment);
+                    let layout = FieldLayout {
+                        name: field.name.clone(),
+                        offset,
+                        size,
+                        bit_field: None,
+                    };
+                    (layout, (offset + size) * 8)
+                }
+                Some(width) => {
+                    if !matches!(field.field_type, Type::Integer(_)) {
+                        bail!("Bitfield '{}' must have an integer type", field.name);
+                    }
+
+                    let width = width as usize;
+                    let unit_bits = size * 8;
+                    if width == 0 || width > unit_bits {
+                        bail!(
+                            "Bitfield '{}' must be between 1 and {unit_bits} bits wide, got {width}",
+                            field.name
+                        );
+                    }
+
+                    let alignment_bits = field_alignment * 8;
+                    let mut unit_start = start_bits - start_bits % alignment_bits;
+                    let mut bit_start = start_bits;
+
+                    if bit_start + width > unit_start + unit_bits {
+                        unit_start = start_bits.next_multiple_of(alignment_bits);
+                        bit_start = unit_start;
+                    }
+
+                    let layout = FieldLayout {
+                        name: field.name.clone(),
+                        offset: unit_start / 8,
+                        size,
+                        bit_field: Some(BitField {
+                            offset: (bit_start - unit_start) as u32,
+                            width: width as u32,
+                        }),
+                    };
+                    (layout, bit_start + width)
+                }
+            };
+
+            layouts.push(layout);
+            end_bits = end_bits.max(field_end_bits);
+        }
+
+        Ok(Self {
+            fields: layouts,
+            size: end_bits.div_ceil(8).next_multiple_of(alignment),
+            alignment,
+        })
+    }
+}
+
+impl LayoutKind {
+    fn name(self) -> &'static str {
+        match self {
+            LayoutKind::Struct => "struct",
+            LayoutKind::Union => "union",
+        }
+    }
+}
+
+/// The libffi type a field of the given type occupies.
+fn ffi_type(field_type: &Type) -> anyhow::Result<libffi::Type> {
+    match field_type {
+        Type::Integer(_)
+        | Type::Float(_)
+        | Type::Boolean
+        | Type::GBoolean
+        | Type::String(_)
+        | Type::GObject(_)
+        | Type::Boxed(_)
+        | Type::Fundamental(_) => Ok(field_type.into()),
+        Type::Struct(struct_type) => {
+            let (Some(size), Some(alignment)) = (struct_type.size, struct_type.alignment) else {
+                bail!(
+                    "Embedded struct {} requires a size and alignment",
+                    struct_type.type_name
+                );
+            };
+
+            opaque_type(size, alignment)
+        }
+        Type::Array(array_type) => {
+            let ArrayKind::Fixed { size: count } = array_type.kind else {
+                bail!(
+                    "Only fixed-size arrays can be embedded in a struct, got {:?}",
+                    array_type.kind
+                );
+            };
+
+            if count == 0 {
+                bail!("Fixed arrays need at least one element");
+            }
+
+            // Each item gets its own type rather than a clone, since cloning
+            // drops the alignment set up by `opaque_type`.
+            let item_types = (0..count)
+                .map(|_| ffi_type(&array_type.item_type))
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            Ok(libffi::Type::structure(item_types))
+        }
+        _ => bail!("Unsupported field type: {field_type}"),
+    }
+}
+
+/// A struct type with the given size and alignment but no known members.
+fn opaque_type(size: usize, alignment: usize) -> anyhow::Result<libffi::Type> {
+    if !alignment.is_power_of_two() || alignment > MAX_ALIGNMENT {
+        bail!("Unsupported alignment {alignment}");
+    }
+
+    if size == 0 || !size.is_multiple_of(alignment) {
+        bail!("Size {size} is not a positive multiple of alignment {alignment}");
+    }
+
+    let (unit, unit_size) = match alignment {
+        1 => (libffi::Type::u8(), 1),
+        2 => (libffi::Type::u16(), 2),
+        4 => (libffi::Type::u32(), 4),
+        _ => (libffi::Type::u64(), 8),
+    };
+    let opaque = libffi::Type::structure(vec![unit; size / unit_size]);
+
+    if alignment > unit_size {
+        // No scalar is aligned this strictly on every platform, so the type is
+        // initialized here; libffi keeps the size and alignment of types that
+        // are already initialized when it prepares the enclosing struct.
+        // SAFETY: The type was just created and is not shared yet.
+        unsafe {
+            let raw = opaque.as_raw_ptr();
+            (*raw).size = size;
+            (*raw).alignment = alignment as u16;
+        }
+    }
+
+    Ok(opaque)
+}
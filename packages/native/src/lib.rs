@@ -13,7 +13,22 @@
 //! | `alloc` | Allocate memory for boxed types |
 //! | `read` | Read field from boxed memory |
 //! | `write` | Write field to boxed memory |
+//! | `defineStruct` | Compute the C layout of a struct from field descriptors |
+//! | `defineUnion` | Compute the C layout of a union from field descriptors |
+//! | `enumValues` | List the names and values of an enum or flags type |
+//! | `describeFunction` | Read a function's signature from its GObject Introspection typelib |
 //! | `getNativeId` | Get internal handle ID for managed object |
+//! | `release` | Release a managed object before garbage collection |
+//! | `debugHandles` | Snapshot the live handle map for leak diagnostics |
+//! | `configureWatchdog` | Configure the JS ⇄ GTK deadlock watchdog |
+//! | `configureTracing` | Enable or disable FFI call tracing |
+//! | `dumpTrace` | Export recorded calls as JSON or a Chrome trace |
+//! | `clearTrace` | Discard recorded calls |
+//! | `configureCrashHandler` | Report the in-flight native call on fatal signals |
+//! | `addTimeout` | Run a JS callback from a GLib timeout source |
+//! | `addIdle` | Run a JS callback from a GLib idle source |
+//! | `addUnixFdWatch` | Run a JS callback when a file descriptor is ready |
+//! | `removeSource` | Remove a source added from JavaScript |
 //!
 //! ## Architecture
 //!
@@ -26,6 +41,9 @@
 //! - `gtk_dispatch`: Schedules tasks from JS thread to GLib thread
 //! - `js_dispatch`: Queues callbacks from GLib thread back to JS
 //!
+//! Calls made with `thread: "worker"` run on the `worker` thread pool instead,
+//! with argument preparation and result conversion still on the GLib thread.
+//!
 //! ## Core Types
 //!
 //! - `Value`: Central data interchange type (JS ↔ CIF ↔ GLib)
@@ -34,16 +52,25 @@
 //! - `ffi::FfiValue`: Low-level libffi argument representation
 
 pub mod arg;
+pub mod async_call;
+pub mod crash;
+pub mod diagnostics;
+pub mod enums;
 pub mod ffi;
 pub mod gtk_dispatch;
+pub mod introspection;
 mod js_dispatch;
+pub mod layout;
 pub mod managed;
 pub mod module;
 pub mod state;
+pub mod trace;
 pub mod trampoline;
 pub mod types;
 pub mod value;
 pub mod wait_signal;
+pub mod watchdog;
+pub mod worker;
 
 pub use managed::{Boxed, Fundamental, NativeHandle, NativeValue};
 
@@ -58,7 +85,22 @@ fn main(mut cx: ModuleContext) -> NeonResult<()> {
     cx.export_function("write", module::write)?;
     cx.export_function("readPointer", module::read_pointer)?;
     cx.export_function("writePointer", module::write_pointer)?;
+    cx.export_function("defineStruct", module::define_struct)?;
+    cx.export_function("defineUnion", module::define_union)?;
+    cx.export_function("enumValues", module::enum_values)?;
+    cx.export_function("describeFunction", module::describe_function)?;
     cx.export_function("alloc", module::alloc)?;
     cx.export_function("getNativeId", module::get_native_id)?;
+    cx.export_function("release", module::release)?;
+    cx.export_function("debugHandles", module::debug_handles)?;
+    cx.export_function("configureWatchdog", module::configure_watchdog)?;
+    cx.export_function("configureTracing", module::configure_tracing)?;
+    cx.export_function("dumpTrace", module::dump_trace)?;
+    cx.export_function("clearTrace", module::clear_trace)?;
+    cx.export_function("configureCrashHandler", module::configure_crash_handler)?;
+    cx.export_function("addTimeout", module::add_timeout)?;
+    cx.export_function("addIdle", module::add_idle)?;
+    cx.export_function("addUnixFdWatch", module::add_unix_fd_watch)?;
+    cx.export_function("removeSource", module::remove_source)?;
     Ok(())
 }
@@ -3,12 +3,19 @@ use std::ffi::c_void;
 use anyhow::bail;
 use gtk4::glib::{self, translate::IntoGlib as _};
 
-use super::OwnedPtr;
+use super::{OwnedPtr, ParentRef};
 
 #[derive(Debug)]
 pub struct Boxed {
     inner: OwnedPtr,
     gtype: Option<glib::Type>,
+    /// Size of the allocation in bytes, when known. Bounds field access.
+    size: Option<usize>,
+    /// For a borrowed view into another value's memory, keeps that value alive.
+    parent: Option<ParentRef>,
+    /// For a borrowed view, whether the parent's memory may be written
+    /// through it like the parent itself.
+    writable_view: bool,
 }
 
 impl Boxed {
@@ -17,6 +24,9 @@ impl Boxed {
         Self {
             inner: OwnedPtr::from_full(ptr),
             gtype,
+            size: None,
+            parent: None,
+            writable_view: false,
         }
     }
 
@@ -25,6 +35,9 @@ impl Boxed {
         Self {
             inner: OwnedPtr::from_none(ptr),
             gtype,
+            size: None,
+            parent: None,
+            writable_view: false,
         }
     }
 
@@ -42,6 +55,9 @@ impl Boxed {
             return Ok(Self {
                 inner: OwnedPtr::from_none(ptr),
                 gtype,
+                size,
+                parent: None,
+                writable_view: false,
             });
         }
 
@@ -52,6 +68,9 @@ impl Boxed {
                 Ok(Self {
                     inner: OwnedPtr::from_full(cloned_ptr),
                     gtype,
+                    size,
+                    parent: None,
+                    writable_view: false,
                 })
             }
             None => {
@@ -64,6 +83,9 @@ impl Boxed {
                     Ok(Self {
                         inner: OwnedPtr::from_full(cloned_ptr),
                         gtype: None,
+                        size: Some(s),
+                        parent: None,
+                        writable_view: false,
                     })
                 } else {
                     let name = type_name.unwrap_or("unknown");
@@ -78,6 +100,28 @@ impl Boxed {
         }
     }
 
+    /// Records the size of the allocation, used to bound field access.
+    #[must_use]
+    pub fn with_size(mut self, size: Option<usize>) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Ties a borrowed view to the value that owns its memory.
+    #[must_use]
+    pub fn with_parent(mut self, parent: ParentRef) -> Self {
+        self.parent = Some(parent);
+        self
+    }
+
+    /// Lets a borrowed view be written without `allowBorrowed` when its
+    /// parent can be.
+    #[must_use]
+    pub fn with_writable_view(mut self, writable: bool) -> Self {
+        self.writable_view = writable;
+        self
+    }
+
     #[inline]
     #[must_use]
     pub fn as_ptr(&self) -> *mut c_void {
@@ -89,10 +133,28 @@ impl Boxed {
         self.gtype
     }
 
+    /// The value a borrowed view points into, if any.
+    #[must_use]
+    pub fn parent(&self) -> Option<&ParentRef> {
+        self.parent.as_ref()
+    }
+
+    #[must_use]
+    pub fn size(&self) -> Option<usize> {
+        self.size
+    }
+
     #[must_use]
     pub fn is_owned(&self) -> bool {
         self.inner.is_owned()
     }
+
+    /// Whether writes through this value need no `allowBorrowed`: it owns its
+    /// memory, or is a view into a parent that does.
+    #[must_use]
+    pub fn is_writable(&self) -> bool {
+        self.is_owned() || self.writable_view
+    }
 }
 
 impl Clone for Boxed {
@@ -101,6 +163,9 @@ impl Clone for Boxed {
             return Self {
                 inner: self.inner.borrow(),
                 gtype: self.gtype,
+                size: self.size,
+                parent: self.parent.clone(),
+                writable_view: self.writable_view,
             };
         }
 
@@ -112,14 +177,36 @@ impl Clone for Boxed {
                 Self {
                     inner: OwnedPtr::from_full(cloned_ptr),
                     gtype: self.gtype,
+                    size: self.size,
+                    parent: None,
+                    writable_view: false,
                 }
             }
             None => {
-                panic!(
-                    "Cannot clone owned Boxed without GType - the size is unknown and \
-                     returning a borrowed reference would create a dangling pointer. \
-                     Use Boxed::borrowed() for non-owned pointers or ensure GType is available."
-                );
+                let Some(size) = self.size else {
+                    panic!(
+                        "Cannot clone owned Boxed without GType - the size is unknown and \
+                         returning a borrowed reference would create a dangling pointer. \
+                         Use Boxed::borrowed() for non-owned pointers or ensure GType is available."
+                    );
+                };
+
+                let cloned_ptr = unsafe {
+                    let dest = glib::ffi::g_malloc(size);
+                    std::ptr::copy_nonoverlapping(
+                        self.inner.as_ptr() as *const u8,
+                        dest as *mut u8,
+                        size,
+                    );
+                    dest
+                };
+                Self {
+                    inner: OwnedPtr::from_full(cloned_ptr),
+                    gtype: None,
+                    size: self.size,
+                    parent: None,
+                    writable_view: false,
+                }
             }
         }
     }
@@ -19,47 +19,98 @@
 //! 4. When JS garbage collects the handle, [`Finalize::finalize`] schedules removal
 //! 5. The GTK thread removes the object from the map, dropping the Rust wrapper
 //!
+//! Handles can also be released early with [`NativeHandle::release`], which drops
+//! the wrapper immediately instead of waiting for V8 garbage collection. Any later
+//! use of a released handle fails with a "released" error.
+//!
+//! ## Type Checks
+//!
+//! Before a handle is passed to a native function, [`NativeHandle::require_typed_ptr`]
+//! checks that it holds the expected kind of value and, when the argument type
+//! names a GType, an instance of that type. A mismatch is reported as a
+//! [`HandleTypeError`] instead of handing C a pointer of the wrong type.
+//!
+//! ## Field Access
+//!
+//! [`NativeHandle::require_memory`] describes the memory behind a handle for
+//! `read`/`write`: its allocation size when known (allocated structs and
+//! GObject instances) so accesses past the end are rejected, and whether the
+//! handle owns the memory so writes through borrowed handles can be refused.
+//!
+//! ## Borrowed Views
+//!
+//! A borrowed view into memory owned by another handle, such as an embedded
+//! struct read from a field, holds a [`ParentRef`] from
+//! [`NativeHandle::parent_ref`]: a reference on a parent GObject, or a
+//! [`HandlePin`] that keeps a boxed or fundamental parent in the handle map
+//! until every view into it is gone, even if the parent's own handle is
+//! finalized or released first. The only way a parent goes away earlier is
+//! `stop` draining every handle, after which its views report that their
+//! parent was freed.
+//!
 //! This ensures proper reference counting for GObjects and proper freeing for Boxed types.
 
 mod boxed;
 mod fundamental;
 mod owned_ptr;
+mod parent;
 
 pub use boxed::Boxed;
 pub use fundamental::{Fundamental, RefFn, UnrefFn};
 pub(crate) use owned_ptr::OwnedPtr;
+pub use parent::{HandlePin, ParentRef};
 
 use std::ffi::c_void;
 
-use gtk4::glib::{self, object::ObjectType as _};
+use gtk4::glib::{
+    self,
+    object::{ObjectExt as _, ObjectType as _},
+    translate::{FromGlib as _, IntoGlib as _},
+};
 use neon::prelude::*;
 
-use crate::{gtk_dispatch, state::GtkThreadState};
+use crate::{diagnostics::HandleKind, gtk_dispatch, state::GtkThreadState};
 
 #[derive(Debug, Clone, Copy)]
 pub struct NativeHandle(pub(crate) usize);
 
 impl From<NativeValue> for NativeHandle {
     fn from(object: NativeValue) -> Self {
-        GtkThreadState::with(|state| {
-            let key = state.next_handle_id;
-            state.next_handle_id = state.next_handle_id.wrapping_add(1);
-            state.handle_map.insert(key, object);
-            NativeHandle(key)
-        })
+        GtkThreadState::with(|state| NativeHandle(state.insert_handle(object)))
     }
 }
 
 impl NativeHandle {
     #[must_use]
     pub fn get_ptr(&self) -> Option<*mut c_void> {
-        GtkThreadState::with(|state| {
-            state.handle_map.get(&self.0).map(|object| match object {
-                NativeValue::GObject(obj) => obj.as_ptr() as *mut c_void,
-                NativeValue::Boxed(boxed) => boxed.as_ptr(),
-                NativeValue::Fundamental(fundamental) => fundamental.as_ptr(),
+        GtkThreadState::with(|state| state.handle_map.get(&self.0).map(NativeValue::as_ptr))
+    }
+
+    /// Returns the pointer held by this handle after checking that it is a
+    /// value of the expected kind and, when `type_name` is given, an instance
+    /// of that GType.
+    pub(crate) fn require_typed_ptr(
+        &self,
+        kind: HandleKind,
+        type_name: Option<&str>,
+    ) -> anyhow::Result<*mut c_void> {
+        let checked = GtkThreadState::with(|state| {
+            let state = &*state;
+            state.handle_map.get(&self.0).map(|native| {
+                native.check_parent(state, self.0)?;
+                native.check_type(kind, type_name)?;
+                Ok(native.as_ptr())
             })
-        })
+        });
+
+        match checked {
+            Some(result) => result,
+            None => Err(self.unavailable_error(&format!(
+                "{} argument with handle {}",
+                kind.as_str(),
+                self.0
+            ))),
+        }
     }
 
     #[must_use]
@@ -67,29 +118,97 @@ impl NativeHandle {
         self.get_ptr().map(|ptr| ptr as usize)
     }
 
-    pub(crate) fn require_ptr(&self) -> anyhow::Result<*mut c_void> {
-        self.get_ptr().ok_or_else(|| {
-            anyhow::anyhow!("Object with handle {} has been garbage collected", self.0)
-        })
+    /// Builds the error reported when this handle is no longer in the handle map.
+    pub(crate) fn unavailable_error(&self, what: &str) -> anyhow::Error {
+        if self.is_released() {
+            anyhow::anyhow!("{} has been released", what)
+        } else {
+            anyhow::anyhow!("{} has been garbage collected", what)
+        }
+    }
+
+    #[must_use]
+    pub fn is_released(&self) -> bool {
+        GtkThreadState::with(|state| state.released_handles.contains(&self.0))
     }
 
-    pub(crate) fn require_non_null_ptr(&self) -> anyhow::Result<*mut c_void> {
-        let ptr = self.require_ptr()?;
-        if ptr.is_null() {
+    /// Removes the managed value from the handle map immediately, dropping its
+    /// reference (or freeing its memory) without waiting for garbage collection.
+    ///
+    /// Returns `false` if the handle was already released. Must be called on the
+    /// GTK thread.
+    pub fn release(&self) -> bool {
+        let (was_live, removed) = GtkThreadState::with(|state| {
+            let was_live = state.handle_map.contains_key(&self.0);
+            let removed = state.remove_handle(self.0);
+            if was_live {
+                state.released_handles.insert(self.0);
+            }
+            (was_live, removed)
+        });
+
+        drop(removed);
+        was_live
+    }
+
+    /// Describes the memory behind this handle for field access. Fails if the
+    /// handle is unavailable or null.
+    pub(crate) fn require_memory(&self) -> anyhow::Result<HandleMemory> {
+        let memory: Option<anyhow::Result<HandleMemory>> = GtkThreadState::with(|state| {
+            let state = &*state;
+            state.handle_map.get(&self.0).map(|native| {
+                native.check_parent(state, self.0)?;
+                Ok(HandleMemory {
+                    ptr: native.as_ptr(),
+                    size: native.memory_size(),
+                    owned: native.owns_memory(),
+                })
+            })
+        });
+
+        let Some(memory) = memory else {
+            return Err(self.unavailable_error(&format!("Object with handle {}", self.0)));
+        };
+        let memory = memory?;
+
+        if memory.ptr.is_null() {
             anyhow::bail!("Object with handle {} has a null pointer", self.0);
         }
-        Ok(ptr)
+
+        Ok(memory)
+    }
+
+    /// Takes a reference that keeps this handle's value alive for a borrowed
+    /// view into its memory.
+    pub(crate) fn parent_ref(&self) -> anyhow::Result<ParentRef> {
+        let parent = GtkThreadState::with(|state| match state.handle_map.get(&self.0) {
+            Some(NativeValue::GObject(object)) => Some(ParentRef::Object(object.clone())),
+            Some(_) => Some(ParentRef::Handle(HandlePin::new(state, self.0))),
+            None => None,
+        });
+
+        parent.ok_or_else(|| self.unavailable_error(&format!("Object with handle {}", self.0)))
     }
 
     pub fn inner(&self) -> usize {
         self.0
     }
+
+    /// Boxes the handle for JavaScript, recording the creating JS stack when
+    /// handle stack capture is enabled.
+    pub fn into_js<'a, C: Context<'a>>(self, cx: &mut C) -> Handle<'a, JsBox<NativeHandle>> {
+        crate::diagnostics::capture_creation_stack(cx, self.0);
+        cx.boxed(self)
+    }
 }
 
 impl Finalize for NativeHandle {
     fn finalize<'a, C: Context<'a>>(self, _cx: &mut C) {
         gtk_dispatch::GtkDispatcher::global().schedule(move || {
-            let removed = GtkThreadState::with(|state| state.handle_map.remove(&self.0));
+            let removed = GtkThreadState::with(|state| {
+                state.released_handles.remove(&self.0);
+                state.remove_handle(self.0)
+            });
             drop(removed);
         });
     }
@@ -111,3 +230,233 @@ pub enum NativeValue {
     Boxed(Boxed),
     Fundamental(Fundamental),
 }
+
+/// The memory behind a handle, with what is known about its extent.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct HandleMemory {
+    pub ptr: *mut c_void,
+    /// Allocation size in bytes, when known.
+    pub size: Option<usize>,
+    /// Whether the handle owns the memory, or is a view into a parent that
+    /// does, rather than borrowing it.
+    pub owned: bool,
+}
+
+impl HandleMemory {
+    /// Returns a pointer to `len` bytes at `offset`, failing if they extend
+    /// past the end of the allocation.
+    pub fn field_ptr(&self, offset: usize, len: usize) -> anyhow::Result<*mut u8> {
+        if let Some(size) = self.size
+            && offset.checked_add(len).is_none_or(|end| end > size)
+        {
+            anyhow::bail!(
+                "Access of {len} bytes at offset {offset} is out of bounds for a {size}-byte allocation"
+            );
+        }
+
+        // SAFETY: The offset is within the allocation when its size is known;
+        // otherwise the caller's layout is trusted.
+        Ok(unsafe { (self.ptr as *mut u8).add(offset) })
+    }
+
+    /// Refuses writes through a borrowed handle, whose memory belongs to
+    /// another owner, unless explicitly allowed.
+    pub fn require_writable(&self, allow_borrowed: bool) -> anyhow::Result<()> {
+        if !self.owned && !allow_borrowed {
+            anyhow::bail!(
+                "Refusing to write through a borrowed handle; pass 'allowBorrowed' to write into memory owned elsewhere"
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// A handle passed to a native function holds the wrong kind of value, or an
+/// instance of the wrong GType.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HandleTypeError {
+    pub expected_kind: HandleKind,
+    pub expected_type: Option<String>,
+    pub actual_kind: HandleKind,
+    pub actual_type: Option<String>,
+}
+
+impl std::fmt::Display for HandleTypeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let describe = |kind: HandleKind, type_name: &Option<String>| match type_name {
+            Some(type_name) => format!("{} {type_name}", kind.as_str()),
+            None => kind.as_str().to_string(),
+        };
+
+        write!(
+            f,
+            "Expected {}, got {}",
+            describe(self.expected_kind, &self.expected_type),
+            describe(self.actual_kind, &self.actual_type)
+        )
+    }
+}
+
+impl std::error::Error for HandleTypeError {}
+
+/// GObject types whose instances may be used from any thread.
+const THREAD_SAFE_GOBJECT_TYPES: &[&str] = &["GCancellable", "GFile"];
+
+/// Boxed types that are immutable or internally synchronized, with atomic
+/// copy/ref functions, and may be used from any thread.
+const THREAD_SAFE_BOXED_TYPES: &[&str] = &[
+    "GBytes",
+    "GDateTime",
+    "GError",
+    "GRegex",
+    "GTimeZone",
+    "GUri",
+    "GVariantType",
+];
+
+impl NativeValue {
+    #[must_use]
+    pub fn as_ptr(&self) -> *mut c_void {
+        match self {
+            NativeValue::GObject(obj) => obj.as_ptr() as *mut c_void,
+            NativeValue::Boxed(boxed) => boxed.as_ptr(),
+            NativeValue::Fundamental(fundamental) => fundamental.as_ptr(),
+        }
+    }
+
+    #[must_use]
+    pub fn kind(&self) -> HandleKind {
+        match self {
+            NativeValue::GObject(_) => HandleKind::GObject,
+            NativeValue::Boxed(_) => HandleKind::Boxed,
+            NativeValue::Fundamental(_) => HandleKind::Fundamental,
+        }
+    }
+
+    /// Fails if this is a borrowed view whose parent has been freed.
+    fn check_parent(&self, state: &GtkThreadState, id: usize) -> anyhow::Result<()> {
+        if let NativeValue::Boxed(boxed) = self
+            && boxed.parent().is_some_and(|parent| parent.is_freed(state))
+        {
+            anyhow::bail!("Borrowed view with handle {id} is invalid: its parent was freed");
+        }
+
+        Ok(())
+    }
+
+    /// Size in bytes of the memory behind this value, when known.
+    fn memory_size(&self) -> Option<usize> {
+        match self {
+            NativeValue::GObject(object) => {
+                let mut query = std::mem::MaybeUninit::<glib::gobject_ffi::GTypeQuery>::zeroed();
+
+                // SAFETY: g_type_query fills in the query for any GType, leaving
+                // it zeroed for types that are not classed.
+                let query = unsafe {
+                    glib::gobject_ffi::g_type_query(object.type_().into_glib(), query.as_mut_ptr());
+                    query.assume_init()
+                };
+
+                (query.instance_size > 0).then_some(query.instance_size as usize)
+            }
+            NativeValue::Boxed(boxed) => boxed.size(),
+            NativeValue::Fundamental(_) => None,
+        }
+    }
+
+    fn owns_memory(&self) -> bool {
+        match self {
+            NativeValue::GObject(_) => true,
+            NativeValue::Boxed(boxed) => boxed.is_writable(),
+            NativeValue::Fundamental(fundamental) => fundamental.is_owned(),
+        }
+    }
+
+    /// The GType of the instance, if known.
+    ///
+    /// Fundamentals are only asked when the caller expects a GType, since not
+    /// every fundamental is a `GTypeInstance` (e.g. `GVariant`).
+    fn instance_type(&self, expects_gtype: bool) -> Option<glib::Type> {
+        match self {
+            NativeValue::GObject(object) => Some(object.type_()),
+            NativeValue::Boxed(boxed) => boxed.gtype(),
+            NativeValue::Fundamental(fundamental) if expects_gtype => {
+                let ptr = fundamental.as_ptr() as *const glib::gobject_ffi::GTypeInstance;
+                if ptr.is_null() {
+                    return None;
+                }
+
+                // SAFETY: The argument type names a GType for this fundamental,
+                // so its instances start with a GTypeInstance header.
+                unsafe {
+                    let class = (*ptr).g_class;
+                    (!class.is_null()).then(|| glib::Type::from_glib((*class).g_type))
+                }
+            }
+            NativeValue::Fundamental(_) => None,
+        }
+    }
+
+    /// Checks that this value is of the expected kind and, when `type_name` is
+    /// given, an instance of that GType. Boxed values allocated without a GType
+    /// can only be checked by kind.
+    pub fn check_type(
+        &self,
+        kind: HandleKind,
+        type_name: Option<&str>,
+    ) -> Result<(), HandleTypeError> {
+        let actual_kind = self.kind();
+        let actual_type = self.instance_type(type_name.is_some());
+
+        let mismatch = || HandleTypeError {
+            expected_kind: kind,
+            expected_type: type_name.map(str::to_string),
+            actual_kind,
+            actual_type: actual_type.map(|gtype| gtype.name().to_string()),
+        };
+
+        if actual_kind != kind {
+            return Err(mismatch());
+        }
+
+        let (Some(type_name), Some(actual_type)) = (type_name, actual_type) else {
+            return Ok(());
+        };
+
+        match glib::Type::from_name(type_name) {
+            Some(expected_type) if actual_type.is_a(expected_type) => Ok(()),
+            _ => Err(mismatch()),
+        }
+    }
+
+    /// Whether this value may be passed to a call running on a worker thread.
+    ///
+    /// Only GObject and boxed types known to be thread-safe are shared.
+    /// Widgets, most other objects and mutable boxed types such as
+    /// `GtkTextIter` are bound to the GTK thread, and so are fundamentals and
+    /// boxed values without a GType, whose thread-safety cannot be known.
+    pub fn is_thread_safe(&self) -> bool {
+        let (instance_type, safe_types) = match self {
+            NativeValue::GObject(object) => (object.type_(), THREAD_SAFE_GOBJECT_TYPES),
+            NativeValue::Boxed(boxed) => match boxed.gtype() {
+                Some(gtype) => (gtype, THREAD_SAFE_BOXED_TYPES),
+                None => return false,
+            },
+            NativeValue::Fundamental(_) => return false,
+        };
+
+        safe_types
+            .iter()
+            .filter_map(|name| glib::Type::from_name(name))
+            .any(|safe_type| instance_type.is_a(safe_type))
+    }
+
+    /// Name of the value's type for error messages.
+    pub fn type_name(&self) -> String {
+        match self.instance_type(false) {
+            Some(gtype) => gtype.name().to_string(),
+            None => self.kind().as_str().to_string(),
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use gtk4::glib;
+
+use crate::state::GtkThreadState;
+
+/// Keeps alive the value that owns the memory a borrowed view points into.
+#[derive(Debug, Clone)]
+pub enum ParentRef {
+    /// A reference on the parent GObject.
+    Object(glib::Object),
+    /// A pin on the parent's entry in the handle map.
+    Handle(HandlePin),
+}
+
+impl ParentRef {
+    /// Whether the parent was freed despite the reference, which only happens
+    /// when `stop` drains every handle during teardown.
+    pub fn is_freed(&self, state: &GtkThreadState) -> bool {
+        match self {
+            ParentRef::Object(_) => false,
+            ParentRef::Handle(pin) => {
+                !state.handle_map.contains_key(&pin.0)
+                    && !state.orphaned_handles.contains_key(&pin.0)
+            }
+        }
+    }
+}
+
+/// Keeps a boxed or fundamental value in the handle map after its JS handle
+/// is finalized or released, until the pin is dropped.
+#[derive(Debug)]
+pub struct HandlePin(usize);
+
+impl HandlePin {
+    /// Pins the value behind handle `id`.
+    pub fn new(state: &mut GtkThreadState, id: usize) -> Self {
+        state.pin_handle(id);
+        Self(id)
+    }
+}
+
+impl Clone for HandlePin {
+    fn clone(&self) -> Self {
+        GtkThreadState::with(|state| Self::new(state, self.0))
+    }
+}
+
+impl Drop for HandlePin {
+    fn drop(&mut self) {
+        let orphan = GtkThreadState::with(|state| state.unpin_handle(self.0));
+        drop(orphan);
+    }
+}
@@ -10,6 +10,9 @@
 //!   proper `g_boxed_free` cleanup.
 //! - **Plain structs** (without type_name): Memory is allocated with `g_malloc0`
 //!   and freed with `g_free` on drop.
+//!
+//! In both modes the handle remembers the allocation size, so `read` and
+//! `write` reject field accesses past its end.
 
 use gtk4::glib::ffi::g_malloc0;
 use neon::prelude::*;
@@ -66,7 +69,7 @@ impl AllocRequest {
             boxed_type.gtype()
         });
 
-        let boxed = Boxed::from_glib_full(gtype, ptr);
+        let boxed = Boxed::from_glib_full(gtype, ptr).with_size(Some(self.size));
         Ok(NativeValue::Boxed(boxed).into())
     }
 }
@@ -81,5 +84,5 @@ pub fn alloc(mut cx: FunctionContext) -> JsResult<JsValue> {
         .or_else(|err| cx.throw_error(format!("Error receiving alloc result: {err}")))?
         .or_else(|err| cx.throw_error(format!("Error during alloc: {err}")))?;
 
-    Ok(cx.boxed(handle).upcast())
+    Ok(handle.into_js(&mut cx).upcast())
 }
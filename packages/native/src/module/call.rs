@@ -14,6 +14,41 @@
 //! 6. Convert the result back to a [`Value`] for JavaScript
 //! 7. Update any `Ref` type out-parameters with modified values
 //!
+//! ## Worker Thread Calls
+//!
+//! With the `thread: "worker"` option, steps 2-4 and 6-7 still run on the GTK
+//! thread, but step 5 runs on the [`WorkerPool`]. Only values that are safe to
+//! share across threads may be passed: GObjects and boxed values must be of a
+//! known thread-safe type (see [`NativeValue::is_thread_safe`]) and callbacks
+//! are refused. Shared GObjects are referenced and other shared values are
+//! pinned in the handle map for the duration of the call, so releasing their
+//! handles on the GTK thread cannot free them while the worker reads them.
+//!
+//! ## Async Calls
+//!
+//! With the `finish` option, the call is a GIO `_async` function: the trailing
+//! cancellable, `GAsyncReadyCallback` and user data arguments are supplied
+//! natively and `call` returns a Promise settled with the result of the paired
+//! `_finish` function. An `AbortSignal` passed as `signal` cancels the operation
+//! through a `GCancellable`. See [`crate::async_call`].
+//!
+//! ## Priorities
+//!
+//! The `priority` option (`"high"`, `"default"` or `"low"`) selects the
+//! [`TaskPriority`] queue the call is scheduled on, so latency-critical calls
+//! run ahead of redraws and background work yields to them.
+//!
+//! ## Tracing
+//!
+//! While [`crate::trace`] is enabled, each call records a [`CallTrace`] when it
+//! is queued, marks when its task starts and how long the native function ran,
+//! and completes it with the decoded return value or error.
+//!
+//! ## Crash Reports
+//!
+//! The native function runs inside a [`crash::CrashScope`] naming the library,
+//! symbol and argument types, so a fatal signal during the call reports it.
+//!
 //! ## Callbacks
 //!
 //! Special handling is required for callback arguments (AsyncReady, Destroy,
@@ -21,33 +56,187 @@
 //! pointer, user data, and optionally a destroy notify.
 
 use std::{
-    ffi::{c_char, c_void},
+    ffi::c_void,
     ops::Deref,
-    sync::{Arc, mpsc},
+    sync::{Arc, Mutex, mpsc},
+    time::Instant,
 };
 
 use anyhow::bail;
+use gtk4::glib::{
+    self,
+    prelude::{Cast as _, ObjectExt as _},
+};
 use libffi::middle as libffi;
 use neon::prelude::*;
 
-use crate::{arg::Arg, ffi, gtk_dispatch, state::GtkThreadState, types::Type, value::Value};
+use crate::{
+    arg::Arg,
+    async_call::{AsyncCompletion, FinishOptions, PendingPromise, Settlement},
+    crash, ffi,
+    gtk_dispatch::{self, TaskPriority},
+    managed::{HandlePin, NativeValue},
+    state::GtkThreadState,
+    trace::{CallTrace, TraceThread},
+    types::{
+        Type,
+        cancellable::{self, AbortListener},
+    },
+    value::{RefUpdate, Value},
+    watchdog::{FrameKind, WaitThread, Watchdog},
+    worker::WorkerPool,
+};
 
-type RefUpdate = (Arc<Root<JsObject>>, Value);
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum CallThread {
+    #[default]
+    Gtk,
+    Worker,
+}
+
+#[derive(Debug, Default)]
+struct CallOptions {
+    thread: CallThread,
+    priority: TaskPriority,
+    finish: Option<FinishOptions>,
+    /// The listeners added to `signal` and to signals among the `_finish`
+    /// arguments, removed when the async call settles.
+    abort_listeners: Vec<AbortListener>,
+}
+
+impl CallOptions {
+    fn from_js(cx: &mut FunctionContext, index: usize) -> NeonResult<Self> {
+        let Some(value) = cx.argument_opt(index) else {
+            return Ok(Self::default());
+        };
+
+        if value.is_a::<JsUndefined, _>(cx) || value.is_a::<JsNull, _>(cx) {
+            return Ok(Self::default());
+        }
+
+        let options = value
+            .downcast::<JsObject, _>(cx)
+            .or_else(|_| cx.throw_type_error("Call options must be an object"))?;
+
+        let thread: Option<Handle<JsString>> = options.get_opt(cx, "thread")?;
+        let thread = match thread.map(|thread| thread.value(cx)).as_deref() {
+            None | Some("gtk") => CallThread::Gtk,
+            Some("worker") => CallThread::Worker,
+            Some(other) => {
+                return cx.throw_type_error(format!(
+                    "Invalid call thread '{other}', expected 'gtk' or 'worker'"
+                ));
+            }
+        };
+
+        let priority: Option<Handle<JsString>> = options.get_opt(cx, "priority")?;
+        let priority = match priority {
+            Some(priority) => {
+                let priority = priority.value(cx);
+                priority
+                    .parse::<TaskPriority>()
+                    .or_else(|err| cx.throw_type_error(err))?
+            }
+            None => TaskPriority::default(),
+        };
+
+        let finish: Option<Handle<JsObject>> = options.get_opt(cx, "finish")?;
+        let signal: Option<Handle<JsObject>> = options.get_opt(cx, "signal")?;
+
+        let mut abort_listeners = Vec::new();
+
+        let finish = match finish {
+            Some(finish) => {
+                let symbol_name = finish.get::<JsString, _, _>(cx, "symbol")?.value(cx);
+                let js_result_type: Handle<JsValue> = finish.get(cx, "resultType")?;
+                let result_type = Type::from_js_value(cx, js_result_type)?;
+                let has_instance = finish
+                    .get_opt::<JsBoolean, _, _>(cx, "instance")?
+                    .is_none_or(|instance| instance.value(cx));
+                let args = match finish.get_opt::<JsArray, _, _>(cx, "args")? {
+                    Some(js_args) => Arg::from_js_array(cx, js_args, &mut abort_listeners)?,
+                    None => Vec::new(),
+                };
+                let cancellable = match signal {
+                    Some(signal) => {
+                        let (cancellable, listener) =
+                            cancellable::cancellable_from_signal(cx, signal)?;
+                        abort_listeners.extend(listener);
+                        Some(cancellable)
+                    }
+                    None => None,
+                };
+
+                Some(FinishOptions {
+                    symbol_name,
+                    result_type,
+                    has_instance,
+                    args,
+                    cancellable,
+                })
+            }
+            None if signal.is_some() => {
+                return cx.throw_type_error("'signal' requires the 'finish' call option");
+            }
+            None => None,
+        };
+
+        if finish.is_some() && thread == CallThread::Worker {
+            return cx.throw_type_error("Async calls cannot run on a worker thread");
+        }
+
+        Ok(Self {
+            thread,
+            priority,
+            finish,
+            abort_listeners,
+        })
+    }
+}
 
 struct CallRequest {
     library_name: String,
     symbol_name: String,
     args: Vec<Arg>,
     result_type: Type,
+    completion: Option<AsyncCompletion>,
+    trace: Option<CallTrace>,
+}
+
+/// A call whose arguments have been encoded and whose symbol has been resolved,
+/// ready to be invoked on any thread.
+struct PreparedCall {
+    request: CallRequest,
+    cif: libffi::Cif,
+    ffi_values: Vec<ffi::FfiValue>,
+    symbol_ptr: libffi::CodePtr,
+    result: Option<ffi::FfiValue>,
+    trace: Option<CallTrace>,
+    /// Thread-safe values passed to a worker call, kept alive until it finishes.
+    _shared: Vec<SharedArg>,
 }
 
+/// Keeps a value passed to a worker call alive until the call finishes.
+enum SharedArg {
+    Object(glib::Object),
+    Pin(HandlePin),
+}
+
+// SAFETY: A prepared call is only moved to a worker thread after every managed
+// argument has been checked with `NativeValue::is_thread_safe`. It is invoked
+// there and moved back to the GTK thread to be finished, so the raw pointers in
+// the encoded arguments are never used on two threads at once.
+unsafe impl Send for PreparedCall {}
+
 impl CallRequest {
-    fn from_js(cx: &mut FunctionContext) -> NeonResult<Self> {
+    /// Parses the call, pushing the `abort` listeners of `AbortSignal`
+    /// arguments to `listeners`.
+    fn from_js(cx: &mut FunctionContext, listeners: &mut Vec<AbortListener>) -> NeonResult<Self> {
         let library_name = cx.argument::<JsString>(0)?.value(cx);
         let symbol_name = cx.argument::<JsString>(1)?.value(cx);
         let js_args = cx.argument::<JsArray>(2)?;
         let js_result_type = cx.argument::<JsObject>(3)?;
-        let args = Arg::from_js_array(cx, js_args)?;
+        let args = Arg::from_js_array(cx, js_args, listeners)?;
         let result_type = Type::from_js_value(cx, js_result_type.upcast())?;
 
         Ok(Self {
@@ -55,32 +244,149 @@ impl CallRequest {
             symbol_name,
             args,
             result_type,
+            completion: None,
+            trace: None,
         })
     }
 
     fn execute(self) -> anyhow::Result<(Value, Vec<RefUpdate>)> {
+        let _frame = Watchdog::global().track(WaitThread::Gtk, FrameKind::Activity, || {
+            format!("executing {}", self.symbol_name)
+        });
+
+        let mut prepared = self.prepare_traced(CallThread::Gtk)?;
+        prepared.invoke();
+        prepared.finish()
+    }
+
+    /// Prepares the call for the given thread, carrying its trace over to the
+    /// prepared call or completing it with the preparation error.
+    fn prepare_traced(mut self, thread: CallThread) -> anyhow::Result<PreparedCall> {
+        let mut trace = self.trace.take();
+
+        if let Some(trace) = &mut trace {
+            trace.dequeued(match thread {
+                CallThread::Gtk => TraceThread::Gtk,
+                CallThread::Worker => TraceThread::Worker,
+            });
+        }
+
+        let prepared = match thread {
+            CallThread::Gtk => self.prepare(Vec::new()),
+            CallThread::Worker => self.prepare_for_worker(),
+        };
+
+        match prepared {
+            Ok(mut prepared) => {
+                prepared.trace = trace;
+                Ok(prepared)
+            }
+            Err(err) => {
+                if let Some(trace) = trace {
+                    trace.complete(Err(&err));
+                }
+                Err(err)
+            }
+        }
+    }
+
+    /// Prepares the call for a worker thread, refusing arguments that are bound
+    /// to the GTK thread. Must be called on the GTK thread.
+    fn prepare_for_worker(self) -> anyhow::Result<PreparedCall> {
+        let mut shared = Vec::new();
+
+        for arg in &self.args {
+            if matches!(arg.ty, Type::Callback(_))
+                && !matches!(arg.value, Value::Null | Value::Undefined)
+            {
+                bail!("Callbacks cannot be passed to worker thread calls");
+            }
+
+            Self::collect_shared(&arg.value, &mut shared)?;
+        }
+
+        self.prepare(shared)
+    }
+
+    fn collect_shared(value: &Value, shared: &mut Vec<SharedArg>) -> anyhow::Result<()> {
+        match value {
+            Value::Object(handle) => {
+                let id = handle.inner();
+                let entry = GtkThreadState::with(|state| {
+                    let native = state.handle_map.get(&id)?;
+                    if !native.is_thread_safe() {
+                        return Some(Err(native.type_name()));
+                    }
+
+                    let object = match native {
+                        NativeValue::GObject(object) => Some(object.clone()),
+                        NativeValue::Boxed(_) | NativeValue::Fundamental(_) => None,
+                    };
+                    let arg = match object {
+                        Some(object) => SharedArg::Object(object),
+                        None => SharedArg::Pin(HandlePin::new(state, id)),
+                    };
+                    Some(Ok(arg))
+                });
+
+                match entry {
+                    Some(Ok(arg)) => shared.push(arg),
+                    Some(Err(type_name)) => bail!(
+                        "{type_name} is not thread-safe and cannot be passed to a worker thread call"
+                    ),
+                    None => return Err(handle.unavailable_error("Object")),
+                }
+            }
+            Value::Array(items) => {
+                for item in items {
+                    Self::collect_shared(item, shared)?;
+                }
+            }
+            Value::Ref(ref_val) => Self::collect_shared(&ref_val.value, shared)?,
+            Value::Cancellable(cancellable) => {
+                shared.push(SharedArg::Object(cancellable.clone().upcast()));
+            }
+            Value::Callback(_) => bail!("Callbacks cannot be passed to worker thread calls"),
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn prepare(mut self, shared: Vec<SharedArg>) -> anyhow::Result<PreparedCall> {
+        match self.result_type {
+            Type::Callback(_) => bail!("Callbacks cannot be return types"),
+            Type::Cancellable => bail!("Cancellables cannot be return types"),
+            Type::Ref(_) => bail!("Ref types cannot be return types"),
+            _ => {}
+        }
+
         let mut arg_types: Vec<libffi::Type> = Vec::with_capacity(self.args.len() + 1);
         for arg in &self.args {
             arg.ty.append_ffi_arg_types(&mut arg_types);
         }
 
+        if self.completion.is_some() {
+            // GCancellable *cancellable, GAsyncReadyCallback callback, gpointer user_data
+            arg_types.extend([
+                libffi::Type::pointer(),
+                libffi::Type::pointer(),
+                libffi::Type::pointer(),
+            ]);
+        }
+
         let cif = libffi::Builder::new()
             .res((&self.result_type).into())
             .args(arg_types)
             .into_cif();
 
-        let ffi_values = self
+        let mut ffi_values = self
             .args
             .clone()
             .into_iter()
             .map(TryInto::<ffi::FfiValue>::try_into)
             .collect::<anyhow::Result<Vec<ffi::FfiValue>>>()?;
 
-        let mut ffi_args: Vec<libffi::Arg> = Vec::with_capacity(ffi_values.len() + 1);
-        for ffi_value in &ffi_values {
-            ffi_value.append_libffi_args(&mut ffi_args);
-        }
-
         // SAFETY: We're loading a symbol from a dynamic library and calling it via libffi.
         // The library/symbol names come from the FFI binding definitions which are trusted.
         let symbol_ptr = unsafe {
@@ -94,73 +400,221 @@ impl CallRequest {
             })?
         };
 
+        if let Some(completion) = self.completion.take() {
+            let cancellable = completion.cancellable_ptr();
+            let user_data = Box::into_raw(Box::new(completion)) as *mut c_void;
+
+            ffi_values.extend([
+                ffi::FfiValue::Ptr(cancellable),
+                ffi::FfiValue::Ptr(crate::trampoline::async_finish_trampoline as *mut c_void),
+                ffi::FfiValue::Ptr(user_data),
+            ]);
+        }
+
+        Ok(PreparedCall {
+            request: self,
+            cif,
+            ffi_values,
+            symbol_ptr,
+            result: None,
+            trace: None,
+            _shared: shared,
+        })
+    }
+}
+
+impl PreparedCall {
+    /// Performs the native call. Safe to run on any thread once prepared.
+    fn invoke(&mut self) {
+        let mut ffi_args: Vec<libffi::Arg> = Vec::with_capacity(self.ffi_values.len() + 1);
+        for ffi_value in &self.ffi_values {
+            ffi_value.append_libffi_args(&mut ffi_args);
+        }
+
+        let _scope = crash::enter_call(
+            &self.request.library_name,
+            &self.request.symbol_name,
+            &self.request.args,
+            &self.request.result_type,
+        );
+        let started = Instant::now();
+
         // SAFETY: The symbol pointer is valid and the CIF matches the function signature.
         // Argument types are validated by the FFI binding definitions.
         let result = unsafe {
-            match self.result_type {
-                Type::Undefined => {
-                    cif.call::<()>(symbol_ptr, &ffi_args);
-                    ffi::FfiValue::Void
-                }
-                Type::Integer(ref int_type) => int_type.kind.call_cif(&cif, symbol_ptr, &ffi_args),
-                Type::Float(ref float_kind) => float_kind.call_cif(&cif, symbol_ptr, &ffi_args),
-                Type::String(_) => {
-                    let ptr = cif.call::<*const c_char>(symbol_ptr, &ffi_args);
-                    ffi::FfiValue::Ptr(ptr as *mut c_void)
-                }
-                Type::Boolean => ffi::FfiValue::U8(cif.call::<u8>(symbol_ptr, &ffi_args)),
-                Type::GObject(_) | Type::Boxed(_) | Type::Struct(_) | Type::Fundamental(_) => {
-                    let ptr = cif.call::<*mut c_void>(symbol_ptr, &ffi_args);
-                    ffi::FfiValue::Ptr(ptr)
-                }
-                Type::Array(_) | Type::HashTable(_) => {
-                    let ptr = cif.call::<*mut c_void>(symbol_ptr, &ffi_args);
-                    ffi::FfiValue::Ptr(ptr)
-                }
-                Type::Null => ffi::FfiValue::Void,
-                Type::Callback(_) => bail!("Callbacks cannot be return types"),
-                Type::Ref(_) => bail!("Ref types cannot be return types"),
-            }
+            self.request
+                .result_type
+                .call_cif(&self.cif, self.symbol_ptr, &ffi_args)
+        };
+
+        if let Some(trace) = &mut self.trace {
+            trace.invoked(started);
+        }
+
+        self.result = Some(result);
+    }
+
+    /// Decodes the return value and `Ref` out-parameters, completing the
+    /// call's trace. Must be called on the GTK thread.
+    fn finish(mut self) -> anyhow::Result<(Value, Vec<RefUpdate>)> {
+        let trace = self.trace.take();
+        let result = self.decode();
+
+        if let Some(trace) = trace {
+            trace.complete(result.as_ref().map(|(value, _)| value));
+        }
+
+        result
+    }
+
+    fn decode(&self) -> anyhow::Result<(Value, Vec<RefUpdate>)> {
+        let Some(result) = &self.result else {
+            bail!("FFI call finished before being invoked");
         };
 
+        let args = &self.request.args;
         let mut ref_updates = Vec::new();
 
-        for (i, arg) in self.args.iter().enumerate() {
+        for (i, arg) in args.iter().enumerate() {
             if let Value::Ref(ref_val) = &arg.value {
                 let new_value = Value::from_ffi_value_with_args(
-                    &ffi_values[i],
+                    &self.ffi_values[i],
                     &arg.ty,
-                    &ffi_values,
-                    &self.args,
+                    &self.ffi_values,
+                    args,
                 )?;
                 ref_updates.push((ref_val.js_obj.clone(), new_value));
             }
         }
 
-        let return_value =
-            Value::from_ffi_value_with_args(&result, &self.result_type, &ffi_values, &self.args)?;
+        let return_value = Value::from_ffi_value_with_args(
+            result,
+            &self.request.result_type,
+            &self.ffi_values,
+            args,
+        )?;
         Ok((return_value, ref_updates))
     }
 }
 
+fn schedule_worker_call(
+    request: CallRequest,
+    priority: TaskPriority,
+    tx: mpsc::Sender<anyhow::Result<(Value, Vec<RefUpdate>)>>,
+) {
+    gtk_dispatch::GtkDispatcher::global().schedule_with_priority(priority, move || {
+        let mut prepared = match request.prepare_traced(CallThread::Worker) {
+            Ok(prepared) => prepared,
+            Err(err) => {
+                let _ = tx.send(Err(err));
+                return;
+            }
+        };
+
+        WorkerPool::global().execute(move || {
+            prepared.invoke();
+
+            gtk_dispatch::GtkDispatcher::global().schedule_with_priority(priority, move || {
+                let _ = tx.send(prepared.finish());
+            });
+        });
+    });
+}
+
+fn call_async<'a>(
+    mut cx: FunctionContext<'a>,
+    mut request: CallRequest,
+    priority: TaskPriority,
+    finish: FinishOptions,
+    abort_listeners: Vec<AbortListener>,
+) -> JsResult<'a, JsValue> {
+    let (deferred, promise) = cx.promise();
+    let pending: PendingPromise = Arc::new(Mutex::new(Some(Settlement {
+        deferred,
+        abort_listeners,
+    })));
+    let channel = cx.channel();
+
+    request.completion = Some(AsyncCompletion::new(
+        request.library_name.clone(),
+        finish,
+        pending.clone(),
+        channel,
+    ));
+
+    let dispatcher = gtk_dispatch::GtkDispatcher::global();
+    dispatcher.enter_js_wait();
+
+    let (tx, rx) = mpsc::channel();
+    dispatcher.schedule_with_priority(priority, move || {
+        let _ = tx.send(request.execute().map(|_| ()));
+    });
+
+    let result = dispatcher
+        .wait_for_gtk_result(&mut cx, &rx)
+        .or_else(|err| cx.throw_error(err.to_string()))?;
+
+    if let Err(err) = result {
+        let settlement = pending.lock().unwrap().take();
+
+        if let Some(Settlement {
+            deferred,
+            abort_listeners,
+        }) = settlement
+        {
+            for listener in abort_listeners {
+                listener.detach(&mut cx)?;
+            }
+
+            let error = cx.error(format!("Error during FFI call: {err}"))?;
+            deferred.reject(&mut cx, error);
+        }
+    }
+
+    Ok(promise.upcast())
+}
+
 pub fn call(mut cx: FunctionContext) -> JsResult<JsValue> {
     if !gtk_dispatch::GtkDispatcher::global().is_started() {
         return cx.throw_error("GTK application has not been started. Call start() first.");
     }
 
-    let request = CallRequest::from_js(&mut cx)?;
+    let mut abort_listeners = Vec::new();
+    let mut request = CallRequest::from_js(&mut cx, &mut abort_listeners)?;
+    let mut options = CallOptions::from_js(&mut cx, 4)?;
+    abort_listeners.append(&mut options.abort_listeners);
+    request.trace = CallTrace::begin(&request.library_name, &request.symbol_name, &request.args);
+
+    let _frame = Watchdog::global().track(WaitThread::Js, FrameKind::Activity, || {
+        format!("call {}::{}", request.library_name, request.symbol_name)
+    });
+
+    if let Some(finish) = options.finish.take() {
+        return call_async(cx, request, options.priority, finish, abort_listeners);
+    }
 
     let (tx, rx) = mpsc::channel::<anyhow::Result<(Value, Vec<RefUpdate>)>>();
 
     gtk_dispatch::GtkDispatcher::global().enter_js_wait();
-    gtk_dispatch::GtkDispatcher::global().schedule(move || {
-        let _ = tx.send(request.execute());
-    });
+
+    match options.thread {
+        CallThread::Gtk => gtk_dispatch::GtkDispatcher::global().schedule_with_priority(
+            options.priority,
+            move || {
+                let _ = tx.send(request.execute());
+            },
+        ),
+        CallThread::Worker => schedule_worker_call(request, options.priority, tx),
+    }
 
     let result = gtk_dispatch::GtkDispatcher::global()
         .wait_for_gtk_result(&mut cx, &rx)
         .or_else(|err| cx.throw_error(err.to_string()))?;
 
+    for listener in abort_listeners {
+        listener.detach(&mut cx)?;
+    }
+
     let (value, ref_updates) =
         result.or_else(|err| cx.throw_error(format!("Error during FFI call: {err}")))?;
 
@@ -0,0 +1,103 @@
+//! Diagnostics exports.
+//!
+//! The [`debug_handles`] function captures a [`HandleSnapshot`] of every live
+//! handle on the GTK thread and returns it to JavaScript. See
+//! [`crate::diagnostics`] for the snapshot contents and stack capture.
+//!
+//! The [`configure_watchdog`] function enables, disables or reconfigures the
+//! deadlock [`crate::watchdog`] at runtime.
+//!
+//! The [`configure_tracing`], [`dump_trace`] and [`clear_trace`] functions
+//! control the FFI call [`crate::trace`] buffer.
+//!
+//! The [`configure_crash_handler`] function toggles [`crate::crash`] reports.
+
+use std::time::Duration;
+
+use neon::prelude::*;
+
+use crate::crash;
+use crate::diagnostics::HandleSnapshot;
+use crate::gtk_dispatch;
+use crate::trace::{self, TraceFormat, Tracer};
+use crate::watchdog::Watchdog;
+
+pub fn debug_handles(mut cx: FunctionContext) -> JsResult<JsObject> {
+    let dispatcher = gtk_dispatch::GtkDispatcher::global();
+
+    if !dispatcher.is_started() {
+        return HandleSnapshot::default().to_js_value(&mut cx);
+    }
+
+    dispatcher.enter_js_wait();
+    let rx = dispatcher.run_on_gtk_thread(HandleSnapshot::capture);
+
+    let snapshot = dispatcher
+        .wait_for_gtk_result(&mut cx, &rx)
+        .or_else(|err| cx.throw_error(err.to_string()))?;
+
+    snapshot.to_js_value(&mut cx)
+}
+
+pub fn configure_watchdog(mut cx: FunctionContext) -> JsResult<JsUndefined> {
+    let options = cx.argument::<JsObject>(0)?;
+
+    let timeout: Option<Handle<JsNumber>> = options.get_opt(&mut cx, "timeoutMs")?;
+    let timeout = timeout
+        .map(|timeout| timeout.value(&mut cx))
+        .filter(|timeout| *timeout > 0.0)
+        .map(|timeout| Duration::from_millis(timeout as u64));
+
+    let abort: Option<Handle<JsBoolean>> = options.get_opt(&mut cx, "abort")?;
+    let abort = abort.is_some_and(|abort| abort.value(&mut cx));
+
+    Watchdog::global().configure(timeout, abort);
+
+    Ok(cx.undefined())
+}
+
+pub fn configure_tracing(mut cx: FunctionContext) -> JsResult<JsUndefined> {
+    let options = cx.argument::<JsObject>(0)?;
+
+    let enabled: Option<Handle<JsBoolean>> = options.get_opt(&mut cx, "enabled")?;
+    let enabled = enabled.is_none_or(|enabled| enabled.value(&mut cx));
+
+    let capacity: Option<Handle<JsNumber>> = options.get_opt(&mut cx, "capacity")?;
+    let capacity = match capacity.map(|capacity| capacity.value(&mut cx)) {
+        Some(capacity) if capacity < 1.0 => {
+            return cx.throw_range_error("Trace capacity must be at least 1");
+        }
+        Some(capacity) => capacity as usize,
+        None => trace::DEFAULT_CAPACITY,
+    };
+
+    Tracer::global().configure(enabled.then_some(capacity));
+
+    Ok(cx.undefined())
+}
+
+pub fn dump_trace(mut cx: FunctionContext) -> JsResult<JsString> {
+    let format: Option<Handle<JsString>> = cx
+        .argument_opt(0)
+        .and_then(|format| format.downcast(&mut cx).ok());
+    let format = match format {
+        Some(format) => format
+            .value(&mut cx)
+            .parse::<TraceFormat>()
+            .or_else(|err| cx.throw_type_error(err))?,
+        None => TraceFormat::default(),
+    };
+
+    Ok(cx.string(Tracer::global().export(format)))
+}
+
+pub fn clear_trace(mut cx: FunctionContext) -> JsResult<JsUndefined> {
+    Tracer::global().clear();
+    Ok(cx.undefined())
+}
+
+pub fn configure_crash_handler(mut cx: FunctionContext) -> JsResult<JsUndefined> {
+    let enabled = cx.argument::<JsBoolean>(0)?.value(&mut cx);
+    crash::configure(enabled);
+    Ok(cx.undefined())
+}
@@ -0,0 +1,52 @@
+//! Enum and flags introspection.
+//!
+//! The [`enum_values`] function looks up the GType returned by a
+//! `*_get_type` function and returns its [`EnumInfo`] to JavaScript as
+//! `{ typeName, flags, values: [{ name, nick, value }] }`, with the values in
+//! declaration order. Tooling uses it to generate symbolic names or to show
+//! them for raw numbers.
+//!
+//! The lookup registers and reads the type class, so it runs on the GTK thread.
+
+use neon::prelude::*;
+
+use crate::enums::EnumInfo;
+use crate::gtk_dispatch;
+
+pub fn enum_values(mut cx: FunctionContext) -> JsResult<JsObject> {
+    let library = cx.argument::<JsString>(0)?.value(&mut cx);
+    let get_type_fn = cx.argument::<JsString>(1)?.value(&mut cx);
+
+    let dispatcher = gtk_dispatch::GtkDispatcher::global();
+    dispatcher.enter_js_wait();
+    let rx = dispatcher.run_on_gtk_thread(move || EnumInfo::from_lib(&library, &get_type_fn));
+
+    let info = dispatcher
+        .wait_for_gtk_result(&mut cx, &rx)
+        .or_else(|err| cx.throw_error(err.to_string()))?
+        .or_else(|err| cx.throw_error(format!("Error reading enum values: {err}")))?;
+
+    let result = cx.empty_object();
+
+    let type_name = cx.string(&info.type_name);
+    result.set(&mut cx, "typeName", type_name)?;
+    let is_flags = cx.boolean(info.is_flags);
+    result.set(&mut cx, "flags", is_flags)?;
+
+    let values = cx.empty_array();
+    for (index, member) in info.members.iter().enumerate() {
+        let js_member = cx.empty_object();
+
+        let name = cx.string(&member.name);
+        js_member.set(&mut cx, "name", name)?;
+        let nick = cx.string(&member.nick);
+        js_member.set(&mut cx, "nick", nick)?;
+        let value = cx.number(member.value as f64);
+        js_member.set(&mut cx, "value", value)?;
+
+        values.set(&mut cx, index as u32, js_member)?;
+    }
+    result.set(&mut cx, "values", values)?;
+
+    Ok(result)
+}
@@ -4,35 +4,130 @@
 //! byte offsets. This enables JavaScript to access struct fields that aren't
 //! exposed via GTK property accessors.
 //!
-//! ## Read Types
+//! ## Field Types
 //!
-//! - `Integer` (all sizes and signs)
+//! `read` and `write` support:
+//! - `Integer` (all sizes and signs, optionally a bitfield)
 //! - `Float` (f32, f64)
-//! - `Boolean`
-//! - `String` (as pointer to C string)
+//! - `Boolean` (one byte) and `GBoolean` (a 4-byte `gboolean`)
+//! - `String` (as pointer to C string, in the type's `encoding`)
 //! - `GObject` (as pointer to object)
 //! - `Boxed` (as pointer to boxed value)
+//! - `Fundamental` (as pointer to instance; write only)
+//! - `Struct` (embedded, `size` bytes)
+//! - `Array` with the `fixed` kind (embedded, `fixedSize` elements)
 //!
-//! ## Write Types
+//! An embedded struct is read as a borrowed view into the parent's memory that
+//! keeps the parent alive (see [`crate::managed::ParentRef`]). A fixed array is
+//! read as a JS array and written from one with exactly `fixedSize` elements.
 //!
-//! Used for struct initialization in constructors:
-//! - `Integer` (all sizes and signs)
-//! - `Float` (f32, f64)
-//! - `Boolean`
+//! ## Bitfields
+//!
+//! An `Integer` type with `bitWidth` (and optionally `bitOffset`, default 0)
+//! accesses that many bits of the storage unit at the field offset, counting
+//! from its least significant bit. Reads sign-extend signed bitfields; writes
+//! keep the other bits of the unit and reject values that don't fit.
+//!
+//! ## Safety Checks
+//!
+//! When the size of a handle's memory is known (see
+//! [`NativeHandle::require_memory`]), accesses that extend past its end are
+//! rejected, including the pointer field read by `readPointer`/`writePointer`
+//! and the bytes copied from the `writePointer` source. Memory reached through
+//! a pointer field has no known extent and is not checked.
+//!
+//! `write` and `writePointer` refuse to write through borrowed handles, whose
+//! memory is owned elsewhere, unless the `allowBorrowed` option is set.
+//!
+//! ## Pointer Fields
+//!
+//! `write` stores strings, GObjects, boxed values and fundamentals as pointers.
+//! The field type's ownership says whether the struct owns what it points to:
+//!
+//! | Type | `full` | `borrowed` |
+//! |------|--------|------------|
+//! | String | `g_strdup` copy, previous value freed | rejected, except `null` |
+//! | GObject | referenced, previous value unreferenced | stored as is |
+//! | Boxed | `g_boxed_copy`, previous value freed (requires a GType) | stored as is |
+//! | Fundamental | `ref_func`, previous value released with `unref_func` | stored as is |
+//!
+//! Writing `null` clears the field, releasing the previous value when owned.
+//! Writing an embedded `Struct` copies its `size` bytes from the source handle.
 
-use std::ffi::{CStr, c_char, c_void};
+use std::ffi::{c_char, c_void};
+use std::mem::size_of;
 
 use anyhow::bail;
-use gtk4::glib::{self, translate::FromGlibPtrNone as _};
+use gtk4::glib::{
+    self,
+    translate::{FromGlibPtrNone as _, IntoGlib as _},
+};
 use neon::prelude::*;
 
 use crate::{
+    crash,
+    diagnostics::HandleKind,
     gtk_dispatch,
     managed::{Boxed, NativeHandle, NativeValue},
-    types::Type,
+    types::{
+        ArrayKind, ArrayType, BoxedType, FundamentalType, GObjectType, StringType, StructType, Type,
+    },
     value::Value,
 };
 
+const POINTER_SIZE: usize = size_of::<*mut c_void>();
+
+/// Number of bytes a field of the given type occupies in memory.
+fn field_size(field_type: &Type) -> anyhow::Result<usize> {
+    Ok(match field_type {
+        Type::Integer(int_type) => int_type.kind.byte_size(),
+        Type::Float(float_kind) => float_kind.byte_size(),
+        Type::Boolean => 1,
+        Type::GBoolean => 4,
+        Type::String(_) | Type::GObject(_) | Type::Boxed(_) | Type::Fundamental(_) => POINTER_SIZE,
+        Type::Struct(struct_type) => struct_type.size.ok_or_else(|| {
+            anyhow::anyhow!("Struct field {} requires a size", struct_type.type_name)
+        })?,
+        Type::Array(array_type) => {
+            let (count, element_len) = fixed_array_layout(array_type)?;
+            count
+                .checked_mul(element_len)
+                .ok_or_else(|| anyhow::anyhow!("Fixed array of {count} elements is too large"))?
+        }
+        _ => bail!("Unsupported field type: {:?}", field_type),
+    })
+}
+
+/// Element count and per-element size of an inline array field.
+fn fixed_array_layout(array_type: &ArrayType) -> anyhow::Result<(usize, usize)> {
+    let ArrayKind::Fixed { size: count } = array_type.kind else {
+        bail!(
+            "Only fixed-size arrays can be stored inline in a field, got {:?}",
+            array_type.kind
+        );
+    };
+
+    let element_len = match array_type.element_size {
+        Some(element_size) => element_size,
+        None => field_size(&array_type.item_type)?,
+    };
+
+    Ok((count, element_len))
+}
+
+/// Parses the `{ allowBorrowed }` options object of a write.
+fn allow_borrowed(cx: &mut FunctionContext, index: usize) -> NeonResult<bool> {
+    let Some(options) = cx
+        .argument_opt(index)
+        .and_then(|value| value.downcast::<JsObject, _>(cx).ok())
+    else {
+        return Ok(false);
+    };
+
+    let allow: Option<Handle<JsBoolean>> = options.get_opt(cx, "allowBorrowed")?;
+    Ok(allow.is_some_and(|allow| allow.value(cx)))
+}
+
 struct ReadRequest {
     handle: NativeHandle,
     field_type: Type,
@@ -62,76 +157,115 @@ impl ReadRequest {
     }
 
     fn execute(self) -> anyhow::Result<Value> {
-        let base_ptr = self.handle.require_non_null_ptr()?;
+        let _scope =
+            crash::enter_access(|| format!("read {} at offset {}", self.field_type, self.offset));
+        let memory = self.handle.require_memory()?;
 
         let field_ptr = if let Some(ptr_offset) = self.ptr_offset {
-            let ptr_field = unsafe { (base_ptr as *const u8).add(ptr_offset) as *const *const u8 };
+            let ptr_field = memory.field_ptr(ptr_offset, POINTER_SIZE)? as *const *const u8;
             let dereferenced_ptr = unsafe { ptr_field.read_unaligned() };
             if dereferenced_ptr.is_null() {
                 anyhow::bail!("Pointer at offset {} is null", ptr_offset);
             }
             unsafe { dereferenced_ptr.add(self.offset) }
         } else {
-            unsafe { (base_ptr as *const u8).add(self.offset) }
+            memory.field_ptr(self.offset, field_size(&self.field_type)?)? as *const u8
         };
 
-        match self.field_type {
-            Type::Integer(int_type) => {
-                let number = int_type.kind.read_ptr(field_ptr);
-                Ok(Value::Number(number))
-            }
-            Type::Float(float_kind) => {
-                let number = float_kind.read_ptr(field_ptr);
-                Ok(Value::Number(number))
-            }
-            Type::Boolean => {
-                // SAFETY: field_ptr is valid and within bounds (checked by field_ptr_const)
-                let value = unsafe { field_ptr.cast::<u8>().read_unaligned() != 0 };
-                Ok(Value::Boolean(value))
-            }
-            Type::String(_) => {
-                // SAFETY: field_ptr is valid and contains a C string pointer
-                let str_ptr = unsafe { field_ptr.cast::<*const c_char>().read_unaligned() };
-
-                if str_ptr.is_null() {
-                    return Ok(Value::Null);
-                }
-
-                // SAFETY: str_ptr is a valid null-terminated C string from GTK
-                let c_str = unsafe { CStr::from_ptr(str_ptr) };
-                let string = c_str.to_str()?.to_string();
-                Ok(Value::String(string))
-            }
-            Type::GObject(_) => {
-                // SAFETY: field_ptr is valid and contains a GObject pointer
-                let obj_ptr = unsafe {
-                    field_ptr
-                        .cast::<*mut glib::gobject_ffi::GObject>()
-                        .read_unaligned()
-                };
-
-                if obj_ptr.is_null() {
-                    return Ok(Value::Null);
-                }
-
-                // SAFETY: obj_ptr is a valid GObject from GTK
-                let object = unsafe { glib::Object::from_glib_none(obj_ptr) };
-                Ok(Value::Object(NativeValue::GObject(object).into()))
+        // Embedded structs share the handle's writability, but memory reached
+        // through a pointer field belongs to whatever it points at.
+        let writable = memory.owned && self.ptr_offset.is_none();
+        read_value(field_ptr, &self.field_type, &self.handle, writable)
+    }
+}
+
+/// Reads a field of the given type at `field_ptr`. Embedded structs are
+/// returned as borrowed views that keep `parent` alive, and that can be
+/// written without `allowBorrowed` when `writable` is set.
+fn read_value(
+    field_ptr: *const u8,
+    field_type: &Type,
+    parent: &NativeHandle,
+    writable: bool,
+) -> anyhow::Result<Value> {
+    match field_type {
+        Type::Integer(int_type) => {
+            let number = match int_type.bit_field {
+                Some(bits) => int_type.kind.read_bits(field_ptr, bits)?,
+                None => int_type.kind.read_ptr(field_ptr),
+            };
+            int_type.number_to_value(number)
+        }
+        Type::Float(float_kind) => {
+            let number = float_kind.read_ptr(field_ptr);
+            Ok(Value::Number(number))
+        }
+        Type::Boolean => {
+            // SAFETY: field_ptr is valid and within bounds (checked by field_ptr_const)
+            let value = unsafe { field_ptr.cast::<u8>().read_unaligned() != 0 };
+            Ok(Value::Boolean(value))
+        }
+        Type::GBoolean => {
+            // SAFETY: field_ptr is valid and within bounds (checked by field_ptr_const)
+            let value = unsafe { field_ptr.cast::<i32>().read_unaligned() != 0 };
+            Ok(Value::Boolean(value))
+        }
+        Type::String(string_type) => {
+            // SAFETY: field_ptr is valid and contains a C string pointer
+            let str_ptr = unsafe { field_ptr.cast::<*const c_char>().read_unaligned() };
+
+            // SAFETY: str_ptr is null or a valid null-terminated C string from GTK
+            unsafe { string_type.decode_ptr(str_ptr) }
+        }
+        Type::GObject(_) => {
+            // SAFETY: field_ptr is valid and contains a GObject pointer
+            let obj_ptr = unsafe {
+                field_ptr
+                    .cast::<*mut glib::gobject_ffi::GObject>()
+                    .read_unaligned()
+            };
+
+            if obj_ptr.is_null() {
+                return Ok(Value::Null);
             }
-            Type::Boxed(ref boxed_type) => {
-                // SAFETY: field_ptr is valid and contains a boxed pointer
-                let boxed_ptr = unsafe { field_ptr.cast::<*mut c_void>().read_unaligned() };
 
-                if boxed_ptr.is_null() {
-                    return Ok(Value::Null);
-                }
+            // SAFETY: obj_ptr is a valid GObject from GTK
+            let object = unsafe { glib::Object::from_glib_none(obj_ptr) };
+            Ok(Value::Object(NativeValue::GObject(object).into()))
+        }
+        Type::Boxed(ref boxed_type) => {
+            // SAFETY: field_ptr is valid and contains a boxed pointer
+            let boxed_ptr = unsafe { field_ptr.cast::<*mut c_void>().read_unaligned() };
 
-                let gtype = boxed_type.gtype();
-                let boxed = Boxed::from_glib_none(gtype, boxed_ptr)?;
-                Ok(Value::Object(NativeValue::Boxed(boxed).into()))
+            if boxed_ptr.is_null() {
+                return Ok(Value::Null);
             }
-            _ => bail!("Unsupported field type for read: {:?}", self.field_type),
+
+            let gtype = boxed_type.gtype();
+            let boxed = Boxed::from_glib_none(gtype, boxed_ptr)?;
+            Ok(Value::Object(NativeValue::Boxed(boxed).into()))
+        }
+        Type::Struct(_) => {
+            let view = Boxed::borrowed(None, field_ptr.cast_mut().cast())
+                .with_size(Some(field_size(field_type)?))
+                .with_parent(parent.parent_ref()?)
+                .with_writable_view(writable);
+            Ok(Value::Object(NativeValue::Boxed(view).into()))
         }
+        Type::Array(array_type) => {
+            let (count, element_len) = fixed_array_layout(array_type)?;
+
+            let items = (0..count)
+                .map(|i| {
+                    // SAFETY: The whole array was bounds-checked by the caller.
+                    let element_ptr = unsafe { field_ptr.add(i * element_len) };
+                    read_value(element_ptr, &array_type.item_type, parent, writable)
+                })
+                .collect::<anyhow::Result<Vec<_>>>()?;
+
+            Ok(Value::Array(items))
+        }
+        _ => bail!("Unsupported field type for read: {:?}", field_type),
     }
 }
 
@@ -153,6 +287,7 @@ struct WriteRequest {
     field_type: Type,
     offset: usize,
     value: Value,
+    allow_borrowed: bool,
 }
 
 impl WriteRequest {
@@ -164,34 +299,234 @@ impl WriteRequest {
         let field_type = Type::from_js_value(cx, js_type.upcast())?;
         let value = Value::from_js_value(cx, js_value)?;
         let handle = *handle.as_inner();
+        let allow_borrowed = allow_borrowed(cx, 4)?;
 
         Ok(Self {
             handle,
             field_type,
             offset,
             value,
+            allow_borrowed,
         })
     }
 
     fn execute(self) -> anyhow::Result<()> {
-        let base_ptr = self.handle.require_non_null_ptr()?;
-        let field_ptr = unsafe { (base_ptr as *mut u8).add(self.offset) };
+        let _scope =
+            crash::enter_access(|| format!("write {} at offset {}", self.field_type, self.offset));
+        let memory = self.handle.require_memory()?;
+        memory.require_writable(self.allow_borrowed)?;
+        let field_len = field_size(&self.field_type)?;
+        let field_ptr = memory.field_ptr(self.offset, field_len)?;
+
+        write_value(field_ptr, field_len, &self.field_type, &self.value)
+    }
+}
 
-        match (&self.field_type, &self.value) {
-            (Type::Integer(int_type), Value::Number(n)) => {
-                int_type.kind.write_ptr(field_ptr, *n);
+/// Writes `value` to a field of `field_len` bytes at `field_ptr`.
+fn write_value(
+    field_ptr: *mut u8,
+    field_len: usize,
+    field_type: &Type,
+    value: &Value,
+) -> anyhow::Result<()> {
+    match (field_type, value) {
+        (Type::Integer(int_type), Value::Number(_) | Value::String(_) | Value::Array(_)) => {
+            let number = int_type.to_number(value)?;
+            match int_type.bit_field {
+                Some(bits) => int_type.kind.write_bits(field_ptr, bits, number)?,
+                None => int_type.kind.write_ptr(field_ptr, number),
+            }
+        }
+        (Type::Float(float_kind), Value::Number(n)) => {
+            float_kind.write_ptr(field_ptr, *n);
+        }
+        (Type::Boolean, Value::Boolean(b)) => unsafe {
+            field_ptr.cast::<u8>().write_unaligned(u8::from(*b));
+        },
+        (Type::GBoolean, Value::Boolean(b)) => unsafe {
+            field_ptr.cast::<i32>().write_unaligned(i32::from(*b));
+        },
+        (Type::String(string_type), value) => write_string(field_ptr, string_type, value)?,
+        (Type::GObject(gobject_type), value) => write_gobject(field_ptr, gobject_type, value)?,
+        (Type::Boxed(boxed_type), value) => write_boxed(field_ptr, boxed_type, value)?,
+        (Type::Fundamental(fundamental_type), value) => {
+            write_fundamental(field_ptr, fundamental_type, value)?;
+        }
+        (Type::Struct(struct_type), value) => {
+            write_struct(field_ptr, field_len, struct_type, value)?;
+        }
+        (Type::Array(array_type), Value::Array(items)) => {
+            let (count, element_len) = fixed_array_layout(array_type)?;
+
+            if items.len() != count {
+                bail!(
+                    "Expected {count} elements for a fixed array field, got {}",
+                    items.len()
+                );
             }
-            (Type::Float(float_kind), Value::Number(n)) => {
-                float_kind.write_ptr(field_ptr, *n);
+
+            for (i, item) in items.iter().enumerate() {
+                // SAFETY: The whole array was bounds-checked by the caller.
+                let element_ptr = unsafe { field_ptr.add(i * element_len) };
+                write_value(element_ptr, element_len, &array_type.item_type, item)?;
             }
-            (Type::Boolean, Value::Boolean(b)) => unsafe {
-                field_ptr.cast::<u8>().write_unaligned(u8::from(*b));
-            },
-            _ => bail!("Unsupported field type for write: {:?}", self.field_type),
         }
+        _ => bail!("Unsupported field type for write: {:?}", field_type),
+    }
 
-        Ok(())
+    Ok(())
+}
+
+/// Stores `ptr` in a pointer field and returns the pointer it replaced.
+///
+/// # Safety
+///
+/// `field_ptr` must be valid for a pointer-sized read and write.
+unsafe fn swap_field_ptr(field_ptr: *mut u8, ptr: *mut c_void) -> *mut c_void {
+    let slot = field_ptr.cast::<*mut c_void>();
+
+    // SAFETY: Guaranteed by the caller.
+    unsafe {
+        let previous = slot.read_unaligned();
+        slot.write_unaligned(ptr);
+        previous
+    }
+}
+
+fn write_string(field_ptr: *mut u8, string_type: &StringType, value: &Value) -> anyhow::Result<()> {
+    let owned = string_type.ownership.is_full();
+
+    let str_ptr = match value {
+        Value::Null | Value::Undefined => std::ptr::null_mut(),
+        _ if !owned => bail!(
+            "Cannot write a borrowed string: nothing would own its memory. \
+             Use 'full' ownership to store a copy the struct owns"
+        ),
+        value => {
+            let c_string = string_type.encode_cstring(value)?;
+            unsafe { glib::ffi::g_strdup(c_string.as_ptr()) }
+        }
+    };
+
+    let previous = unsafe { swap_field_ptr(field_ptr, str_ptr.cast()) };
+
+    if owned {
+        unsafe { glib::ffi::g_free(previous) };
+    }
+
+    Ok(())
+}
+
+fn write_gobject(
+    field_ptr: *mut u8,
+    gobject_type: &GObjectType,
+    value: &Value,
+) -> anyhow::Result<()> {
+    let owned = gobject_type.ownership.is_full();
+    let obj_ptr = value.typed_object_ptr(HandleKind::GObject, gobject_type.type_name.as_deref())?;
+
+    // Take the new reference before dropping the old one, in case they are
+    // the same object.
+    if owned && !obj_ptr.is_null() {
+        unsafe { glib::gobject_ffi::g_object_ref(obj_ptr.cast()) };
+    }
+
+    let previous = unsafe { swap_field_ptr(field_ptr, obj_ptr) };
+
+    if owned && !previous.is_null() {
+        unsafe { glib::gobject_ffi::g_object_unref(previous.cast()) };
+    }
+
+    Ok(())
+}
+
+fn write_boxed(field_ptr: *mut u8, boxed_type: &BoxedType, value: &Value) -> anyhow::Result<()> {
+    let gtype = boxed_type.gtype();
+    let boxed_ptr = value.typed_object_ptr(HandleKind::Boxed, gtype.map(|gtype| gtype.name()))?;
+
+    if boxed_type.ownership.is_borrowed() {
+        unsafe { swap_field_ptr(field_ptr, boxed_ptr) };
+        return Ok(());
     }
+
+    let Some(gtype) = gtype else {
+        bail!(
+            "Cannot own boxed field {} without a GType to copy and free it",
+            boxed_type.type_name
+        );
+    };
+
+    let copied = if boxed_ptr.is_null() {
+        boxed_ptr
+    } else {
+        unsafe { glib::gobject_ffi::g_boxed_copy(gtype.into_glib(), boxed_ptr) }
+    };
+
+    let previous = unsafe { swap_field_ptr(field_ptr, copied) };
+
+    if !previous.is_null() {
+        unsafe { glib::gobject_ffi::g_boxed_free(gtype.into_glib(), previous) };
+    }
+
+    Ok(())
+}
+
+fn write_fundamental(
+    field_ptr: *mut u8,
+    fundamental_type: &FundamentalType,
+    value: &Value,
+) -> anyhow::Result<()> {
+    let mut ptr = value.typed_object_ptr(
+        HandleKind::Fundamental,
+        fundamental_type.type_name.as_deref(),
+    )?;
+
+    if fundamental_type.ownership.is_borrowed() {
+        unsafe { swap_field_ptr(field_ptr, ptr) };
+        return Ok(());
+    }
+
+    let (ref_fn, unref_fn) = fundamental_type.lookup_fns()?;
+
+    if let Some(ref_fn) = ref_fn
+        && !ptr.is_null()
+    {
+        // Copy-based fundamentals return a new pointer from their ref function.
+        ptr = unsafe { ref_fn(ptr) };
+    }
+
+    let previous = unsafe { swap_field_ptr(field_ptr, ptr) };
+
+    if let Some(unref_fn) = unref_fn
+        && !previous.is_null()
+    {
+        unsafe { unref_fn(previous) };
+    }
+
+    Ok(())
+}
+
+fn write_struct(
+    field_ptr: *mut u8,
+    size: usize,
+    struct_type: &StructType,
+    value: &Value,
+) -> anyhow::Result<()> {
+    let Value::Object(handle) = value else {
+        bail!(
+            "Expected an Object for struct field {}, got {:?}",
+            struct_type.type_name,
+            value
+        );
+    };
+
+    let source_ptr = handle.require_memory()?.field_ptr(0, size)?;
+
+    // SAFETY: Both ranges were bounds-checked for `size` bytes. The source may
+    // be the field itself, so the copy allows overlap.
+    unsafe { std::ptr::copy(source_ptr.cast_const(), field_ptr, size) };
+
+    Ok(())
 }
 
 pub fn write(mut cx: FunctionContext) -> JsResult<JsUndefined> {
@@ -227,10 +562,15 @@ impl ReadPointerRequest {
     }
 
     fn execute(self) -> anyhow::Result<NativeHandle> {
-        let base_ptr = self.handle.require_non_null_ptr()?;
-
-        let ptr_field =
-            unsafe { (base_ptr as *const u8).add(self.ptr_offset) as *const *mut c_void };
+        let _scope = crash::enter_access(|| {
+            format!(
+                "readPointer at offset {} + {}",
+                self.ptr_offset, self.element_offset
+            )
+        });
+        let memory = self.handle.require_memory()?;
+
+        let ptr_field = memory.field_ptr(self.ptr_offset, POINTER_SIZE)? as *const *mut c_void;
         let array_ptr = unsafe { ptr_field.read_unaligned() };
 
         if array_ptr.is_null() {
@@ -238,7 +578,7 @@ impl ReadPointerRequest {
         }
 
         let element_ptr = unsafe { (array_ptr as *mut u8).add(self.element_offset) as *mut c_void };
-        let boxed = Boxed::borrowed(None, element_ptr);
+        let boxed = Boxed::borrowed(None, element_ptr).with_parent(self.handle.parent_ref()?);
         Ok(NativeValue::Boxed(boxed).into())
     }
 }
@@ -253,7 +593,7 @@ pub fn read_pointer(mut cx: FunctionContext) -> JsResult<JsValue> {
         .or_else(|err| cx.throw_error(format!("Error receiving read_pointer result: {err}")))?
         .or_else(|err| cx.throw_error(format!("Error during read_pointer: {err}")))?;
 
-    Ok(cx.boxed(handle).upcast())
+    Ok(handle.into_js(&mut cx).upcast())
 }
 
 struct WritePointerRequest {
@@ -262,6 +602,7 @@ struct WritePointerRequest {
     element_offset: usize,
     source_handle: NativeHandle,
     size: usize,
+    allow_borrowed: bool,
 }
 
 impl WritePointerRequest {
@@ -271,6 +612,7 @@ impl WritePointerRequest {
         let element_offset = cx.argument::<JsNumber>(2)?.value(cx) as usize;
         let source_handle = cx.argument::<JsBox<NativeHandle>>(3)?;
         let size = cx.argument::<JsNumber>(4)?.value(cx) as usize;
+        let allow_borrowed = allow_borrowed(cx, 5)?;
 
         Ok(Self {
             dest_handle: *dest_handle.as_inner(),
@@ -278,15 +620,25 @@ impl WritePointerRequest {
             element_offset,
             source_handle: *source_handle.as_inner(),
             size,
+            allow_borrowed,
         })
     }
 
     fn execute(self) -> anyhow::Result<()> {
-        let dest_base = self.dest_handle.require_non_null_ptr()?;
-        let source_ptr = self.source_handle.require_non_null_ptr()?;
-
-        let ptr_field =
-            unsafe { (dest_base as *const u8).add(self.ptr_offset) as *const *mut c_void };
+        let _scope = crash::enter_access(|| {
+            format!(
+                "writePointer of {} bytes at offset {} + {}",
+                self.size, self.ptr_offset, self.element_offset
+            )
+        });
+        let dest = self.dest_handle.require_memory()?;
+        dest.require_writable(self.allow_borrowed)?;
+        let source_ptr = self
+            .source_handle
+            .require_memory()?
+            .field_ptr(0, self.size)?;
+
+        let ptr_field = dest.field_ptr(self.ptr_offset, POINTER_SIZE)? as *const *mut c_void;
         let array_ptr = unsafe { ptr_field.read_unaligned() };
 
         if array_ptr.is_null() {
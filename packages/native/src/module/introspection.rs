@@ -0,0 +1,72 @@
+//! Function signatures from GObject Introspection.
+//!
+//! The [`describe_function`] function looks up a function in an installed
+//! typelib and returns its [`FunctionSignature`] to JavaScript as
+//! `{ library, symbol, parameters: [{ name, direction, type, nullable, lengthOf? }], returnType, throws }`,
+//! where the types are descriptors `call` accepts. `lengthOf` is the index of
+//! the array parameter whose length the parameter carries, or `-1` for the
+//! return value.
+//!
+//! Typelibs are loaded into the default repository, so the lookup runs on the
+//! GTK thread.
+
+use neon::prelude::*;
+
+use crate::gtk_dispatch;
+use crate::introspection::{FunctionSignature, LengthOf};
+
+pub fn describe_function(mut cx: FunctionContext) -> JsResult<JsObject> {
+    let namespace = cx.argument::<JsString>(0)?.value(&mut cx);
+    let name = cx.argument::<JsString>(1)?.value(&mut cx);
+
+    let dispatcher = gtk_dispatch::GtkDispatcher::global();
+    dispatcher.enter_js_wait();
+    let rx = {
+        let (namespace, name) = (namespace.clone(), name.clone());
+        dispatcher.run_on_gtk_thread(move || FunctionSignature::lookup(&namespace, &name))
+    };
+
+    let signature = dispatcher
+        .wait_for_gtk_result(&mut cx, &rx)
+        .or_else(|err| cx.throw_error(err.to_string()))?
+        .or_else(|err| cx.throw_error(format!("Error describing {namespace}.{name}: {err:#}")))?;
+
+    let result = cx.empty_object();
+
+    let library = cx.string(&signature.library);
+    result.set(&mut cx, "library", library)?;
+    let symbol = cx.string(&signature.symbol);
+    result.set(&mut cx, "symbol", symbol)?;
+
+    let parameters = cx.empty_array();
+    for (index, parameter) in signature.parameters.iter().enumerate() {
+        let js_parameter = cx.empty_object();
+
+        let name = cx.string(&parameter.name);
+        js_parameter.set(&mut cx, "name", name)?;
+        let direction = cx.string(parameter.direction.as_str());
+        js_parameter.set(&mut cx, "direction", direction)?;
+        let arg_type = parameter.arg_type.to_js_value(&mut cx)?;
+        js_parameter.set(&mut cx, "type", arg_type)?;
+        let nullable = cx.boolean(parameter.nullable);
+        js_parameter.set(&mut cx, "nullable", nullable)?;
+
+        if let Some(length_of) = parameter.length_of {
+            let length_of = match length_of {
+                LengthOf::Parameter(index) => cx.number(index as f64),
+                LengthOf::Return => cx.number(-1),
+            };
+            js_parameter.set(&mut cx, "lengthOf", length_of)?;
+        }
+
+        parameters.set(&mut cx, index as u32, js_parameter)?;
+    }
+    result.set(&mut cx, "parameters", parameters)?;
+
+    let return_type = signature.return_type.to_js_value(&mut cx)?;
+    result.set(&mut cx, "returnType", return_type)?;
+    let throws = cx.boolean(signature.throws);
+    result.set(&mut cx, "throws", throws)?;
+
+    Ok(result)
+}
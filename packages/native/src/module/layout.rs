@@ -0,0 +1,165 @@
+//! Struct and union layout definition.
+//!
+//! The [`define_struct`] and [`define_union`] functions compute the C layout of
+//! a list of `{ name, type, bits? }` field descriptors with
+//! [`StructLayout::compute`] or [`StructLayout::compute_union`] and return it
+//! to JavaScript:
+//!
+//! - `size`, `alignment`: Of the whole struct, for `alloc` and embedding
+//! - `fields`: `{ offset, size, type }` by field name, for `read`/`write`
+//! - `type`: A `struct` type describing the layout, for embedding it in
+//!   another layout or reading it as a field
+//!
+//! The `type` of a bitfield is a copy of its integer type with `bitOffset` and
+//! `bitWidth` set, so `read`/`write` access just its bits. A `boolean` without
+//! a `size`, alone or as the item of a fixed array, is laid out as a `gboolean`
+//! and its `type` is returned as a copy with `size: 32`.
+//!
+//! The computation does not touch native memory and runs on the JS thread.
+
+use neon::prelude::*;
+
+use crate::layout::{FieldSpec, StructLayout};
+use crate::types::Type;
+
+pub fn define_struct(mut cx: FunctionContext) -> JsResult<JsObject> {
+    define_layout(&mut cx, "struct", StructLayout::compute)
+}
+
+pub fn define_union(mut cx: FunctionContext) -> JsResult<JsObject> {
+    define_layout(&mut cx, "union", StructLayout::compute_union)
+}
+
+fn define_layout<'a>(
+    cx: &mut FunctionContext<'a>,
+    kind: &str,
+    compute: fn(&[FieldSpec]) -> anyhow::Result<StructLayout>,
+) -> JsResult<'a, JsObject> {
+    let js_fields = cx.argument::<JsArray>(0)?.to_vec(cx)?;
+    let name = cx
+        .argument_opt(1)
+        .and_then(|value| value.downcast::<JsString, _>(cx).ok())
+        .map_or_else(|| kind.to_string(), |name| name.value(cx));
+
+    let mut fields = Vec::with_capacity(js_fields.len());
+    let mut js_types = Vec::with_capacity(js_fields.len());
+
+    for js_field in js_fields {
+        let js_field = js_field.downcast_or_throw::<JsObject, _>(cx)?;
+        let field_name: Handle<JsString> = js_field.get(cx, "name")?;
+        let js_type: Handle<JsObject> = js_field.get(cx, "type")?;
+        let js_type = default_to_gboolean(cx, js_type)?;
+        let field_type = Type::from_js_value(cx, js_type.upcast())?;
+        let bits: Option<Handle<JsNumber>> = js_field.get_opt(cx, "bits")?;
+
+        fields.push(FieldSpec {
+            name: field_name.value(cx),
+            field_type,
+            bits: bits.map(|bits| bits.value(cx) as u32),
+        });
+        js_types.push(js_type);
+    }
+
+    let layout = compute(&fields)
+        .or_else(|err| cx.throw_error(format!("Invalid {kind} {name}: {err:#}")))?;
+
+    let result = cx.empty_object();
+
+    let size = cx.number(layout.size as f64);
+    result.set(cx, "size", size)?;
+    let alignment = cx.number(layout.alignment as f64);
+    result.set(cx, "alignment", alignment)?;
+
+    let js_layout_fields = cx.empty_object();
+    for (field, js_type) in layout.fields.iter().zip(js_types) {
+        let js_field = cx.empty_object();
+
+        let offset = cx.number(field.offset as f64);
+        js_field.set(cx, "offset", offset)?;
+        let field_size = cx.number(field.size as f64);
+        js_field.set(cx, "size", field_size)?;
+
+        let js_type = match field.bit_field {
+            Some(bits) => {
+                let bitfield_type = copy_object(cx, js_type)?;
+                let bit_offset = cx.number(bits.offset);
+                bitfield_type.set(cx, "bitOffset", bit_offset)?;
+                let bit_width = cx.number(bits.width);
+                bitfield_type.set(cx, "bitWidth", bit_width)?;
+                bitfield_type
+            }
+            None => js_type,
+        };
+        js_field.set(cx, "type", js_type)?;
+
+        js_layout_fields.set(cx, field.name.as_str(), js_field)?;
+    }
+    result.set(cx, "fields", js_layout_fields)?;
+
+    let js_type = cx.empty_object();
+    let type_name = cx.string("struct");
+    js_type.set(cx, "type", type_name)?;
+    let ownership = cx.string("borrowed");
+    js_type.set(cx, "ownership", ownership)?;
+    let inner_type = cx.string(&name);
+    js_type.set(cx, "innerType", inner_type)?;
+    js_type.set(cx, "size", size)?;
+    js_type.set(cx, "alignment", alignment)?;
+    result.set(cx, "type", js_type)?;
+
+    Ok(result)
+}
+
+/// Gives a `boolean` type without a `size`, or an array of them, the size of
+/// a `gboolean`, the width C structs use for booleans.
+fn default_to_gboolean<'a>(
+    cx: &mut FunctionContext<'a>,
+    js_type: Handle<'a, JsObject>,
+) -> JsResult<'a, JsObject> {
+    let kind: Option<Handle<JsString>> = js_type.get_opt(cx, "type")?;
+
+    match kind.map(|kind| kind.value(cx)).as_deref() {
+        Some("boolean") => {
+            let size: Option<Handle<JsValue>> = js_type.get_opt(cx, "size")?;
+            if size.is_some() {
+                return Ok(js_type);
+            }
+
+            let gboolean = copy_object(cx, js_type)?;
+            let size = cx.number(32);
+            gboolean.set(cx, "size", size)?;
+            Ok(gboolean)
+        }
+        Some("array") => {
+            let item_type: Option<Handle<JsObject>> = js_type.get_opt(cx, "itemType")?;
+            let Some(item_type) = item_type else {
+                return Ok(js_type);
+            };
+
+            let defaulted = default_to_gboolean(cx, item_type)?;
+            if defaulted.strict_equals(cx, item_type) {
+                return Ok(js_type);
+            }
+
+            let array = copy_object(cx, js_type)?;
+            array.set(cx, "itemType", defaulted)?;
+            Ok(array)
+        }
+        _ => Ok(js_type),
+    }
+}
+
+/// A shallow copy of the own properties of `object`.
+fn copy_object<'a>(
+    cx: &mut FunctionContext<'a>,
+    object: Handle<'a, JsObject>,
+) -> JsResult<'a, JsObject> {
+    let copy = cx.empty_object();
+
+    for key in object.get_own_property_names(cx)?.to_vec(cx)? {
+        let value: Handle<JsValue> = object.get(cx, key)?;
+        copy.set(cx, key, value)?;
+    }
+
+    Ok(copy)
+}
@@ -4,14 +4,29 @@
 
 mod alloc;
 mod call;
+mod debug;
+mod enums;
 mod field;
+mod introspection;
+mod layout;
 mod object;
+mod release;
+mod source;
 mod start;
 mod stop;
 
 pub use alloc::alloc;
 pub use call::call;
+pub use debug::{
+    clear_trace, configure_crash_handler, configure_tracing, configure_watchdog, debug_handles,
+    dump_trace,
+};
+pub use enums::enum_values;
 pub use field::{read, read_pointer, write, write_pointer};
+pub use introspection::describe_function;
+pub use layout::{define_struct, define_union};
 pub use object::get_native_id;
+pub use release::release;
+pub use source::{add_idle, add_timeout, add_unix_fd_watch, remove_source};
 pub use start::start;
 pub use stop::stop;
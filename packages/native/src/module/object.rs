@@ -18,7 +18,11 @@ pub fn get_native_id(mut cx: FunctionContext) -> JsResult<JsNumber> {
 
     gtk_dispatch::GtkDispatcher::global().enter_js_wait();
     gtk_dispatch::GtkDispatcher::global().schedule(move || {
-        let _ = tx.send(native_handle.get_ptr_as_usize());
+        let _ = tx.send(
+            native_handle
+                .get_ptr_as_usize()
+                .ok_or_else(|| native_handle.unavailable_error("Object")),
+        );
     });
 
     let ptr = gtk_dispatch::GtkDispatcher::global()
@@ -26,7 +30,7 @@ pub fn get_native_id(mut cx: FunctionContext) -> JsResult<JsNumber> {
         .or_else(|err| cx.throw_error(err.to_string()))?;
 
     match ptr {
-        Some(p) => Ok(cx.number(p as f64)),
-        None => cx.throw_error("Object has been garbage collected"),
+        Ok(p) => Ok(cx.number(p as f64)),
+        Err(err) => cx.throw_error(err.to_string()),
     }
 }
@@ -0,0 +1,37 @@
+//! Deterministic release of managed handles.
+//!
+//! The [`release`] function removes a handle's managed value from the handle
+//! map on the GTK thread, dropping the GObject reference or freeing the boxed
+//! memory immediately instead of waiting for V8 to garbage collect the handle.
+//!
+//! Releasing is idempotent: releasing an already-released handle returns
+//! `false`. Any other use of a released handle fails with a "released" error.
+
+use std::sync::mpsc;
+
+use neon::prelude::*;
+
+use crate::gtk_dispatch;
+use crate::managed::NativeHandle;
+
+pub fn release(mut cx: FunctionContext) -> JsResult<JsBoolean> {
+    let boxed_handle = cx.argument::<JsBox<NativeHandle>>(0)?;
+    let native_handle = *boxed_handle.as_inner();
+
+    if !gtk_dispatch::GtkDispatcher::global().is_started() {
+        return Ok(cx.boolean(false));
+    }
+
+    let (tx, rx) = mpsc::channel();
+
+    gtk_dispatch::GtkDispatcher::global().enter_js_wait();
+    gtk_dispatch::GtkDispatcher::global().schedule(move || {
+        let _ = tx.send(native_handle.release());
+    });
+
+    let released = gtk_dispatch::GtkDispatcher::global()
+        .wait_for_gtk_result(&mut cx, &rx)
+        .or_else(|err| cx.throw_error(err.to_string()))?;
+
+    Ok(cx.boolean(released))
+}
@@ -0,0 +1,179 @@
+//! Main loop sources driven from JavaScript.
+//!
+//! These exports install GLib sources on the GTK main context so timing-critical
+//! work runs in step with the main loop and frame clock rather than Node's timers:
+//!
+//! - [`add_timeout`]: `addTimeout(ms, callback, priority?)`
+//! - [`add_idle`]: `addIdle(callback, priority?)`
+//! - [`add_unix_fd_watch`]: `addUnixFdWatch(fd, conditions, callback, priority?)`
+//! - [`remove_source`]: `removeSource(id)`
+//!
+//! Each returns the GLib source ID. Callbacks are dispatched through the
+//! `JsDispatcher` like signal handlers; returning `true` keeps the source
+//! installed, any other value removes it.
+
+use std::cell::Cell;
+use std::os::fd::RawFd;
+use std::rc::Rc;
+use std::time::Duration;
+
+use gtk4::glib::{self, translate::from_glib};
+use neon::prelude::*;
+
+use crate::{
+    gtk_dispatch, js_dispatch,
+    state::GtkThreadState,
+    value::{Callback, Value},
+};
+
+/// Default priority for timeouts and fd watches (`G_PRIORITY_DEFAULT`).
+const PRIORITY_DEFAULT: i32 = 0;
+/// Default priority for idles (`G_PRIORITY_DEFAULT_IDLE`).
+const PRIORITY_DEFAULT_IDLE: i32 = 200;
+
+fn priority_arg(
+    cx: &mut FunctionContext,
+    index: usize,
+    default: i32,
+) -> NeonResult<glib::Priority> {
+    let priority = match cx.argument_opt(index) {
+        Some(value) if value.is_a::<JsNumber, _>(cx) => {
+            value.downcast_or_throw::<JsNumber, _>(cx)?.value(cx) as i32
+        }
+        _ => default,
+    };
+
+    // SAFETY: Any integer is a valid GLib priority.
+    Ok(unsafe { from_glib(priority) })
+}
+
+fn callback_arg(cx: &mut FunctionContext, index: usize) -> NeonResult<Callback> {
+    let value = cx.argument::<JsFunction>(index)?;
+    Callback::from_js_value(cx, value.upcast())
+}
+
+/// Runs the JS callback and keeps the source only if it returned `true`. Once
+/// the source stops, its ID is forgotten so `removeSource` does not act on it.
+fn dispatch(callback: &Callback, source_id: &Cell<u32>, args: Vec<Value>) -> glib::ControlFlow {
+    let flow = js_dispatch::JsDispatcher::global().invoke_and_wait(
+        &callback.channel,
+        &callback.js_func,
+        args,
+        true,
+        |result| match result {
+            Ok(Value::Boolean(true)) => glib::ControlFlow::Continue,
+            _ => glib::ControlFlow::Break,
+        },
+    );
+
+    if matches!(flow, glib::ControlFlow::Break) {
+        let id = source_id.get();
+        GtkThreadState::with(|state| state.sources.remove(&id));
+    }
+
+    flow
+}
+
+/// Installs a source on the GTK thread and returns its ID to JavaScript.
+fn add_source<F>(mut cx: FunctionContext, install: F) -> JsResult<JsNumber>
+where
+    F: FnOnce(Rc<Cell<u32>>) -> glib::SourceId + Send + 'static,
+{
+    let dispatcher = gtk_dispatch::GtkDispatcher::global();
+
+    if !dispatcher.is_started() {
+        return cx.throw_error("GTK application has not been started. Call start() first.");
+    }
+
+    dispatcher.enter_js_wait();
+
+    let rx = dispatcher.run_on_gtk_thread(move || {
+        let source_id = Rc::new(Cell::new(0));
+        let source = install(source_id.clone());
+        let id = source.as_raw();
+
+        source_id.set(id);
+        GtkThreadState::with(|state| state.sources.insert(id, source));
+        id
+    });
+
+    let id = dispatcher
+        .wait_for_gtk_result(&mut cx, &rx)
+        .or_else(|err| cx.throw_error(err.to_string()))?;
+
+    Ok(cx.number(id))
+}
+
+pub fn add_timeout(mut cx: FunctionContext) -> JsResult<JsNumber> {
+    let interval = cx.argument::<JsNumber>(0)?.value(&mut cx).max(0.0) as u64;
+    let callback = callback_arg(&mut cx, 1)?;
+    let priority = priority_arg(&mut cx, 2, PRIORITY_DEFAULT)?;
+
+    add_source(cx, move |source_id| {
+        glib::timeout_add_local_full(Duration::from_millis(interval), priority, move || {
+            dispatch(&callback, &source_id, vec![])
+        })
+    })
+}
+
+pub fn add_idle(mut cx: FunctionContext) -> JsResult<JsNumber> {
+    let callback = callback_arg(&mut cx, 0)?;
+    let priority = priority_arg(&mut cx, 1, PRIORITY_DEFAULT_IDLE)?;
+
+    add_source(cx, move |source_id| {
+        glib::idle_add_local_full(priority, move || dispatch(&callback, &source_id, vec![]))
+    })
+}
+
+pub fn add_unix_fd_watch(mut cx: FunctionContext) -> JsResult<JsNumber> {
+    let fd = cx.argument::<JsNumber>(0)?.value(&mut cx) as RawFd;
+    let conditions = cx.argument::<JsNumber>(1)?.value(&mut cx) as u32;
+    let callback = callback_arg(&mut cx, 2)?;
+    let priority = priority_arg(&mut cx, 3, PRIORITY_DEFAULT)?;
+    let conditions = glib::IOCondition::from_bits_truncate(conditions);
+
+    add_source(cx, move |source_id| {
+        glib::unix_fd_add_local_full(fd, priority, conditions, move |fd, condition| {
+            let args = vec![
+                Value::Number(fd as f64),
+                Value::Number(condition.bits() as f64),
+            ];
+            dispatch(&callback, &source_id, args)
+        })
+    })
+}
+
+pub fn remove_source(mut cx: FunctionContext) -> JsResult<JsBoolean> {
+    let id = cx.argument::<JsNumber>(0)?.value(&mut cx) as u32;
+    let dispatcher = gtk_dispatch::GtkDispatcher::global();
+
+    if !dispatcher.is_started() {
+        return Ok(cx.boolean(false));
+    }
+
+    dispatcher.enter_js_wait();
+
+    let rx = dispatcher.run_on_gtk_thread(move || {
+        let Some(source) = GtkThreadState::with(|state| state.sources.remove(&id)) else {
+            return false;
+        };
+
+        // The source may already be gone if native code removed it directly;
+        // `SourceId::remove` would panic on it, so only remove live sources.
+        let live = glib::MainContext::ref_thread_default()
+            .find_source_by_id(&source)
+            .is_some();
+
+        if live {
+            source.remove();
+        }
+
+        live
+    });
+
+    let removed = dispatcher
+        .wait_for_gtk_result(&mut cx, &rx)
+        .or_else(|err| cx.throw_error(err.to_string()))?;
+
+    Ok(cx.boolean(removed))
+}
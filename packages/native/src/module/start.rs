@@ -12,6 +12,9 @@
 //! 5. Start the GTK main loop with `app.run_with_args`
 //! 6. When activate fires, send the application's `NativeHandle` back to JS
 //! 7. Return the `NativeHandle` to JavaScript
+//!
+//! When the main loop exits after a teardown-mode `stop`, the GTK thread closes
+//! the loaded libraries before terminating.
 
 use std::sync::mpsc;
 
@@ -56,6 +59,9 @@ pub fn start(mut cx: FunctionContext) -> JsResult<JsValue> {
         });
 
         app.run_with_args::<&str>(&[]);
+
+        let libraries = GtkThreadState::with(|state| state.take_libraries_for_teardown());
+        drop(libraries);
     });
 
     GtkThread::global().set_handle(handle);
@@ -66,5 +72,5 @@ pub fn start(mut cx: FunctionContext) -> JsResult<JsValue> {
 
     GtkDispatcher::global().mark_started();
 
-    Ok(cx.boxed(app_handle).upcast())
+    Ok(app_handle.into_js(&mut cx).upcast())
 }
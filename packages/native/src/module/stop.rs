@@ -10,24 +10,91 @@
 //! 3. Mark stopped to reject any further scheduled tasks
 //! 4. Join the GTK thread, waiting for it to fully terminate
 //!
-//! Note: The handle map is intentionally NOT cleared during stop. Handles are
-//! stored in thread-local storage and will be dropped when the GTK thread exits.
-//! Clearing them earlier could cause use-after-free if signal closures are still
-//! being processed by the GTK main loop.
+//! Note: By default the handle map is intentionally NOT cleared during stop.
+//! Handles are stored in thread-local storage and leaked at process exit.
+//! Clearing them after the main loop has exited could cause use-after-free if
+//! signal closures are still pending.
+//!
+//! ## Teardown Mode
+//!
+//! When called with `{ teardown: true }`, every handle is released on the GTK
+//! thread before the hold guard is dropped, while the main context can still
+//! iterate to run pending closure unrefs and idle cleanups. Once the main loop
+//! exits, loaded libraries are closed except those matching `keepLibraries`
+//! (WebKit and JavaScriptCore are always kept). This keeps leak checkers such as
+//! valgrind and ASan quiet.
 
+use gtk4::glib;
 use neon::prelude::*;
 
 use crate::{
     gtk_dispatch,
-    state::{GtkThread, GtkThreadState},
+    state::{GtkThread, GtkThreadState, TeardownOptions},
 };
 
+/// Upper bound on main context iterations while flushing teardown work, so a
+/// source that is always ready cannot hang shutdown.
+const MAX_TEARDOWN_ITERATIONS: usize = 1000;
+
+fn teardown_options(cx: &mut FunctionContext) -> NeonResult<Option<TeardownOptions>> {
+    let Some(options) = cx.argument_opt(0) else {
+        return Ok(None);
+    };
+
+    let Ok(options) = options.downcast::<JsObject, _>(cx) else {
+        return Ok(None);
+    };
+
+    let teardown: Option<Handle<JsBoolean>> = options.get_opt(cx, "teardown")?;
+    if !teardown.is_some_and(|teardown| teardown.value(cx)) {
+        return Ok(None);
+    }
+
+    let mut keep_libraries = Vec::new();
+    let keep: Option<Handle<JsArray>> = options.get_opt(cx, "keepLibraries")?;
+
+    if let Some(keep) = keep {
+        for item in keep.to_vec(cx)? {
+            let name = item
+                .downcast::<JsString, _>(cx)
+                .or_else(|_| cx.throw_type_error("'keepLibraries' must contain only strings"))?;
+            keep_libraries.push(name.value(cx));
+        }
+    }
+
+    Ok(Some(TeardownOptions { keep_libraries }))
+}
+
+fn teardown_handles(options: TeardownOptions) {
+    let values = GtkThreadState::with(|state| {
+        state.teardown = Some(options);
+        state.drain_handles_for_teardown()
+    });
+
+    drop(values);
+
+    let context = glib::MainContext::default();
+
+    for _ in 0..MAX_TEARDOWN_ITERATIONS {
+        gtk_dispatch::GtkDispatcher::global().flush_deferred_closure_unrefs();
+
+        if !context.iteration(false) {
+            break;
+        }
+    }
+}
+
 pub fn stop(mut cx: FunctionContext) -> JsResult<JsUndefined> {
+    let teardown = teardown_options(&mut cx)?;
     let dispatcher = gtk_dispatch::GtkDispatcher::global();
 
     dispatcher.enter_js_wait();
 
-    let rx = dispatcher.run_on_gtk_thread(|| {
+    let rx = dispatcher.run_on_gtk_thread(move || {
+        if let Some(options) = teardown {
+            teardown_handles(options);
+        }
+
         GtkThreadState::with(|state| {
             state.app_hold_guard.take();
         });
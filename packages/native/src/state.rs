@@ -12,18 +12,26 @@
 //!
 //! - `handle_map`: Maps handle IDs to managed [`NativeValue`] instances
 //! - `next_handle_id`: Counter for generating unique handle IDs
+//! - `released_handles`: IDs explicitly released from JavaScript before GC
+//! - `handle_created_at`: Creation time of each live handle, for diagnostics
+//! - `handle_pins`/`orphaned_handles`: Values kept alive for borrowed views
+//!   into their memory after their own handle is gone
 //! - `libraries`: Cache of dynamically loaded native libraries
 //! - `app_hold_guard`: Keeps the GTK application alive while running
+//! - `sources`: Main loop sources installed from JavaScript, by source ID
+//! - `teardown`: Set by `stop` when handles and libraries should be released
+//!   instead of leaked at process exit
 
 use std::cell::RefCell;
-use std::collections::{HashMap, hash_map::Entry};
+use std::collections::{HashMap, HashSet, hash_map::Entry};
 use std::mem::ManuallyDrop;
 use std::ptr::NonNull;
 use std::sync::{Mutex, OnceLock};
 use std::thread::JoinHandle;
+use std::time::Instant;
 
 use gtk4::gio::ApplicationHoldGuard;
-use gtk4::glib::gobject_ffi;
+use gtk4::glib::{self, gobject_ffi};
 use libloading::os::unix::{Library, RTLD_GLOBAL, RTLD_NOW};
 
 use crate::managed::NativeValue;
@@ -62,16 +70,61 @@ pub struct GtkThreadState {
     pub app_hold_guard: Option<ApplicationHoldGuard>,
     /// Native object handles. Wrapped in ManuallyDrop because dropping GLib objects
     /// after GTK cleanup can crash - e.g., WebKit objects have complex cleanup
-    /// that depends on the main loop. Objects are reclaimed at process exit.
+    /// that depends on the main loop. Objects are reclaimed at process exit unless
+    /// `stop` is called in teardown mode, which drains them while the main loop
+    /// is still running.
     pub handle_map: ManuallyDrop<HashMap<usize, NativeValue>>,
     pub next_handle_id: usize,
+    /// Handles removed from `handle_map` by an explicit `release` call. Kept until
+    /// the JS handle is finalized so later use reports a released handle instead
+    /// of a garbage-collected one.
+    pub released_handles: HashSet<usize>,
+    /// When each live handle in `handle_map` was created. Used by `debugHandles`
+    /// to report handle ages.
+    pub handle_created_at: HashMap<usize, Instant>,
+    /// Number of borrowed views keeping each handle's value alive.
+    pub handle_pins: HashMap<usize, usize>,
+    /// Values whose handle was finalized or released while still pinned. Each
+    /// is dropped when its last pin goes away. Wrapped in ManuallyDrop for the
+    /// same reason as `handle_map`.
+    pub orphaned_handles: ManuallyDrop<HashMap<usize, NativeValue>>,
     /// Dynamically loaded libraries. Wrapped in ManuallyDrop because libraries
     /// like WebKit spawn threads with TLS destructors - calling dlclose() while
-    /// those threads exist causes segfaults. Libraries are reclaimed at process exit.
+    /// those threads exist causes segfaults. Libraries are reclaimed at process exit
+    /// unless `stop` is called in teardown mode, in which case those not matching
+    /// [`TeardownOptions::keep_libraries`] are closed after the main loop exits.
     pub libraries: ManuallyDrop<HashMap<String, Library>>,
     /// Closures that need to be unreffed after the current callback completes.
     /// Used to defer closure cleanup during signal emission to prevent use-after-free.
     pub deferred_closure_unrefs: Vec<NonNull<gobject_ffi::GClosure>>,
+    /// Timeouts, idles and fd watches added with `addTimeout`, `addIdle` and
+    /// `addUnixFdWatch`. Entries are removed when the JS callback stops the
+    /// source or `removeSource` is called, so only live sources are ever removed.
+    pub sources: HashMap<u32, glib::SourceId>,
+    pub teardown: Option<TeardownOptions>,
+}
+
+/// Options for an orderly shutdown, passed to `stop` from JavaScript.
+#[derive(Debug, Clone, Default)]
+pub struct TeardownOptions {
+    /// Library name fragments that must never be closed (e.g. `"webkit"`).
+    /// A loaded library is kept if any of its names contains one of these.
+    pub keep_libraries: Vec<String>,
+}
+
+impl TeardownOptions {
+    /// Libraries that spawn threads with TLS destructors and crash on dlclose().
+    const ALWAYS_KEEP: &'static [&'static str] = &["webkit", "javascriptcore"];
+
+    fn keeps_library(&self, name: &str) -> bool {
+        let name = name.to_lowercase();
+
+        Self::ALWAYS_KEEP
+            .iter()
+            .copied()
+            .chain(self.keep_libraries.iter().map(String::as_str))
+            .any(|fragment| name.contains(&fragment.to_lowercase()))
+    }
 }
 
 impl Default for GtkThreadState {
@@ -79,9 +132,15 @@ impl Default for GtkThreadState {
         GtkThreadState {
             handle_map: ManuallyDrop::new(HashMap::new()),
             next_handle_id: 1,
+            released_handles: HashSet::new(),
+            handle_created_at: HashMap::new(),
+            handle_pins: HashMap::new(),
+            orphaned_handles: ManuallyDrop::new(HashMap::new()),
             libraries: ManuallyDrop::new(HashMap::new()),
             app_hold_guard: None,
             deferred_closure_unrefs: Vec::new(),
+            sources: HashMap::new(),
+            teardown: None,
         }
     }
 }
@@ -94,6 +153,111 @@ impl GtkThreadState {
         GTK_THREAD_STATE.with_borrow_mut(f)
     }
 
+    /// Inserts a managed value into the handle map and returns its new handle ID.
+    pub fn insert_handle(&mut self, value: NativeValue) -> usize {
+        let id = self.next_handle_id;
+        self.next_handle_id = self.next_handle_id.wrapping_add(1);
+        self.handle_map.insert(id, value);
+        self.handle_created_at.insert(id, Instant::now());
+        id
+    }
+
+    /// Removes a managed value from the handle map along with its bookkeeping.
+    ///
+    /// A value pinned by borrowed views is moved to `orphaned_handles` instead
+    /// of being returned. The returned value must be dropped outside of
+    /// [`GtkThreadState::with`], since dropping GLib objects can re-enter the
+    /// state through finalizers.
+    pub fn remove_handle(&mut self, id: usize) -> Option<NativeValue> {
+        self.handle_created_at.remove(&id);
+        crate::diagnostics::forget_creation_stack(id);
+        let value = self.handle_map.remove(&id)?;
+
+        if self.handle_pins.contains_key(&id) {
+            self.orphaned_handles.insert(id, value);
+            return None;
+        }
+
+        Some(value)
+    }
+
+    pub fn pin_handle(&mut self, id: usize) {
+        *self.handle_pins.entry(id).or_default() += 1;
+    }
+
+    /// Drops a pin taken with [`GtkThreadState::pin_handle`], returning the
+    /// orphaned value once nothing keeps it alive. The value must be dropped
+    /// outside of [`GtkThreadState::with`].
+    pub fn unpin_handle(&mut self, id: usize) -> Option<NativeValue> {
+        let Entry::Occupied(mut pins) = self.handle_pins.entry(id) else {
+            return None;
+        };
+
+        *pins.get_mut() -= 1;
+        if *pins.get() > 0 {
+            return None;
+        }
+
+        pins.remove();
+        self.orphaned_handles.remove(&id)
+    }
+
+    /// Removes every handle from the handle map in an order that is safe to drop.
+    ///
+    /// Orphaned, boxed and fundamental values go first since they may point into GObjects,
+    /// then GObjects newest-first so children are released before the parents
+    /// created ahead of them. Drained handles are marked as released. The values
+    /// must be dropped outside of [`GtkThreadState::with`].
+    pub fn drain_handles_for_teardown(&mut self) -> Vec<NativeValue> {
+        // Views dropped after teardown find their pins gone and do nothing.
+        self.handle_pins.clear();
+        let orphans: Vec<NativeValue> = self
+            .orphaned_handles
+            .drain()
+            .map(|(_, value)| value)
+            .collect();
+
+        let mut ids: Vec<usize> = self.handle_map.keys().copied().collect();
+        ids.sort_unstable_by(|a, b| b.cmp(a));
+
+        let (objects, others): (Vec<usize>, Vec<usize>) = ids
+            .into_iter()
+            .partition(|id| matches!(self.handle_map.get(id), Some(NativeValue::GObject(_))));
+
+        let handles: Vec<NativeValue> = others
+            .into_iter()
+            .chain(objects)
+            .filter_map(|id| {
+                let value = self.remove_handle(id)?;
+                self.released_handles.insert(id);
+                Some(value)
+            })
+            .collect();
+
+        orphans.into_iter().chain(handles).collect()
+    }
+
+    /// Takes the loaded libraries that may be closed during teardown, leaving
+    /// the ones matching the keep list loaded. Returns nothing unless `stop` was
+    /// called in teardown mode.
+    pub fn take_libraries_for_teardown(&mut self) -> Vec<Library> {
+        let Some(teardown) = self.teardown.as_ref() else {
+            return Vec::new();
+        };
+
+        let names: Vec<String> = self
+            .libraries
+            .keys()
+            .filter(|name| !teardown.keeps_library(name))
+            .cloned()
+            .collect();
+
+        names
+            .iter()
+            .filter_map(|name| self.libraries.remove(name))
+            .collect()
+    }
+
     pub fn library(&mut self, name: &str) -> anyhow::Result<&Library> {
         match self.libraries.entry(name.to_string()) {
             Entry::Occupied(entry) => Ok(entry.into_mut()),
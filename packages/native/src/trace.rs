@@ -0,0 +1,510 @@
+//! FFI call tracing.
+//!
+//! When enabled, every call made through `call` is recorded as a
+//! [`TraceEntry`] in a fixed-size ring buffer: the library and symbol, the
+//! decoded arguments and return value (or error), how long the call waited in
+//! the GTK task queue and how long the native function ran. The oldest entries
+//! are discarded once the buffer is full.
+//!
+//! ## Configuration
+//!
+//! - `GTKX_TRACE`: A positive number enables tracing with that many entries;
+//!   any other non-empty value other than `0` uses [`DEFAULT_CAPACITY`]
+//!
+//! Tracing can also be changed at runtime through the `configureTracing`
+//! export, and the buffer read back with `dumpTrace`.
+//!
+//! ## Formats
+//!
+//! | Format | Contents |
+//! |--------|----------|
+//! | [`TraceFormat::Json`] | Array of entries, oldest first |
+//! | [`TraceFormat::Chrome`] | Trace Event Format, loadable in Perfetto or `chrome://tracing` |
+//!
+//! ## Fatal Signals
+//!
+//! Enabling tracing installs the [`crate::crash`] signal handlers, which write
+//! the last [`SIGNAL_DUMP_ENTRIES`] calls to stderr as JSON when the process
+//! receives a fatal signal. Each entry is serialised into a preallocated slot
+//! when it is recorded, so the handler itself only calls `write(2)`.
+
+use std::cell::UnsafeCell;
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::{arg::Arg, crash, value::Value};
+
+const TRACE_ENV: &str = "GTKX_TRACE";
+
+/// Number of entries kept when tracing is enabled without a capacity.
+pub const DEFAULT_CAPACITY: usize = 1024;
+/// Longest description kept for a single argument or return value.
+const MAX_VALUE_LEN: usize = 256;
+/// Number of most recent calls kept pre-serialised for the fatal signal dump.
+pub const SIGNAL_DUMP_ENTRIES: usize = 64;
+/// Space reserved for each pre-serialised call. Longer entries are stored
+/// without their arguments.
+const SIGNAL_DUMP_ENTRY_LEN: usize = 2048;
+
+struct SignalDumpSlot {
+    len: AtomicUsize,
+    bytes: UnsafeCell<[u8; SIGNAL_DUMP_ENTRY_LEN]>,
+}
+
+/// The most recent entries, serialised as JSON ahead of time so the fatal
+/// signal handler neither allocates nor takes locks.
+struct SignalDump {
+    enabled: AtomicBool,
+    /// Number of entries stored so far; the next one goes in slot
+    /// `head % SIGNAL_DUMP_ENTRIES`.
+    head: AtomicUsize,
+    slots: [SignalDumpSlot; SIGNAL_DUMP_ENTRIES],
+}
+
+// SAFETY: Slots are only written by `Tracer::record` and `Tracer::clear` while
+// holding the entries lock. The signal handler only hands them to `write(2)`,
+// and a slot's length is zero while its bytes are being replaced.
+unsafe impl Sync for SignalDump {}
+
+static SIGNAL_DUMP: SignalDump = SignalDump {
+    enabled: AtomicBool::new(false),
+    head: AtomicUsize::new(0),
+    slots: [const {
+        SignalDumpSlot {
+            len: AtomicUsize::new(0),
+            bytes: UnsafeCell::new([0; SIGNAL_DUMP_ENTRY_LEN]),
+        }
+    }; SIGNAL_DUMP_ENTRIES],
+};
+
+impl SignalDump {
+    /// Stores a serialised entry. Callers must hold the tracer's entries lock.
+    fn push(&self, json: &str) {
+        if json.len() > SIGNAL_DUMP_ENTRY_LEN {
+            return;
+        }
+
+        let head = self.head.load(Ordering::Relaxed);
+        let slot = &self.slots[head % SIGNAL_DUMP_ENTRIES];
+
+        slot.len.store(0, Ordering::Release);
+        // SAFETY: Writers are serialised by the entries lock, and the length
+        // check above keeps the copy in bounds.
+        unsafe { (*slot.bytes.get())[..json.len()].copy_from_slice(json.as_bytes()) };
+        slot.len.store(json.len(), Ordering::Release);
+        self.head.store(head + 1, Ordering::Release);
+    }
+
+    /// Forgets all stored entries. Callers must hold the tracer's entries lock.
+    fn clear(&self) {
+        self.head.store(0, Ordering::Release);
+
+        for slot in &self.slots {
+            slot.len.store(0, Ordering::Release);
+        }
+    }
+}
+
+/// Writes the pre-serialised entries to stderr from a fatal signal handler.
+/// Only calls `write(2)`; entries being replaced concurrently are skipped.
+pub(crate) fn dump_on_signal(signal: libc::c_int) {
+    let dump = &SIGNAL_DUMP;
+    if !dump.enabled.load(Ordering::Acquire) {
+        return;
+    }
+
+    let head = dump.head.load(Ordering::Acquire);
+    let count = head.min(SIGNAL_DUMP_ENTRIES);
+
+    crash::write_stderr(b"[gtkx] TRACE: fatal signal ");
+    crash::write_stderr_number(signal as u64);
+    crash::write_stderr(b", last ");
+    crash::write_stderr_number(count as u64);
+    crash::write_stderr(b" calls:\n[");
+
+    let mut first = true;
+    for index in head - count..head {
+        let slot = &dump.slots[index % SIGNAL_DUMP_ENTRIES];
+        let len = slot.len.load(Ordering::Acquire);
+        if len == 0 {
+            continue;
+        }
+
+        if !first {
+            crash::write_stderr(b",");
+        }
+        first = false;
+
+        // SAFETY: The first `len` bytes of the slot were written before `len`
+        // was published.
+        crash::write_stderr(unsafe { &(*slot.bytes.get())[..len] });
+    }
+
+    crash::write_stderr(b"]\n");
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceThread {
+    Gtk,
+    Worker,
+}
+
+impl TraceThread {
+    fn as_str(self) -> &'static str {
+        match self {
+            TraceThread::Gtk => "gtk",
+            TraceThread::Worker => "worker",
+        }
+    }
+
+    fn tid(self) -> u32 {
+        match self {
+            TraceThread::Gtk => 1,
+            TraceThread::Worker => 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TraceFormat {
+    #[default]
+    Json,
+    Chrome,
+}
+
+impl FromStr for TraceFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(TraceFormat::Json),
+            "chrome" => Ok(TraceFormat::Chrome),
+            other => Err(format!(
+                "Invalid trace format '{other}', expected 'json' or 'chrome'"
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TraceEntry {
+    pub library: String,
+    pub symbol: String,
+    pub args: Vec<String>,
+    /// The decoded return value, or the error the call failed with.
+    pub result: Result<String, String>,
+    pub thread: TraceThread,
+    /// When the call was queued, relative to the start of tracing.
+    pub queued_at: Duration,
+    /// Time between queueing the call and its task starting.
+    pub queue_wait: Duration,
+    /// Time spent inside the native function.
+    pub duration: Duration,
+}
+
+/// A call being traced, from the moment it is queued until it completes.
+#[derive(Debug)]
+pub struct CallTrace {
+    library: String,
+    symbol: String,
+    args: Vec<String>,
+    thread: TraceThread,
+    queued_at: Instant,
+    queue_wait: Duration,
+    duration: Duration,
+}
+
+impl CallTrace {
+    /// Starts tracing a call that is about to be queued. Returns `None` when
+    /// tracing is disabled.
+    pub fn begin(library: &str, symbol: &str, args: &[Arg]) -> Option<Self> {
+        if !Tracer::global().is_enabled() {
+            return None;
+        }
+
+        Some(Self {
+            library: library.to_string(),
+            symbol: symbol.to_string(),
+            args: args.iter().map(|arg| describe(&arg.value)).collect(),
+            thread: TraceThread::Gtk,
+            queued_at: Instant::now(),
+            queue_wait: Duration::ZERO,
+            duration: Duration::ZERO,
+        })
+    }
+
+    /// Marks the call's task as started on the GTK thread.
+    pub fn dequeued(&mut self, thread: TraceThread) {
+        self.thread = thread;
+        self.queue_wait = self.queued_at.elapsed();
+    }
+
+    /// Records how long the native function ran.
+    pub fn invoked(&mut self, started: Instant) {
+        self.duration = started.elapsed();
+    }
+
+    /// Records the outcome of the call in the trace buffer.
+    pub fn complete(self, result: Result<&Value, &anyhow::Error>) {
+        let tracer = Tracer::global();
+
+        tracer.record(TraceEntry {
+            library: self.library,
+            symbol: self.symbol,
+            args: self.args,
+            result: result.map(describe).map_err(ToString::to_string),
+            thread: self.thread,
+            queued_at: self.queued_at.saturating_duration_since(tracer.epoch),
+            queue_wait: self.queue_wait,
+            duration: self.duration,
+        });
+    }
+}
+
+pub struct Tracer {
+    /// Maximum number of entries; `0` when tracing is disabled.
+    capacity: AtomicUsize,
+    entries: Mutex<VecDeque<TraceEntry>>,
+    epoch: Instant,
+}
+
+static TRACER: LazyLock<Tracer> = LazyLock::new(Tracer::from_env);
+
+impl Tracer {
+    pub fn global() -> &'static Tracer {
+        &TRACER
+    }
+
+    fn from_env() -> Self {
+        let capacity = std::env::var(TRACE_ENV)
+            .ok()
+            .filter(|value| !value.is_empty() && value != "0")
+            .map_or(0, |value| {
+                value.parse::<usize>().unwrap_or(DEFAULT_CAPACITY)
+            });
+
+        if capacity > 0 {
+            crash::install_signal_handlers();
+        }
+
+        SIGNAL_DUMP.enabled.store(capacity > 0, Ordering::Release);
+
+        Self {
+            capacity: AtomicUsize::new(capacity),
+            entries: Mutex::new(VecDeque::new()),
+            epoch: Instant::now(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.capacity.load(Ordering::Acquire) > 0
+    }
+
+    /// Enables tracing with the given buffer capacity, or disables it with
+    /// `None`. Disabling keeps the recorded entries so they can still be read.
+    pub fn configure(&self, capacity: Option<usize>) {
+        let capacity = capacity.unwrap_or(0);
+        self.capacity.store(capacity, Ordering::Release);
+        SIGNAL_DUMP.enabled.store(capacity > 0, Ordering::Release);
+
+        if capacity > 0 {
+            let mut entries = self.entries.lock().unwrap();
+            while entries.len() > capacity {
+                entries.pop_front();
+            }
+            drop(entries);
+
+            crash::install_signal_handlers();
+        }
+    }
+
+    pub fn record(&self, entry: TraceEntry) {
+        let capacity = self.capacity.load(Ordering::Acquire);
+        if capacity == 0 {
+            return;
+        }
+
+        let json = Self::signal_dump_json(&entry);
+
+        let mut entries = self.entries.lock().unwrap();
+        while entries.len() >= capacity {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+        SIGNAL_DUMP.push(&json);
+    }
+
+    /// Returns the recorded entries, oldest first.
+    pub fn entries(&self) -> Vec<TraceEntry> {
+        self.entries.lock().unwrap().iter().cloned().collect()
+    }
+
+    pub fn clear(&self) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.clear();
+        SIGNAL_DUMP.clear();
+    }
+
+    /// Serializes the recorded entries in the given format.
+    pub fn export(&self, format: TraceFormat) -> String {
+        let entries = self.entries();
+
+        match format {
+            TraceFormat::Json => Self::to_json(&entries),
+            TraceFormat::Chrome => Self::to_chrome_trace(&entries),
+        }
+    }
+
+    pub fn to_json(entries: &[TraceEntry]) -> String {
+        let mut json = String::from("[");
+
+        for (i, entry) in entries.iter().enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+
+            Self::write_entry_json(&mut json, entry, &entry.args);
+        }
+
+        json.push(']');
+        json
+    }
+
+    fn write_entry_json(json: &mut String, entry: &TraceEntry, args: &[String]) {
+        let _ = write!(
+            json,
+            "{{\"library\":{},\"symbol\":{},\"args\":{},",
+            json_string(&entry.library),
+            json_string(&entry.symbol),
+            json_string_array(args),
+        );
+
+        match &entry.result {
+            Ok(value) => {
+                let _ = write!(json, "\"result\":{},", json_string(value));
+            }
+            Err(err) => {
+                let _ = write!(json, "\"error\":{},", json_string(err));
+            }
+        }
+
+        let _ = write!(
+            json,
+            "\"thread\":\"{}\",\"queuedAtUs\":{},\"queueWaitUs\":{},\"durationUs\":{}}}",
+            entry.thread.as_str(),
+            entry.queued_at.as_micros(),
+            entry.queue_wait.as_micros(),
+            entry.duration.as_micros(),
+        );
+    }
+
+    /// Serializes an entry for the fatal signal dump, dropping its arguments
+    /// if the full entry does not fit in a slot.
+    fn signal_dump_json(entry: &TraceEntry) -> String {
+        let mut json = String::new();
+        Self::write_entry_json(&mut json, entry, &entry.args);
+
+        if json.len() > SIGNAL_DUMP_ENTRY_LEN {
+            json.clear();
+            let omitted = [format!("<{} args omitted>", entry.args.len())];
+            Self::write_entry_json(&mut json, entry, &omitted);
+        }
+
+        json
+    }
+
+    /// Serializes entries as Trace Event Format "complete" events, one per
+    /// call, with the GTK thread and worker calls on separate tracks.
+    pub fn to_chrome_trace(entries: &[TraceEntry]) -> String {
+        let pid = std::process::id();
+        let mut json = String::from("{\"displayTimeUnit\":\"ms\",\"traceEvents\":[");
+
+        for (i, entry) in entries.iter().enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+
+            let (result_key, result) = match &entry.result {
+                Ok(value) => ("result", value),
+                Err(err) => ("error", err),
+            };
+
+            let _ = write!(
+                json,
+                "{{\"name\":{},\"cat\":{},\"ph\":\"X\",\"ts\":{},\"dur\":{},\"pid\":{pid},\"tid\":{},\
+                 \"args\":{{\"args\":{},\"{result_key}\":{},\"queueWaitUs\":{}}}}}",
+                json_string(&entry.symbol),
+                json_string(&entry.library),
+                (entry.queued_at + entry.queue_wait).as_micros(),
+                entry.duration.as_micros(),
+                entry.thread.tid(),
+                json_string_array(&entry.args),
+                json_string(result),
+                entry.queue_wait.as_micros(),
+            );
+        }
+
+        json.push_str("]}");
+        json
+    }
+}
+
+/// Describes a value for the trace without exposing native pointers.
+fn describe(value: &Value) -> String {
+    let mut description = match value {
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => format!("{s:?}"),
+        Value::Bytes(bytes) => format!("<{} bytes>", bytes.len()),
+        Value::Date(ms) => format!("<date {ms}>"),
+        Value::BigInt(n) => format!("{n}n"),
+        Value::Boolean(b) => b.to_string(),
+        Value::Object(handle) => format!("<handle {}>", handle.inner()),
+        Value::Null => "null".to_string(),
+        Value::Undefined => "undefined".to_string(),
+        Value::Array(items) => {
+            let items: Vec<String> = items.iter().map(describe).collect();
+            format!("[{}]", items.join(", "))
+        }
+        Value::Callback(_) => "<callback>".to_string(),
+        Value::Cancellable(_) => "<cancellable>".to_string(),
+        Value::Ref(ref_val) => format!("ref({})", describe(&ref_val.value)),
+    };
+
+    if let Some((end, _)) = description.char_indices().nth(MAX_VALUE_LEN) {
+        description.truncate(end);
+        description.push('…');
+    }
+
+    description
+}
+
+fn json_string(s: &str) -> String {
+    let mut json = String::with_capacity(s.len() + 2);
+    json.push('"');
+
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(json, "\\u{:04x}", c as u32);
+            }
+            c => json.push(c),
+        }
+    }
+
+    json.push('"');
+    json
+}
+
+fn json_string_array(items: &[String]) -> String {
+    let items: Vec<String> = items.iter().map(|item| json_string(item)).collect();
+    format!("[{}]", items.join(","))
+}
@@ -18,6 +18,7 @@
 //! - [`ClosureCallbackData::tick_callback`]: For `GtkWidget` add_tick_callback
 //! - [`destroy_trampoline`]: Generic destroy notify callback
 //! - [`async_ready_trampoline`]: For `GAsyncReadyCallback`
+//! - [`async_finish_trampoline`]: For `GAsyncReadyCallback` settling a Promise
 
 use std::ffi::c_void;
 use std::ptr::NonNull;
@@ -33,6 +34,9 @@ use gtk4::{
     },
 };
 
+use crate::async_call::AsyncCompletion;
+use crate::crash;
+
 pub struct ClosureGuard {
     closure: NonNull<gobject_ffi::GClosure>,
 }
@@ -89,6 +93,7 @@ impl ClosureCallbackData {
         height: i32,
         user_data: *mut c_void,
     ) {
+        let _scope = crash::enter_callback("draw_func");
         let Some(data_ptr) = NonNull::new(user_data as *mut ClosureCallbackData) else {
             eprintln!(
                 "[gtkx] WARNING: ClosureCallbackData::draw_func: user_data is null, callback skipped"
@@ -135,6 +140,7 @@ impl ClosureCallbackData {
         args: *mut glib::ffi::GVariant,
         user_data: *mut c_void,
     ) -> glib::ffi::gboolean {
+        let _scope = crash::enter_callback("shortcut_func");
         let Some(data_ptr) = NonNull::new(user_data as *mut ClosureCallbackData) else {
             eprintln!(
                 "[gtkx] WARNING: ClosureCallbackData::shortcut_func: user_data is null, callback skipped"
@@ -175,6 +181,7 @@ impl ClosureCallbackData {
         item: *mut gobject_ffi::GObject,
         user_data: *mut c_void,
     ) -> *mut gobject_ffi::GObject {
+        let _scope = crash::enter_callback("tree_list_model_create_func");
         let Some(data_ptr) = NonNull::new(user_data as *mut ClosureCallbackData) else {
             eprintln!(
                 "[gtkx] WARNING: ClosureCallbackData::tree_list_model_create_func: user_data is null, callback skipped"
@@ -213,6 +220,7 @@ impl ClosureCallbackData {
     /// - `value` is the animation value (0.0 to 1.0 typically).
     /// - `user_data` must be a valid pointer to a `ClosureCallbackData`, or null.
     pub unsafe extern "C" fn animation_target_func(value: f64, user_data: *mut c_void) {
+        let _scope = crash::enter_callback("animation_target_func");
         let Some(data_ptr) = NonNull::new(user_data as *mut ClosureCallbackData) else {
             eprintln!(
                 "[gtkx] WARNING: ClosureCallbackData::animation_target_func: user_data is null, callback skipped"
@@ -248,6 +256,7 @@ impl ClosureCallbackData {
         value: f64,
         user_data: *mut c_void,
     ) -> *mut std::ffi::c_char {
+        let _scope = crash::enter_callback("scale_format_value_func");
         let Some(data_ptr) = NonNull::new(user_data as *mut ClosureCallbackData) else {
             eprintln!(
                 "[gtkx] WARNING: ClosureCallbackData::scale_format_value_func: user_data is null, callback skipped"
@@ -292,6 +301,7 @@ impl ClosureCallbackData {
         frame_clock: *mut gobject_ffi::GObject,
         user_data: *mut c_void,
     ) -> glib::ffi::gboolean {
+        let _scope = crash::enter_callback("tick_callback");
         let Some(data_ptr) = NonNull::new(user_data as *mut ClosureCallbackData) else {
             eprintln!(
                 "[gtkx] WARNING: ClosureCallbackData::tick_callback: user_data is null, callback skipped"
@@ -330,6 +340,7 @@ impl ClosureCallbackData {
 /// `user_data` must be a valid pointer to a `GClosure` that was previously
 /// allocated and ref'd, or null. The closure will be invoked and then unref'd.
 pub unsafe extern "C" fn destroy_trampoline(user_data: *mut c_void) {
+    let _scope = crash::enter_callback("destroy_trampoline");
     let Some(closure_ptr) = NonNull::new(user_data as *mut gobject_ffi::GClosure) else {
         eprintln!("[gtkx] WARNING: destroy_trampoline: user_data is null, callback skipped");
         return;
@@ -361,6 +372,7 @@ pub unsafe extern "C" fn async_ready_trampoline(
     res: *mut GAsyncResult,
     user_data: *mut c_void,
 ) {
+    let _scope = crash::enter_callback("async_ready_trampoline");
     let Some(closure_ptr) = NonNull::new(user_data as *mut gobject_ffi::GClosure) else {
         eprintln!("[gtkx] WARNING: async_ready_trampoline: user_data is null, callback skipped");
         return;
@@ -391,6 +403,28 @@ pub unsafe extern "C" fn async_ready_trampoline(
     unsafe { gobject_ffi::g_closure_unref(closure_ptr.as_ptr()) };
 }
 
+/// # Safety
+///
+/// - `source_object` must be a valid `GObject` pointer or null.
+/// - `res` must be a valid `GAsyncResult` pointer.
+/// - `user_data` must be a pointer to an [`AsyncCompletion`] that was previously
+///   allocated with `Box::into_raw`, or null. Ownership is taken.
+pub unsafe extern "C" fn async_finish_trampoline(
+    source_object: *mut gobject_ffi::GObject,
+    res: *mut GAsyncResult,
+    user_data: *mut c_void,
+) {
+    let _scope = crash::enter_callback("async_finish_trampoline");
+    let Some(completion_ptr) = NonNull::new(user_data as *mut AsyncCompletion) else {
+        eprintln!("[gtkx] WARNING: async_finish_trampoline: user_data is null, callback skipped");
+        return;
+    };
+
+    let completion = unsafe { Box::from_raw(completion_ptr.as_ptr()) };
+    let outcome = completion.finish(source_object as *mut c_void, res as *mut c_void);
+    completion.settle(outcome);
+}
+
 pub struct TickCallbackData {
     pub channel: neon::event::Channel,
     pub js_func: std::sync::Arc<neon::handle::Root<neon::types::JsFunction>>,
@@ -432,6 +466,7 @@ impl TickCallbackData {
         frame_clock: *mut gobject_ffi::GObject,
         user_data: *mut c_void,
     ) -> glib::ffi::gboolean {
+        let _scope = crash::enter_callback("tick_callback");
         let Some(data_ptr) = NonNull::new(user_data as *mut TickCallbackData) else {
             eprintln!(
                 "[gtkx] WARNING: TickCallbackData::tick_callback: user_data is null, callback skipped"
@@ -500,6 +535,7 @@ impl PathIntersectionCallbackData {
         kind: i32,
         user_data: *mut c_void,
     ) -> glib::ffi::gboolean {
+        let _scope = crash::enter_callback("path_intersection_func");
         let Some(data_ptr) = NonNull::new(user_data as *mut PathIntersectionCallbackData) else {
             eprintln!(
                 "[gtkx] WARNING: PathIntersectionCallbackData::path_intersection_func: user_data is null, callback skipped"
@@ -580,6 +616,7 @@ impl ShapeRendererCallbackData {
         do_path: glib::ffi::gboolean,
         user_data: *mut c_void,
     ) {
+        let _scope = crash::enter_callback("shape_renderer_func");
         let Some(data_ptr) = NonNull::new(user_data as *mut ShapeRendererCallbackData) else {
             eprintln!(
                 "[gtkx] WARNING: ShapeRendererCallbackData::shape_renderer_func: user_data is null, callback skipped"
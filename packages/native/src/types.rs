@@ -11,13 +11,15 @@
 //! ├── Integer(IntegerKind)    - Sized integers (i8..i64, u8..u64)
 //! ├── Float(FloatKind)        - Floating point (f32, f64)
 //! ├── String(StringType)      - UTF-8 strings (owned or borrowed)
-//! ├── Boolean                 - Boolean values
+//! ├── Boolean                 - Boolean values (one byte)
+//! ├── GBoolean                - `gboolean` values (an `int`)
 //! ├── Null / Undefined        - Null pointer / void return
 //! ├── GObject(GObjectType)    - GObject instances
 //! ├── Boxed(BoxedType)        - GObject boxed types (e.g., GdkRGBA)
 //! ├── Fundamental(FundamentalType) - Fundamental types (GVariant, GParamSpec, etc.)
 //! ├── Array(ArrayType)        - Arrays, GLists, GSLists
 //! ├── Callback(CallbackType)  - JavaScript callback functions
+//! ├── Cancellable             - GCancellable created from an AbortSignal
 //! └── Ref(RefType)            - Pointers to values (out parameters)
 //! ```
 //!
@@ -43,6 +45,8 @@ use crate::{ffi, value};
 mod array;
 mod boxed;
 mod callback;
+pub mod cancellable;
+mod convert;
 mod fundamental;
 mod gobject;
 mod hashtable;
@@ -54,12 +58,15 @@ pub use array::ArrayKind;
 pub use array::ArrayType;
 pub use boxed::{BoxedType, StructType};
 pub use callback::{CallbackKind, CallbackType};
+pub use convert::Converter;
 pub use fundamental::FundamentalType;
 pub use gobject::GObjectType;
 pub use hashtable::{HashTableEntryEncoder, HashTableType};
-pub use numeric::{FloatKind, IntegerKind, IntegerPrimitive, IntegerType, NumericPrimitive};
+pub use numeric::{
+    BitField, FloatKind, IntegerKind, IntegerPrimitive, IntegerType, NumericPrimitive,
+};
 pub use ref_type::RefType;
-pub use string::StringType;
+pub use string::{StringEncoding, StringType};
 
 #[derive(Debug, Clone, Copy, Default)]
 pub enum Ownership {
@@ -136,6 +143,8 @@ pub enum Type {
     Null,
     Undefined,
     Boolean,
+    /// A 4-byte `gboolean`, from a `boolean` type with `size: 32`.
+    GBoolean,
     GObject(GObjectType),
     Boxed(BoxedType),
     Struct(StructType),
@@ -143,6 +152,7 @@ pub enum Type {
     Array(ArrayType),
     HashTable(HashTableType),
     Callback(CallbackType),
+    Cancellable,
     Ref(RefType),
 }
 
@@ -155,6 +165,7 @@ impl std::fmt::Display for Type {
             Type::Null => write!(f, "Null"),
             Type::Undefined => write!(f, "Undefined"),
             Type::Boolean => write!(f, "Boolean"),
+            Type::GBoolean => write!(f, "GBoolean"),
             Type::GObject(_) => write!(f, "GObject"),
             Type::Boxed(t) => write!(f, "Boxed({})", t.type_name),
             Type::Struct(t) => write!(f, "Struct({})", t.type_name),
@@ -162,6 +173,7 @@ impl std::fmt::Display for Type {
             Type::Array(_) => write!(f, "Array"),
             Type::HashTable(_) => write!(f, "HashTable"),
             Type::Callback(t) => write!(f, "Callback({:?})", t.kind),
+            Type::Cancellable => write!(f, "Cancellable"),
             Type::Ref(t) => write!(f, "Ref({})", t.inner_type),
         }
     }
@@ -181,7 +193,14 @@ impl Type {
             "int" => Ok(Type::Integer(IntegerType::from_js_value(cx, value)?)),
             "float" => Ok(Type::Float(FloatKind::from_js_value(cx, value)?)),
             "string" => Ok(Type::String(StringType::from_js_value(cx, value)?)),
-            "boolean" => Ok(Type::Boolean),
+            "boolean" => {
+                let size: Option<Handle<JsNumber>> = obj.get_opt(cx, "size")?;
+                match size.map(|size| size.value(cx) as u64) {
+                    None | Some(8) => Ok(Type::Boolean),
+                    Some(32) => Ok(Type::GBoolean),
+                    Some(size) => cx.throw_type_error(format!("Invalid boolean size: {size}")),
+                }
+            }
             "null" => Ok(Type::Null),
             "undefined" => Ok(Type::Undefined),
             "gobject" => Ok(Type::GObject(GObjectType::from_js_value(cx, value)?)),
@@ -190,6 +209,7 @@ impl Type {
             "array" => Ok(Type::Array(ArrayType::from_js_value(cx, obj.upcast())?)),
             "hashtable" => Ok(Type::HashTable(HashTableType::from_js_value(cx, value)?)),
             "callback" => Ok(Type::Callback(CallbackType::from_js_value(cx, value)?)),
+            "cancellable" => Ok(Type::Cancellable),
             "ref" => Ok(Type::Ref(RefType::from_js_value(cx, obj.upcast())?)),
             "fundamental" => Ok(Type::Fundamental(FundamentalType::from_js_value(
                 cx, value,
@@ -199,15 +219,10 @@ impl Type {
     }
 
     pub fn ptr_to_value(&self, ptr: *mut c_void, context: &str) -> anyhow::Result<value::Value> {
-        use std::ffi::CStr;
         match self {
-            Type::String(_) => {
-                if ptr.is_null() {
-                    return Ok(value::Value::Null);
-                }
-                let c_str = unsafe { CStr::from_ptr(ptr as *const c_char) };
-                Ok(value::Value::String(c_str.to_string_lossy().into_owned()))
-            }
+            Type::String(string_type) => unsafe {
+                string_type.or_lossy().decode_ptr(ptr as *const c_char)
+            },
             Type::Integer(int_type) => {
                 let number = match int_type.kind {
                     IntegerKind::I32 => ptr as i32 as f64,
@@ -216,8 +231,16 @@ impl Type {
                     IntegerKind::U64 => ptr as u64 as f64,
                     _ => ptr as isize as f64,
                 };
-                Ok(value::Value::Number(number))
+                int_type.number_to_value(number)
             }
+            Type::GObject(GObjectType {
+                converter: Some(converter),
+                ..
+            })
+            | Type::Boxed(BoxedType {
+                converter: Some(converter),
+                ..
+            }) => converter.decode(ptr, Ownership::Borrowed),
             Type::GObject(_) => {
                 if ptr.is_null() {
                     return Ok(value::Value::Null);
@@ -238,7 +261,7 @@ impl Type {
                     crate::managed::NativeValue::Boxed(boxed).into(),
                 ))
             }
-            Type::Boolean => {
+            Type::Boolean | Type::GBoolean => {
                 let boolean = ptr as isize != 0;
                 Ok(value::Value::Boolean(boolean))
             }
@@ -307,6 +330,48 @@ impl Type {
             other => types.push(other.into()),
         }
     }
+
+    /// Calls `ptr` through `cif`, reading the return value as this type.
+    ///
+    /// Callback and `Ref` types cannot be returned and yield [`ffi::FfiValue::Void`];
+    /// callers are expected to reject them beforehand.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must point to a function matching `cif`, and `args` must match the
+    /// CIF's argument types.
+    pub unsafe fn call_cif(
+        &self,
+        cif: &libffi::Cif,
+        ptr: libffi::CodePtr,
+        args: &[libffi::Arg],
+    ) -> ffi::FfiValue {
+        unsafe {
+            match self {
+                Type::Undefined => {
+                    cif.call::<()>(ptr, args);
+                    ffi::FfiValue::Void
+                }
+                Type::Integer(int_type) => int_type.kind.call_cif(cif, ptr, args),
+                Type::Float(float_kind) => float_kind.call_cif(cif, ptr, args),
+                Type::String(_) => {
+                    let result = cif.call::<*const c_char>(ptr, args);
+                    ffi::FfiValue::Ptr(result as *mut c_void)
+                }
+                Type::Boolean => ffi::FfiValue::U8(cif.call::<u8>(ptr, args)),
+                Type::GBoolean => ffi::FfiValue::I32(cif.call::<i32>(ptr, args)),
+                Type::GObject(_)
+                | Type::Boxed(_)
+                | Type::Struct(_)
+                | Type::Fundamental(_)
+                | Type::Array(_)
+                | Type::HashTable(_) => ffi::FfiValue::Ptr(cif.call::<*mut c_void>(ptr, args)),
+                Type::Null | Type::Callback(_) | Type::Cancellable | Type::Ref(_) => {
+                    ffi::FfiValue::Void
+                }
+            }
+        }
+    }
 }
 
 impl From<&Type> for libffi::Type {
@@ -316,6 +381,7 @@ impl From<&Type> for libffi::Type {
             Type::Float(ty) => (*ty).into(),
             Type::String(ty) => ty.into(),
             Type::Boolean => libffi::Type::u8(),
+            Type::GBoolean => libffi::Type::i32(),
             Type::Null => libffi::Type::pointer(),
             Type::GObject(ty) => ty.into(),
             Type::Boxed(ty) => ty.into(),
@@ -323,7 +389,7 @@ impl From<&Type> for libffi::Type {
             Type::Fundamental(ty) => ty.into(),
             Type::Array(ty) => ty.into(),
             Type::HashTable(ty) => ty.into(),
-            Type::Callback(_) => libffi::Type::pointer(),
+            Type::Callback(_) | Type::Cancellable => libffi::Type::pointer(),
             Type::Ref(ty) => ty.into(),
             Type::Undefined => libffi::Type::void(),
         }
@@ -343,6 +409,13 @@ impl ffi::FfiEncode for Type {
                 };
                 Ok(ffi::FfiValue::U8(u8::from(boolean)))
             }
+            Type::GBoolean => {
+                let boolean = match value {
+                    value::Value::Boolean(b) => *b,
+                    _ => bail!("Expected a Boolean for gboolean type, got {:?}", value),
+                };
+                Ok(ffi::FfiValue::I32(i32::from(boolean)))
+            }
             Type::Null => Ok(ffi::FfiValue::Ptr(std::ptr::null_mut())),
             Type::Undefined => Ok(ffi::FfiValue::Ptr(std::ptr::null_mut())),
             Type::GObject(t) => t.encode(value, optional),
@@ -352,6 +425,7 @@ impl ffi::FfiEncode for Type {
             Type::Array(t) => t.encode(value, optional),
             Type::HashTable(t) => t.encode(value, optional),
             Type::Callback(t) => t.encode(value, optional),
+            Type::Cancellable => cancellable::encode(value),
             Type::Ref(t) => t.encode(value, optional),
         }
     }
@@ -365,9 +439,10 @@ impl ffi::FfiDecode for Type {
             Type::Integer(t) => t.decode(ffi_value),
             Type::Float(t) => t.decode(ffi_value),
             Type::String(t) => t.decode(ffi_value),
-            Type::Boolean => {
+            Type::Boolean | Type::GBoolean => {
                 let b = match ffi_value {
                     ffi::FfiValue::U8(v) => *v != 0,
+                    ffi::FfiValue::I32(v) => *v != 0,
                     _ => bail!("Expected a boolean ffi::FfiValue, got {:?}", ffi_value),
                 };
                 Ok(value::Value::Boolean(b))
@@ -379,6 +454,7 @@ impl ffi::FfiDecode for Type {
             Type::Array(t) => t.decode(ffi_value),
             Type::HashTable(t) => t.decode(ffi_value),
             Type::Callback(_) => bail!("Callbacks cannot be converted from ffi::FfiValue"),
+            Type::Cancellable => bail!("Cancellables cannot be converted from ffi::FfiValue"),
             Type::Ref(t) => t.decode(ffi_value),
         }
     }
@@ -390,7 +466,7 @@ impl ffi::FfiDecode for Type {
         args: &[crate::arg::Arg],
     ) -> anyhow::Result<value::Value> {
         match self {
-            Type::Array(array_type) => array_type.decode_with_context(ffi_value, ffi_args, args),
+            Type::Array(array_type) => array_type.decode_returned(ffi_value, ffi_args, args),
             Type::Ref(ref_type) => ref_type.decode_with_context(ffi_value, ffi_args, args),
             _ => self.decode(ffi_value),
         }
@@ -1,7 +1,7 @@
-use std::ffi::{CStr, CString, c_char, c_void};
+use std::ffi::{CString, c_char, c_void};
 
 use anyhow::bail;
-use gtk4::glib;
+use gtk4::glib::{self, translate::IntoGlib as _};
 use libffi::middle as libffi;
 use neon::object::Object as _;
 use neon::prelude::*;
@@ -9,7 +9,7 @@ use neon::prelude::*;
 use super::Ownership;
 use crate::arg::Arg;
 use crate::ffi::{FfiStorage, FfiStorageKind};
-use crate::types::{FloatKind, Type};
+use crate::types::{FloatKind, IntegerKind, StringType, Type};
 use crate::{ffi, value};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -161,10 +161,7 @@ impl ffi::FfiEncode for ArrayType {
                 let mut values = Vec::new();
 
                 for value in array {
-                    match value {
-                        value::Value::Number(n) => values.push(*n),
-                        _ => bail!("Expected a Number for integer item type, got {:?}", value),
-                    }
+                    values.push(int_type.to_number(value)?);
                 }
 
                 Ok(ffi::FfiValue::Storage(
@@ -192,17 +189,11 @@ impl ffi::FfiEncode for ArrayType {
                     }
                 }
             }
-            Type::String(_) => {
-                let mut cstrings = Vec::new();
-
-                for v in array {
-                    match v {
-                        value::Value::String(s) => {
-                            cstrings.push(CString::new(s.as_bytes())?);
-                        }
-                        _ => bail!("Expected a String for string item type, got {:?}", v),
-                    }
-                }
+            Type::String(string_type) => {
+                let cstrings = array
+                    .iter()
+                    .map(|v| string_type.encode_cstring(v))
+                    .collect::<anyhow::Result<Vec<CString>>>()?;
 
                 let mut ptrs: Vec<*mut c_void> =
                     cstrings.iter().map(|s| s.as_ptr() as *mut c_void).collect();
@@ -240,7 +231,7 @@ impl ffi::FfiEncode for ArrayType {
                                     );
                                 }
                             }
-                            None => bail!("GObject in array has been garbage collected"),
+                            None => return Err(handle.unavailable_error("GObject in array")),
                         }
                     }
                     return Ok(ffi::FfiValue::Storage(buffer.into()));
@@ -250,7 +241,7 @@ impl ffi::FfiEncode for ArrayType {
                 for handle in &handles {
                     match handle.get_ptr() {
                         Some(ptr) => ptrs.push(ptr),
-                        None => bail!("GObject in array has been garbage collected"),
+                        None => return Err(handle.unavailable_error("GObject in array")),
                     }
                 }
                 let ptr = ptrs.as_ptr() as *mut c_void;
@@ -272,6 +263,18 @@ impl ffi::FfiEncode for ArrayType {
 
                 Ok(ffi::FfiValue::Storage(values.into()))
             }
+            Type::GBoolean => {
+                let mut values = Vec::new();
+
+                for value in array {
+                    match value {
+                        value::Value::Boolean(b) => values.push(i32::from(*b)),
+                        _ => bail!("Expected a Boolean for gboolean item type, got {:?}", value),
+                    }
+                }
+
+                Ok(ffi::FfiValue::Storage(values.into()))
+            }
             _ => bail!("Unsupported array item type: {:?}", self.item_type),
         }
     }
@@ -288,8 +291,8 @@ impl ffi::FfiDecode for ArrayType {
                 return Ok(value::Value::Array(vec![]));
             }
 
-            if matches!(&*self.item_type, Type::String(_)) {
-                return self.decode_null_terminated_string_array(*ptr);
+            if let Type::String(string_type) = &*self.item_type {
+                return self.decode_null_terminated_string_array(*ptr, string_type);
             }
 
             bail!(
@@ -320,18 +323,7 @@ impl ffi::FfiDecode for ArrayType {
                 let length = self.size_from_args(ffi_args, args, *size_index)?;
 
                 if let ffi::FfiValue::Ptr(ptr) = ffi_value {
-                    if ptr.is_null() {
-                        return Ok(value::Value::Array(vec![]));
-                    }
-
-                    if let Type::Integer(int_type) = &*self.item_type {
-                        return Self::decode_sized_byte_array(*ptr, length, &int_type.kind);
-                    }
-
-                    bail!(
-                        "Sized arrays are only supported for integer types, got: {:?}",
-                        self.item_type
-                    );
+                    return self.decode_sized(*ptr, length);
                 }
             }
             ArrayKind::Fixed { size } => {
@@ -379,9 +371,33 @@ impl ArrayType {
         Ok(value::Value::Array(values))
     }
 
+    /// Decodes an array returned by the callee. A `sized` array transferred
+    /// in full has its container freed with `g_free` once decoded, whether or
+    /// not its items converted, as the `Ref` path does for out-parameters.
+    pub fn decode_returned(
+        &self,
+        ffi_value: &ffi::FfiValue,
+        ffi_args: &[ffi::FfiValue],
+        args: &[Arg],
+    ) -> anyhow::Result<value::Value> {
+        let value = ffi::FfiDecode::decode_with_context(self, ffi_value, ffi_args, args);
+
+        if let (ArrayKind::Sized { .. }, ffi::FfiValue::Ptr(ptr)) = (&self.kind, ffi_value)
+            && self.ownership.is_full()
+            && !ptr.is_null()
+        {
+            // SAFETY: The callee transferred the container to the caller, and
+            // its items were released or copied by `decode_sized`.
+            unsafe { glib::ffi::g_free(*ptr) };
+        }
+
+        value
+    }
+
     fn decode_null_terminated_string_array(
         &self,
         ptr: *mut c_void,
+        string_type: &StringType,
     ) -> anyhow::Result<value::Value> {
         let mut values = Vec::new();
         let str_array = ptr as *const *const c_char;
@@ -391,8 +407,7 @@ impl ArrayType {
             if str_ptr.is_null() {
                 break;
             }
-            let c_str = unsafe { CStr::from_ptr(str_ptr) };
-            values.push(value::Value::String(c_str.to_string_lossy().into_owned()));
+            values.push(unsafe { string_type.or_lossy().decode_ptr(str_ptr) });
             i += 1;
         }
 
@@ -400,14 +415,19 @@ impl ArrayType {
             unsafe { glib::ffi::g_strfreev(ptr as *mut *mut c_char) };
         }
 
-        Ok(value::Value::Array(values))
+        Ok(value::Value::Array(
+            values.into_iter().collect::<anyhow::Result<_>>()?,
+        ))
     }
 
     fn decode_storage(&self, storage: &FfiStorage) -> anyhow::Result<value::Value> {
         let values = match &*self.item_type {
             Type::Integer(int_type) => {
                 let f64_vec = int_type.kind.vec_to_f64(storage)?;
-                f64_vec.into_iter().map(value::Value::Number).collect()
+                f64_vec
+                    .into_iter()
+                    .map(|number| int_type.number_to_value(number))
+                    .collect::<anyhow::Result<_>>()?
             }
             Type::Float(float_kind) => match float_kind {
                 FloatKind::F32 => {
@@ -422,11 +442,11 @@ impl ArrayType {
                     f64_vec.iter().map(|v| value::Value::Number(*v)).collect()
                 }
             },
-            Type::String(_) => {
+            Type::String(string_type) => {
                 let cstrings = storage.as_cstring_array()?;
                 cstrings
                     .iter()
-                    .map(|cstr| Ok(value::Value::String(cstr.to_str()?.to_string())))
+                    .map(|cstr| string_type.decode_cstr(cstr))
                     .collect::<anyhow::Result<Vec<value::Value>>>()?
             }
             Type::Boolean => {
@@ -436,6 +456,11 @@ impl ArrayType {
                     .map(|v| value::Value::Boolean(*v != 0))
                     .collect()
             }
+            Type::GBoolean => IntegerKind::I32
+                .vec_to_f64(storage)?
+                .into_iter()
+                .map(|v| value::Value::Boolean(v != 0.0))
+                .collect(),
             Type::GObject(_) | Type::Boxed(_) | Type::Struct(_) | Type::Fundamental(_) => {
                 let handles = storage.as_object_array()?;
                 handles
@@ -452,6 +477,88 @@ impl ArrayType {
         Ok(value::Value::Array(values))
     }
 
+    /// Decodes a C array of `length` items whose length was passed in another
+    /// argument, such as an out-parameter `Ref`. Pointer items owned by the
+    /// array (`full` ownership) are released once converted, or when an item
+    /// fails to convert; the container itself is freed by whoever received it.
+    fn decode_sized(&self, ptr: *mut c_void, length: usize) -> anyhow::Result<value::Value> {
+        if ptr.is_null() {
+            return Ok(value::Value::Array(vec![]));
+        }
+
+        match &*self.item_type {
+            Type::Integer(int_type) => Self::decode_sized_byte_array(ptr, length, &int_type.kind),
+            Type::Float(float_kind) => {
+                let item_size = float_kind.byte_size();
+                let values = (0..length)
+                    .map(|i| {
+                        // SAFETY: The callee reported `length` items at `ptr`.
+                        let item_ptr = unsafe { (ptr as *const u8).add(i * item_size) };
+                        value::Value::Number(float_kind.read_ptr(item_ptr))
+                    })
+                    .collect();
+                Ok(value::Value::Array(values))
+            }
+            Type::String(_) | Type::GObject(_) | Type::Boxed(_) | Type::Fundamental(_) => {
+                // SAFETY: The callee reported `length` pointers at `ptr`.
+                let items =
+                    unsafe { std::slice::from_raw_parts(ptr as *const *mut c_void, length) };
+
+                let values = items
+                    .iter()
+                    .map(|&item| self.item_type.ptr_to_value(item, "sized array item"))
+                    .collect::<anyhow::Result<Vec<_>>>();
+
+                let freed = if self.ownership.is_full() {
+                    self.free_items(items)
+                } else {
+                    Ok(())
+                };
+
+                let values = values?;
+                freed?;
+
+                Ok(value::Value::Array(values))
+            }
+            _ => bail!(
+                "Sized arrays are not supported for item type {:?}",
+                self.item_type
+            ),
+        }
+    }
+
+    /// Releases pointer items transferred to the caller, after
+    /// [`Type::ptr_to_value`] took its own copies or references.
+    fn free_items(&self, items: &[*mut c_void]) -> anyhow::Result<()> {
+        let items = items.iter().copied().filter(|item| !item.is_null());
+
+        match &*self.item_type {
+            Type::String(_) => items.for_each(|item| unsafe { glib::ffi::g_free(item) }),
+            Type::GObject(_) => {
+                items.for_each(|item| unsafe { glib::gobject_ffi::g_object_unref(item.cast()) });
+            }
+            Type::Boxed(boxed_type) => {
+                let Some(gtype) = boxed_type.gtype() else {
+                    bail!(
+                        "Cannot free owned {} array items without a GType",
+                        boxed_type.type_name
+                    );
+                };
+                items.for_each(|item| unsafe {
+                    glib::gobject_ffi::g_boxed_free(gtype.into_glib(), item);
+                });
+            }
+            Type::Fundamental(fundamental_type) => {
+                if let (_, Some(unref_fn)) = fundamental_type.lookup_fns()? {
+                    items.for_each(|item| unsafe { unref_fn(item) });
+                }
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
     fn decode_sized_byte_array(
         ptr: *mut c_void,
         length: usize,
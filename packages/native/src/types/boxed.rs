@@ -10,7 +10,8 @@ use libffi::middle as libffi;
 use neon::object::Object as _;
 use neon::prelude::*;
 
-use super::Ownership;
+use super::{Converter, Ownership};
+use crate::diagnostics::HandleKind;
 use crate::managed::{Boxed, NativeValue};
 use crate::state::GtkThreadState;
 use crate::{ffi, value};
@@ -21,6 +22,8 @@ pub struct BoxedType {
     pub type_name: String,
     pub library: Option<String>,
     pub get_type_fn: Option<String>,
+    /// Exchanges instances as a JavaScript value; see [`Converter`].
+    pub converter: Option<Converter>,
 }
 
 impl BoxedType {
@@ -35,6 +38,7 @@ impl BoxedType {
             type_name,
             library,
             get_type_fn,
+            converter: None,
         }
     }
 
@@ -60,7 +64,12 @@ impl BoxedType {
             .map(|s: Handle<'_, JsString>| s.value(cx))
             .ok();
 
-        Ok(Self::new(ownership, type_name, library, get_type_fn))
+        let converter = Converter::from_js_value(cx, obj, HandleKind::Boxed)?;
+
+        Ok(Self {
+            converter,
+            ..Self::new(ownership, type_name, library, get_type_fn)
+        })
     }
 
     pub fn gtype_from_name(&self) -> Option<glib::Type> {
@@ -100,9 +109,17 @@ impl From<&BoxedType> for libffi::Type {
 
 impl ffi::FfiEncode for BoxedType {
     fn encode(&self, value: &value::Value, _optional: bool) -> anyhow::Result<ffi::FfiValue> {
-        let ptr = value.object_ptr("Boxed object")?;
+        if let Some(converter) = self.converter
+            && Converter::applies_to(value)
+        {
+            return converter.encode(value, self.ownership);
+        }
+
+        // Plain structs registered without a GType can only be checked by kind.
+        let gtype = self.gtype();
+        let ptr = value.typed_object_ptr(HandleKind::Boxed, gtype.map(|gtype| gtype.name()))?;
 
-        if let Some(gtype) = self.gtype()
+        if let Some(gtype) = gtype
             && self.ownership.is_full()
             && !ptr.is_null()
         {
@@ -121,6 +138,10 @@ impl ffi::FfiDecode for BoxedType {
             return Ok(value::Value::Null);
         };
 
+        if let Some(converter) = self.converter {
+            return converter.decode(boxed_ptr, self.ownership);
+        }
+
         let gtype = self.gtype();
         let boxed = if self.ownership.is_full() {
             NativeValue::Boxed(Boxed::from_glib_full(gtype, boxed_ptr))
@@ -142,6 +163,8 @@ pub struct StructType {
     pub ownership: Ownership,
     pub type_name: String,
     pub size: Option<usize>,
+    /// Alignment in bytes, needed to embed the struct in a computed layout.
+    pub alignment: Option<usize>,
 }
 
 impl StructType {
@@ -150,6 +173,7 @@ impl StructType {
             ownership,
             type_name,
             size,
+            alignment: None,
         }
     }
 
@@ -169,7 +193,14 @@ impl StructType {
             .map(|n: Handle<'_, JsNumber>| n.value(cx) as usize)
             .ok();
 
-        Ok(Self::new(ownership, type_name, size))
+        let alignment = obj
+            .get_opt::<JsNumber, _, _>(cx, "alignment")?
+            .map(|alignment| alignment.value(cx) as usize);
+
+        Ok(Self {
+            alignment,
+            ..Self::new(ownership, type_name, size)
+        })
     }
 }
 
@@ -181,7 +212,7 @@ impl From<&StructType> for libffi::Type {
 
 impl ffi::FfiEncode for StructType {
     fn encode(&self, value: &value::Value, _optional: bool) -> anyhow::Result<ffi::FfiValue> {
-        let ptr = value.object_ptr("Struct object")?;
+        let ptr = value.typed_object_ptr(HandleKind::Boxed, None)?;
         Ok(ffi::FfiValue::Ptr(ptr))
     }
 }
@@ -196,7 +227,8 @@ impl ffi::FfiDecode for StructType {
             Boxed::from_glib_full(None, struct_ptr)
         } else {
             Boxed::borrowed(None, struct_ptr)
-        };
+        }
+        .with_size(self.size);
 
         Ok(value::Value::Object(NativeValue::Boxed(boxed).into()))
     }
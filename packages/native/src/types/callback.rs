@@ -1,20 +1,23 @@
-use std::ffi::c_void;
+use std::ffi::{CStr, c_void};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicPtr, Ordering};
 
 use gtk4::glib::{
     self, gobject_ffi,
+    prelude::{ObjectExt as _, ObjectType as _},
     translate::{FromGlibPtrFull as _, FromGlibPtrNone as _, ToGlibPtr as _},
     value::ToValue as _,
 };
 use neon::prelude::*;
 
+use crate::crash;
 use crate::ffi::{CallbackValue, FfiStorage, FfiStorageKind};
 use crate::gtk_dispatch::GtkDispatcher;
 use crate::js_dispatch;
 use crate::trampoline::{ClosureCallbackData, ClosureGuard};
 use crate::types::Type;
 use crate::value::Callback;
+use crate::watchdog::{FrameKind, WaitThread, Watchdog};
 use crate::{ffi, value};
 
 struct ClosureContext {
@@ -121,6 +124,11 @@ impl ClosureContext {
 
             let return_type_inner = *return_type.clone();
 
+            let _frame = Watchdog::global().track(WaitThread::Gtk, FrameKind::Activity, || {
+                describe_emission(args)
+            });
+            let _scope = crash::enter_callback("closure");
+
             let args_values = value::Value::from_glib_values(args, &self.arg_types)
                 .expect("Failed to convert GLib callback arguments");
 
@@ -149,6 +157,33 @@ impl ClosureContext {
     }
 }
 
+/// Describes a closure invocation for watchdog reports, naming the signal being
+/// emitted when the closure is a signal handler.
+fn describe_emission(args: &[glib::Value]) -> String {
+    let Some(instance) = args
+        .first()
+        .and_then(|value| value.get::<glib::Object>().ok())
+    else {
+        return "JS closure".to_string();
+    };
+
+    let type_name = instance.type_().name();
+    let hint =
+        unsafe { gobject_ffi::g_signal_get_invocation_hint(instance.as_ptr() as *mut c_void) };
+
+    if hint.is_null() {
+        return format!("JS closure on {type_name}");
+    }
+
+    let name = unsafe { gobject_ffi::g_signal_name((*hint).signal_id) };
+    if name.is_null() {
+        return format!("JS closure on {type_name}");
+    }
+
+    let name = unsafe { CStr::from_ptr(name) }.to_string_lossy();
+    format!("signal \"{name}\" on {type_name}")
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum CallbackKind {
     Closure,
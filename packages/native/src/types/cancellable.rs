@@ -0,0 +1,141 @@
+//! `GCancellable` arguments bridged from JavaScript `AbortSignal`s.
+//!
+//! An argument of type `{ type: "cancellable" }` accepts an `AbortSignal`. A
+//! `GCancellable` owned by the call is created for it and cancelled on the GTK
+//! thread when the signal aborts. An existing `GCancellable` handle, `null` or
+//! `undefined` are also accepted.
+//!
+//! The `abort` listener only holds a weak reference to the cancellable, so a
+//! signal that outlives its calls does not keep their cancellables alive or
+//! cancel them after they are gone. Listeners are also removed once their call
+//! returns, or settles for async calls (see [`AbortListener`]), so a
+//! long-lived signal reused across calls does not collect them.
+
+use std::fmt;
+
+use gtk4::{
+    gio,
+    glib::{prelude::ObjectExt as _, translate::ToGlibPtr as _},
+};
+use neon::prelude::*;
+
+use crate::{diagnostics::HandleKind, ffi, gtk_dispatch::GtkDispatcher, value};
+
+/// An `abort` listener added to an `AbortSignal`, to be removed once the call
+/// it cancels has completed.
+pub struct AbortListener {
+    signal: Root<JsObject>,
+    listener: Root<JsFunction>,
+}
+
+impl AbortListener {
+    /// Removes the listener from its signal. Must be called on the JS thread.
+    pub fn detach<'a, C: Context<'a>>(self, cx: &mut C) -> NeonResult<()> {
+        let signal = self.signal.into_inner(cx);
+        let listener = self.listener.into_inner(cx);
+
+        let remove_event_listener: Handle<JsFunction> = signal.get(cx, "removeEventListener")?;
+        let event = cx.string("abort");
+        remove_event_listener.call(cx, signal, [event.upcast(), listener.upcast()])?;
+
+        Ok(())
+    }
+}
+
+impl fmt::Debug for AbortListener {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AbortListener").finish_non_exhaustive()
+    }
+}
+
+/// Creates a `GCancellable` that is cancelled on the GTK thread when `signal`
+/// aborts, along with the listener to remove once the call completes.
+/// Already-aborted signals yield an already-cancelled cancellable and no
+/// listener.
+pub fn cancellable_from_signal<'a, C: Context<'a>>(
+    cx: &mut C,
+    signal: Handle<JsObject>,
+) -> NeonResult<(gio::Cancellable, Option<AbortListener>)> {
+    let cancellable = gio::Cancellable::new();
+
+    let listener = add_abort_listener(cx, signal, &cancellable)?.map(|listener| AbortListener {
+        signal: signal.root(cx),
+        listener: listener.root(cx),
+    });
+
+    Ok((cancellable, listener))
+}
+
+/// Cancels `cancellable` when `signal` aborts, returning the listener, or
+/// cancels it right away if the signal already aborted.
+fn add_abort_listener<'a, C: Context<'a>>(
+    cx: &mut C,
+    signal: Handle<JsObject>,
+    cancellable: &gio::Cancellable,
+) -> NeonResult<Option<Handle<'a, JsFunction>>> {
+    let aborted = signal.get::<JsBoolean, _, _>(cx, "aborted")?.value(cx);
+    if aborted {
+        cancellable.cancel();
+        return Ok(None);
+    }
+
+    let target = cancellable.downgrade();
+    let listener = JsFunction::new(cx, move |mut cx| {
+        let target = target.clone();
+        GtkDispatcher::global().schedule(move || {
+            if let Some(target) = target.upgrade() {
+                target.cancel();
+            }
+        });
+        Ok(cx.undefined())
+    })?;
+
+    let add_event_listener: Handle<JsFunction> = signal.get(cx, "addEventListener")?;
+    let event = cx.string("abort");
+    let listener_options = cx.empty_object();
+    let once = cx.boolean(true);
+    listener_options.set(cx, "once", once)?;
+
+    add_event_listener.call(
+        cx,
+        signal,
+        [event.upcast(), listener.upcast(), listener_options.upcast()],
+    )?;
+
+    Ok(Some(listener))
+}
+
+/// Converts a JS argument for a cancellable parameter into a [`value::Value`],
+/// along with the `abort` listener to remove once the call completes when the
+/// argument is an `AbortSignal`.
+pub fn value_from_js<'a, C: Context<'a>>(
+    cx: &mut C,
+    value: Handle<JsValue>,
+) -> NeonResult<(value::Value, Option<AbortListener>)> {
+    if value.is_a::<JsBox<crate::managed::NativeHandle>, _>(cx)
+        || value.is_a::<JsNull, _>(cx)
+        || value.is_a::<JsUndefined, _>(cx)
+    {
+        return Ok((value::Value::from_js_value(cx, value)?, None));
+    }
+
+    let signal = value
+        .downcast::<JsObject, _>(cx)
+        .or_else(|_| cx.throw_type_error("Expected an AbortSignal for cancellable type"))?;
+
+    let (cancellable, listener) = cancellable_from_signal(cx, signal)?;
+
+    Ok((value::Value::Cancellable(cancellable), listener))
+}
+
+pub fn encode(value: &value::Value) -> anyhow::Result<ffi::FfiValue> {
+    match value {
+        value::Value::Cancellable(cancellable) => {
+            let ptr: *mut gio::ffi::GCancellable = cancellable.to_glib_none().0;
+            Ok(ffi::FfiValue::Ptr(ptr.cast()))
+        }
+        other => Ok(ffi::FfiValue::Ptr(
+            other.typed_object_ptr(HandleKind::GObject, Some("GCancellable"))?,
+        )),
+    }
+}
@@ -0,0 +1,277 @@
+//! Value converters for common GLib types.
+//!
+//! A `boxed` or `gobject` type descriptor with a `convert` property exchanges
+//! instances of a well-known GLib type as an idiomatic JavaScript value instead
+//! of an opaque handle:
+//!
+//! | `convert` | Native | JavaScript |
+//! |-----------|--------|------------|
+//! | `date` | `GDateTime` (boxed) | `Date`, millisecond precision |
+//! | `bigint` | `GDateTime` (boxed) | `bigint` microseconds since the Unix epoch |
+//! | `path` | `GFile` (gobject) | Local path string |
+//! | `uri` | `GFile` (gobject) | URI string |
+//! | `bytes` | `GBytes` (boxed) | `Uint8Array` |
+//! | `string` | `GIcon` (gobject) | `g_icon_to_string` form |
+//!
+//! Handles, `null` and `undefined` are still passed through unchanged, so a
+//! converted argument also accepts an existing instance. Converted arguments
+//! are created on the GTK thread and kept alive for the duration of the call;
+//! `GDateTime`s created from JavaScript are in UTC.
+
+use std::ffi::c_void;
+
+use anyhow::{Context as _, bail};
+use gtk4::gio::{self, prelude::FileExt as _};
+use gtk4::glib::{
+    self,
+    prelude::{Cast as _, ObjectExt as _, StaticType as _},
+    translate::{FromGlibPtrFull, FromGlibPtrNone, IntoGlib as _, ToGlibPtr as _},
+};
+use neon::prelude::*;
+
+use super::Ownership;
+use crate::diagnostics::HandleKind;
+use crate::ffi::{FfiStorage, FfiStorageKind};
+use crate::managed::{Boxed, NativeValue};
+use crate::{ffi, value};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Converter {
+    Date,
+    BigInt,
+    Path,
+    Uri,
+    Bytes,
+    String,
+}
+
+impl Converter {
+    /// Reads the optional `convert` property of a `boxed` or `gobject` type
+    /// descriptor, checking that the converter applies to that kind of type.
+    pub fn from_js_value(
+        cx: &mut FunctionContext,
+        obj: Handle<JsObject>,
+        kind: HandleKind,
+    ) -> NeonResult<Option<Self>> {
+        let Some(name) = obj.get_opt::<JsString, _, _>(cx, "convert")? else {
+            return Ok(None);
+        };
+
+        let converter = match name.value(cx).as_str() {
+            "date" => Converter::Date,
+            "bigint" => Converter::BigInt,
+            "path" => Converter::Path,
+            "uri" => Converter::Uri,
+            "bytes" => Converter::Bytes,
+            "string" => Converter::String,
+            other => return cx.throw_type_error(format!("Unknown converter '{other}'")),
+        };
+
+        if converter.handle_kind() != kind {
+            return cx.throw_type_error(format!(
+                "The '{}' converter applies to {} types, not {}",
+                converter.name(),
+                converter.handle_kind().as_str(),
+                kind.as_str()
+            ));
+        }
+
+        Ok(Some(converter))
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Converter::Date => "date",
+            Converter::BigInt => "bigint",
+            Converter::Path => "path",
+            Converter::Uri => "uri",
+            Converter::Bytes => "bytes",
+            Converter::String => "string",
+        }
+    }
+
+    fn handle_kind(self) -> HandleKind {
+        match self {
+            Converter::Date | Converter::BigInt | Converter::Bytes => HandleKind::Boxed,
+            Converter::Path | Converter::Uri | Converter::String => HandleKind::GObject,
+        }
+    }
+
+    /// The GType of the boxed type this converter produces, if any.
+    fn boxed_gtype(self) -> Option<glib::Type> {
+        match self {
+            Converter::Date | Converter::BigInt => Some(glib::DateTime::static_type()),
+            Converter::Bytes => Some(glib::Bytes::static_type()),
+            Converter::Path | Converter::Uri | Converter::String => None,
+        }
+    }
+
+    /// Whether `value` should be converted rather than passed as a handle.
+    pub fn applies_to(value: &value::Value) -> bool {
+        !matches!(
+            value,
+            value::Value::Object(_) | value::Value::Null | value::Value::Undefined
+        )
+    }
+
+    /// Creates a native instance from a JavaScript value.
+    pub fn to_native(self, value: &value::Value) -> anyhow::Result<NativeValue> {
+        let instance = match (self, value) {
+            (Converter::Date, value::Value::Date(ms) | value::Value::Number(ms)) => {
+                if !ms.is_finite() {
+                    bail!("Expected a finite time for the 'date' converter, got {ms}");
+                }
+                date_time_instance((*ms * 1000.0) as i64)?
+            }
+            (Converter::BigInt, value::Value::BigInt(micros)) => date_time_instance(*micros)?,
+            (Converter::Bytes, value::Value::Bytes(bytes)) => {
+                let bytes = glib::Bytes::from(bytes.as_slice());
+                let ptr: *mut glib::ffi::GBytes = bytes.to_glib_full();
+                NativeValue::Boxed(Boxed::from_glib_full(
+                    Some(glib::Bytes::static_type()),
+                    ptr.cast(),
+                ))
+            }
+            (Converter::Path, value::Value::String(path)) => {
+                NativeValue::GObject(gio::File::for_path(path).upcast())
+            }
+            (Converter::Uri, value::Value::String(uri)) => {
+                NativeValue::GObject(gio::File::for_uri(uri).upcast())
+            }
+            (Converter::String, value::Value::String(icon)) => NativeValue::GObject(
+                gio::Icon::for_string(icon)
+                    .with_context(|| format!("Invalid GIcon string {icon:?}"))?
+                    .upcast(),
+            ),
+            _ => bail!(
+                "Expected {} for the '{}' converter, got {:?}",
+                self.expected(),
+                self.name(),
+                value
+            ),
+        };
+
+        Ok(instance)
+    }
+
+    fn expected(self) -> &'static str {
+        match self {
+            Converter::Date => "a Date",
+            Converter::BigInt => "a BigInt",
+            Converter::Bytes => "a Uint8Array",
+            Converter::Path | Converter::Uri | Converter::String => "a String",
+        }
+    }
+
+    /// Encodes a converted argument. The instance is kept in the argument's
+    /// storage until the call returns; with `full` ownership the callee gets
+    /// its own reference or copy.
+    pub fn encode(
+        self,
+        value: &value::Value,
+        ownership: Ownership,
+    ) -> anyhow::Result<ffi::FfiValue> {
+        let instance = self.to_native(value)?;
+        let ptr = instance.as_ptr();
+
+        let arg_ptr = match (ownership.is_full(), self.boxed_gtype()) {
+            (false, _) => ptr,
+            (true, Some(gtype)) => unsafe {
+                glib::gobject_ffi::g_boxed_copy(gtype.into_glib(), ptr as *const _)
+            },
+            (true, None) => unsafe { glib::gobject_ffi::g_object_ref(ptr.cast()).cast() },
+        };
+
+        Ok(ffi::FfiValue::Storage(FfiStorage::new(
+            arg_ptr,
+            FfiStorageKind::Instance(instance),
+        )))
+    }
+
+    /// Converts a native instance to its JavaScript value, releasing it when
+    /// `ownership` is `full`.
+    pub fn decode(self, ptr: *mut c_void, ownership: Ownership) -> anyhow::Result<value::Value> {
+        if ptr.is_null() {
+            return Ok(value::Value::Null);
+        }
+
+        match self {
+            Converter::Date | Converter::BigInt => {
+                let date_time =
+                    unsafe { wrap::<glib::DateTime, glib::ffi::GDateTime>(ptr, ownership) };
+                let micros = date_time.to_unix() * 1_000_000 + i64::from(date_time.microsecond());
+
+                Ok(match self {
+                    Converter::Date => value::Value::Date(micros.div_euclid(1000) as f64),
+                    _ => value::Value::BigInt(micros),
+                })
+            }
+            Converter::Bytes => {
+                let bytes = unsafe { wrap::<glib::Bytes, glib::ffi::GBytes>(ptr, ownership) };
+                Ok(value::Value::Bytes(bytes.to_vec()))
+            }
+            Converter::Path => {
+                let file = downcast::<gio::File>(ptr, ownership)?;
+                let path = file.path().context(
+                    "GFile has no local path; use the 'uri' converter for non-local files",
+                )?;
+                let path = path
+                    .into_os_string()
+                    .into_string()
+                    .map_err(|path| anyhow::anyhow!("GFile path {path:?} is not valid UTF-8"))?;
+                Ok(value::Value::String(path))
+            }
+            Converter::Uri => {
+                let file = downcast::<gio::File>(ptr, ownership)?;
+                Ok(value::Value::String(file.uri().to_string()))
+            }
+            Converter::String => {
+                let icon = downcast::<gio::Icon>(ptr, ownership)?;
+                let string = gio::prelude::IconExt::to_string(&icon)
+                    .context("GIcon cannot be serialized to a string")?;
+                Ok(value::Value::String(string.to_string()))
+            }
+        }
+    }
+}
+
+/// A UTC `GDateTime` `micros` microseconds after the Unix epoch.
+fn date_time_instance(micros: i64) -> anyhow::Result<NativeValue> {
+    let date_time = glib::DateTime::from_unix_utc(micros.div_euclid(1_000_000))
+        .and_then(|date_time| date_time.add(glib::TimeSpan(micros.rem_euclid(1_000_000))))
+        .with_context(|| format!("Time {micros}µs since the Unix epoch is out of range"))?;
+
+    let ptr: *mut glib::ffi::GDateTime = date_time.to_glib_full();
+    Ok(NativeValue::Boxed(Boxed::from_glib_full(
+        Some(glib::DateTime::static_type()),
+        ptr.cast(),
+    )))
+}
+
+/// Takes ownership of `ptr` when `ownership` is `full`, or adds a reference.
+///
+/// # Safety
+///
+/// `ptr` must be a valid, non-null instance of `T`.
+unsafe fn wrap<T, P>(ptr: *mut c_void, ownership: Ownership) -> T
+where
+    T: FromGlibPtrFull<*mut P> + FromGlibPtrNone<*mut P>,
+{
+    if ownership.is_full() {
+        unsafe { T::from_glib_full(ptr.cast()) }
+    } else {
+        unsafe { T::from_glib_none(ptr.cast()) }
+    }
+}
+
+fn downcast<T: glib::prelude::IsA<glib::Object>>(
+    ptr: *mut c_void,
+    ownership: Ownership,
+) -> anyhow::Result<T> {
+    let object = unsafe { wrap::<glib::Object, glib::gobject_ffi::GObject>(ptr, ownership) };
+    let type_name = object.type_().name();
+
+    object
+        .downcast::<T>()
+        .map_err(|_| anyhow::anyhow!("Expected a {}, got a {type_name}", T::static_type().name()))
+}
@@ -3,12 +3,17 @@
 //! GLib fundamental types are custom reference-counted types that don't
 //! derive from GObject. Examples include `GParamSpec` and Pango layout types.
 //! They have custom ref/unref functions rather than using `g_object_ref/unref`.
+//!
+//! A [`FundamentalType`] may name the GType its arguments must be instances of.
+//! This is only valid for fundamentals whose instances are `GTypeInstance`s,
+//! such as `GParamSpec` or `GskRenderNode`, and not for e.g. `GVariant`.
 
 use libffi::middle as libffi;
 use neon::object::Object as _;
 use neon::prelude::*;
 
 use super::Ownership;
+use crate::diagnostics::HandleKind;
 use crate::managed::{Fundamental, NativeValue, RefFn, UnrefFn};
 use crate::state::GtkThreadState;
 use crate::{ffi, value};
@@ -19,6 +24,8 @@ pub struct FundamentalType {
     pub library: String,
     pub ref_func: String,
     pub unref_func: String,
+    /// GType name the argument must be an instance of, checked before the call.
+    pub type_name: Option<String>,
 }
 
 impl FundamentalType {
@@ -33,6 +40,7 @@ impl FundamentalType {
             library,
             ref_func,
             unref_func,
+            type_name: None,
         }
     }
 
@@ -43,13 +51,19 @@ impl FundamentalType {
         let library: Handle<JsString> = obj.get(cx, "library")?;
         let ref_func: Handle<JsString> = obj.get(cx, "refFn")?;
         let unref_func: Handle<JsString> = obj.get(cx, "unrefFn")?;
-
-        Ok(Self::new(
-            ownership,
-            library.value(cx),
-            ref_func.value(cx),
-            unref_func.value(cx),
-        ))
+        let type_name = obj
+            .get_opt::<JsString, _, _>(cx, "typeName")?
+            .map(|type_name| type_name.value(cx));
+
+        Ok(Self {
+            type_name,
+            ..Self::new(
+                ownership,
+                library.value(cx),
+                ref_func.value(cx),
+                unref_func.value(cx),
+            )
+        })
     }
 
     pub fn lookup_fns(&self) -> anyhow::Result<(Option<RefFn>, Option<UnrefFn>)> {
@@ -83,7 +97,7 @@ impl From<&FundamentalType> for libffi::Type {
 
 impl ffi::FfiEncode for FundamentalType {
     fn encode(&self, value: &value::Value, _optional: bool) -> anyhow::Result<ffi::FfiValue> {
-        let mut ptr = value.object_ptr("Fundamental")?;
+        let mut ptr = value.typed_object_ptr(HandleKind::Fundamental, self.type_name.as_deref())?;
 
         if self.ownership.is_full() && !ptr.is_null() {
             let (ref_fn, _) = self.lookup_fns()?;
@@ -2,24 +2,50 @@ use gtk4::glib::{self, translate::FromGlibPtrFull as _, translate::FromGlibPtrNo
 use libffi::middle as libffi;
 use neon::prelude::*;
 
-use super::Ownership;
+use super::{Converter, Ownership};
+use crate::diagnostics::HandleKind;
 use crate::managed::NativeValue;
 use crate::{ffi, value};
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub struct GObjectType {
     pub ownership: Ownership,
+    /// GType name the argument must be an instance of, checked before the call.
+    pub type_name: Option<String>,
+    /// Exchanges instances as a JavaScript value; see [`Converter`].
+    pub converter: Option<Converter>,
 }
 
 impl GObjectType {
     pub fn new(ownership: Ownership) -> Self {
-        GObjectType { ownership }
+        GObjectType {
+            ownership,
+            type_name: None,
+            converter: None,
+        }
+    }
+
+    pub fn with_type_name(ownership: Ownership, type_name: String) -> Self {
+        GObjectType {
+            ownership,
+            type_name: Some(type_name),
+            converter: None,
+        }
     }
 
     pub fn from_js_value(cx: &mut FunctionContext, value: Handle<JsValue>) -> NeonResult<Self> {
         let obj = value.downcast::<JsObject, _>(cx).or_throw(cx)?;
         let ownership = Ownership::from_js_value(cx, obj, "gobject")?;
-        Ok(Self::new(ownership))
+        let type_name = obj
+            .get_opt::<JsString, _, _>(cx, "typeName")?
+            .map(|type_name| type_name.value(cx));
+        let converter = Converter::from_js_value(cx, obj, HandleKind::GObject)?;
+
+        Ok(GObjectType {
+            ownership,
+            type_name,
+            converter,
+        })
     }
 }
 
@@ -31,7 +57,13 @@ impl From<&GObjectType> for libffi::Type {
 
 impl ffi::FfiEncode for GObjectType {
     fn encode(&self, value: &value::Value, _optional: bool) -> anyhow::Result<ffi::FfiValue> {
-        let ptr = value.object_ptr("GObject")?;
+        if let Some(converter) = self.converter
+            && Converter::applies_to(value)
+        {
+            return converter.encode(value, self.ownership);
+        }
+
+        let ptr = value.typed_object_ptr(HandleKind::GObject, self.type_name.as_deref())?;
 
         if self.ownership.is_full() && !ptr.is_null() {
             unsafe { glib::gobject_ffi::g_object_ref(ptr as *mut _) };
@@ -47,6 +79,10 @@ impl ffi::FfiDecode for GObjectType {
             return Ok(value::Value::Null);
         };
 
+        if let Some(converter) = self.converter {
+            return converter.decode(object_ptr, self.ownership);
+        }
+
         let gobject_ptr = object_ptr as *mut glib::gobject_ffi::GObject;
 
         let object = if self.ownership.is_full() {
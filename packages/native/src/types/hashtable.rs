@@ -32,7 +32,7 @@ impl HashTableEntryEncoder {
         match ty {
             Type::String(_) => Some(Self::String),
             Type::Integer(_) => Some(Self::Integer),
-            Type::Boolean => Some(Self::Boolean),
+            Type::Boolean | Type::GBoolean => Some(Self::Boolean),
             Type::Float(_) => Some(Self::Float),
             Type::GObject(_) | Type::Boxed(_) | Type::Struct(_) | Type::Fundamental(_) => {
                 Some(Self::NativeHandle)
@@ -108,9 +108,9 @@ impl HashTableEntryEncoder {
             },
             Self::NativeHandle => match val {
                 value::Value::Object(handle) => {
-                    let ptr = handle.get_ptr().ok_or_else(|| {
-                        anyhow::anyhow!("Native object in GHashTable has been garbage collected")
-                    })?;
+                    let ptr = handle
+                        .get_ptr()
+                        .ok_or_else(|| handle.unavailable_error("Native object in GHashTable"))?;
                     Ok((ptr, HashTableStorage::NativeHandles))
                 }
                 value::Value::Null | value::Value::Undefined => {
@@ -126,9 +126,9 @@ impl HashTableEntryEncoder {
                 let ptr_array = unsafe { glib::ffi::g_ptr_array_new() };
                 for item in items {
                     let item_ptr = match item {
-                        value::Value::Object(handle) => handle.get_ptr().ok_or_else(|| {
-                            anyhow::anyhow!("Native object in GPtrArray has been garbage collected")
-                        })?,
+                        value::Value::Object(handle) => handle
+                            .get_ptr()
+                            .ok_or_else(|| handle.unavailable_error("Native object in GPtrArray"))?,
                         value::Value::Null | value::Value::Undefined => std::ptr::null_mut(),
                         _ => bail!("Expected Object in GPtrArray, got {:?}", item),
                     };
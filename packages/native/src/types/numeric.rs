@@ -1,7 +1,10 @@
+use std::rc::Rc;
+
 use anyhow::bail;
 use libffi::middle as libffi;
 use neon::prelude::*;
 
+use crate::enums::EnumInfo;
 use crate::{ffi, value};
 
 mod sealed {
@@ -390,6 +393,79 @@ impl IntegerKind {
         (self.dispatch().to_ffi_value)(value)
     }
 
+    /// Reads a bitfield from the storage unit at `ptr`, sign-extending it for
+    /// signed kinds.
+    pub fn read_bits(self, ptr: *const u8, bits: BitField) -> anyhow::Result<f64> {
+        let mask = bits.mask(self)?;
+        let raw = (self.read_unit(ptr) >> bits.offset) & mask;
+
+        if self.is_unsigned() {
+            return Ok(raw as f64);
+        }
+
+        let shift = 64 - bits.width;
+        Ok(((raw << shift) as i64 >> shift) as f64)
+    }
+
+    /// Writes a bitfield into the storage unit at `ptr`, leaving the other bits
+    /// of the unit untouched.
+    pub fn write_bits(self, ptr: *mut u8, bits: BitField, value: f64) -> anyhow::Result<()> {
+        let mask = bits.mask(self)?;
+
+        let (min, max) = if self.is_unsigned() {
+            (0.0, mask as f64)
+        } else {
+            let half = (1u64 << (bits.width - 1)) as f64;
+            (-half, half - 1.0)
+        };
+
+        if value.fract() != 0.0 || value < min || value > max {
+            bail!(
+                "Value {value} does not fit in a {}-bit {} bitfield",
+                bits.width,
+                if self.is_unsigned() {
+                    "unsigned"
+                } else {
+                    "signed"
+                }
+            );
+        }
+
+        let raw = if value < 0.0 {
+            value as i64 as u64
+        } else {
+            value as u64
+        };
+
+        let unit = self.read_unit(ptr) & !(mask << bits.offset);
+        self.write_unit(ptr, unit | ((raw & mask) << bits.offset));
+        Ok(())
+    }
+
+    fn read_unit(self, ptr: *const u8) -> u64 {
+        // SAFETY: The caller guarantees `byte_size` readable bytes at `ptr`.
+        unsafe {
+            match self.byte_size() {
+                1 => u64::from(ptr.read()),
+                2 => u64::from(ptr.cast::<u16>().read_unaligned()),
+                4 => u64::from(ptr.cast::<u32>().read_unaligned()),
+                _ => ptr.cast::<u64>().read_unaligned(),
+            }
+        }
+    }
+
+    fn write_unit(self, ptr: *mut u8, unit: u64) {
+        // SAFETY: The caller guarantees `byte_size` writable bytes at `ptr`.
+        unsafe {
+            match self.byte_size() {
+                1 => ptr.write(unit as u8),
+                2 => ptr.cast::<u16>().write_unaligned(unit as u16),
+                4 => ptr.cast::<u32>().write_unaligned(unit as u32),
+                _ => ptr.cast::<u64>().write_unaligned(unit),
+            }
+        }
+    }
+
     pub fn read_slice(self, ptr: *const u8, length: usize) -> Vec<f64> {
         (self.dispatch().read_slice)(ptr, length)
     }
@@ -430,7 +506,7 @@ impl ffi::FfiEncode for IntegerKind {
             value::Value::Number(n) => *n,
             value::Value::Object(handle) => handle
                 .get_ptr_as_usize()
-                .ok_or_else(|| anyhow::anyhow!("Object has been garbage collected"))?
+                .ok_or_else(|| handle.unavailable_error("Object"))?
                 as f64,
             value::Value::Null | value::Value::Undefined if optional => 0.0,
             _ => bail!("Expected a Number for integer type, got {:?}", value),
@@ -446,11 +522,41 @@ impl ffi::FfiDecode for IntegerKind {
     }
 }
 
+/// A C bitfield: `width` bits starting `offset` bits above the least
+/// significant bit of the integer storage unit that contains it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BitField {
+    pub offset: u32,
+    pub width: u32,
+}
+
+impl BitField {
+    /// The mask of `width` low bits, after checking that the bitfield fits in
+    /// a storage unit of the given kind.
+    fn mask(self, kind: IntegerKind) -> anyhow::Result<u64> {
+        let unit_bits = kind.byte_size() as u32 * 8;
+
+        if self.width == 0 || self.offset.saturating_add(self.width) > unit_bits {
+            bail!(
+                "Bitfield of {} bits at bit {} does not fit in a {unit_bits}-bit integer",
+                self.width,
+                self.offset
+            );
+        }
+
+        Ok(u64::MAX >> (64 - self.width))
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct IntegerType {
     pub kind: IntegerKind,
     pub library: Option<String>,
     pub get_type_fn: Option<String>,
+    /// Set when the integer is a bitfield within its storage unit.
+    pub bit_field: Option<BitField>,
+    /// Exchange enum and flags values by nickname; see [`crate::enums`].
+    pub symbolic: bool,
 }
 
 impl IntegerType {
@@ -465,16 +571,70 @@ impl IntegerType {
             .get_opt::<JsString, _, _>(cx, "getTypeFn")?
             .map(|h| h.value(cx));
 
+        let bit_offset: Option<Handle<JsNumber>> = obj.get_opt(cx, "bitOffset")?;
+        let bit_width: Option<Handle<JsNumber>> = obj.get_opt(cx, "bitWidth")?;
+
+        let bit_field = match (bit_offset, bit_width) {
+            (None, None) => None,
+            (offset, Some(width)) => Some(BitField {
+                offset: offset.map_or(0.0, |offset| offset.value(cx)) as u32,
+                width: width.value(cx) as u32,
+            }),
+            (Some(_), None) => {
+                return cx.throw_type_error("'bitOffset' requires 'bitWidth' for integer types");
+            }
+        };
+
+        let symbolic: Option<Handle<JsBoolean>> = obj.get_opt(cx, "symbolic")?;
+        let symbolic = symbolic.is_some_and(|symbolic| symbolic.value(cx));
+
+        if symbolic && (library.is_none() || get_type_fn.is_none()) {
+            return cx.throw_type_error(
+                "'symbolic' requires 'library' and 'getTypeFn' for integer types",
+            );
+        }
+
         Ok(IntegerType {
             kind,
             library,
             get_type_fn,
+            bit_field,
+            symbolic,
         })
     }
 
     pub fn is_enum_or_flags(&self) -> bool {
         self.library.is_some() && self.get_type_fn.is_some()
     }
+
+    fn enum_info(&self) -> anyhow::Result<Rc<EnumInfo>> {
+        match (&self.library, &self.get_type_fn) {
+            (Some(library), Some(get_type_fn)) => EnumInfo::cached(library, get_type_fn),
+            _ => bail!("Symbolic integer types require a library and get_type_fn"),
+        }
+    }
+
+    /// The number `value` stands for, resolving enum and flags names when
+    /// the type is symbolic.
+    pub fn to_number(&self, value: &value::Value) -> anyhow::Result<f64> {
+        match value {
+            value::Value::Number(n) => Ok(*n),
+            value::Value::String(_) | value::Value::Array(_) if self.symbolic => {
+                self.enum_info()?.to_number(value)
+            }
+            _ => bail!("Expected a Number for integer type, got {:?}", value),
+        }
+    }
+
+    /// Converts a native number to JavaScript, as enum or flags names when the
+    /// type is symbolic.
+    pub fn number_to_value(&self, number: f64) -> anyhow::Result<value::Value> {
+        if self.symbolic {
+            return Ok(self.enum_info()?.to_symbolic(number));
+        }
+
+        Ok(value::Value::Number(number))
+    }
 }
 
 impl From<IntegerKind> for IntegerType {
@@ -483,6 +643,8 @@ impl From<IntegerKind> for IntegerType {
             kind,
             library: None,
             get_type_fn: None,
+            bit_field: None,
+            symbolic: false,
         }
     }
 }
@@ -495,13 +657,18 @@ impl From<&IntegerType> for libffi::Type {
 
 impl ffi::FfiEncode for IntegerType {
     fn encode(&self, value: &value::Value, optional: bool) -> anyhow::Result<ffi::FfiValue> {
-        self.kind.encode(value, optional)
+        match value {
+            value::Value::String(_) | value::Value::Array(_) if self.symbolic => {
+                Ok(self.kind.to_ffi_value(self.to_number(value)?))
+            }
+            _ => self.kind.encode(value, optional),
+        }
     }
 }
 
 impl ffi::FfiDecode for IntegerType {
     fn decode(&self, ffi_value: &ffi::FfiValue) -> anyhow::Result<value::Value> {
-        self.kind.decode(ffi_value)
+        self.number_to_value(ffi_value.to_number()?)
     }
 }
 
@@ -555,6 +722,13 @@ impl FloatKind {
         })
     }
 
+    pub fn byte_size(self) -> usize {
+        match self {
+            Self::F32 => 4,
+            Self::F64 => 8,
+        }
+    }
+
     fn dispatch(&self) -> &'static FloatDispatch {
         match self {
             Self::F32 => &F32_DISPATCH,
@@ -1,25 +1,48 @@
 use std::ffi::{CStr, c_char, c_void};
 
 use anyhow::bail;
-use gtk4::glib::{self, translate::FromGlibPtrFull as _, translate::FromGlibPtrNone as _};
+use gtk4::glib::{
+    self,
+    translate::{FromGlibPtrFull as _, FromGlibPtrNone as _, IntoGlib as _},
+};
 use libffi::middle as libffi;
 use neon::object::Object as _;
 use neon::prelude::*;
 
 use crate::arg::Arg;
+use crate::diagnostics::HandleKind;
 use crate::ffi::{FfiStorage, FfiStorageKind};
 use crate::managed::{Boxed, Fundamental, NativeValue};
-use crate::{ffi, types::Type, value};
+use crate::types::{BoxedType, GObjectType, Type};
+use crate::{ffi, value};
 
+/// A pointer to a value the callee reads and writes back (`Ref` in JS).
+///
+/// Object-like inner types are passed as a pointer slot initialized from the
+/// Ref's current value, so the same type models out and inout parameters.
+/// With `full` ownership the callee receives its own reference or copy of the
+/// incoming value, and the value it leaves in the slot is taken over.
 #[derive(Debug, Clone)]
 pub struct RefType {
     pub inner_type: Box<Type>,
+    /// Size of the struct the call allocates for a GIR `caller-allocates`
+    /// out-parameter. The callee fills zeroed storage passed by pointer, and
+    /// the Ref receives the struct as an owned boxed handle.
+    pub caller_allocates: Option<usize>,
 }
 
 impl RefType {
     pub fn new(inner_type: Type) -> Self {
         RefType {
             inner_type: Box::new(inner_type),
+            caller_allocates: None,
+        }
+    }
+
+    pub fn caller_allocates(inner_type: Type, size: usize) -> Self {
+        RefType {
+            inner_type: Box::new(inner_type),
+            caller_allocates: Some(size),
         }
     }
 
@@ -28,7 +51,177 @@ impl RefType {
         let inner_type_value: Handle<'_, JsValue> = obj.prop(cx, "innerType").get()?;
         let inner_type = Type::from_js_value(cx, inner_type_value)?;
 
-        Ok(Self::new(inner_type))
+        let caller_allocates: Option<Handle<JsBoolean>> = obj.get_opt(cx, "callerAllocates")?;
+        if !caller_allocates.is_some_and(|flag| flag.value(cx)) {
+            return Ok(Self::new(inner_type));
+        }
+
+        let struct_size = match &inner_type {
+            Type::Boxed(_) => None,
+            Type::Struct(struct_type) => struct_type.size,
+            _ => {
+                return cx.throw_type_error(
+                    "'callerAllocates' is only supported for boxed and struct refs",
+                );
+            }
+        };
+
+        let size: Option<Handle<JsNumber>> = obj.get_opt(cx, "size")?;
+        let size = match size.map(|size| size.value(cx) as usize).or(struct_size) {
+            Some(size) if size > 0 => size,
+            _ => return cx.throw_type_error("'callerAllocates' refs require a positive 'size'"),
+        };
+
+        Ok(Self::caller_allocates(inner_type, size))
+    }
+
+    /// Zeroed storage for the callee to fill, aligned for any struct member.
+    fn encode_caller_allocated(
+        &self,
+        ref_val: &value::Ref,
+        size: usize,
+    ) -> anyhow::Result<ffi::FfiValue> {
+        let mut buffer = vec![0u64; size.div_ceil(size_of::<u64>())];
+
+        match &*ref_val.value {
+            value::Value::Null | value::Value::Undefined => {}
+            value::Value::Object(handle) => {
+                let source = handle.require_memory()?.field_ptr(0, size)?;
+                // SAFETY: The source was bounds-checked for `size` bytes, and
+                // the buffer holds at least as many.
+                unsafe {
+                    std::ptr::copy_nonoverlapping(
+                        source.cast_const(),
+                        buffer.as_mut_ptr().cast::<u8>(),
+                        size,
+                    );
+                }
+            }
+            _ => bail!(
+                "Expected an Object or Null for a caller-allocated Ref, got {:?}",
+                ref_val.value
+            ),
+        }
+
+        Ok(ffi::FfiValue::Storage(buffer.into()))
+    }
+
+    /// The pointer an inout Ref passes in its slot for the value it holds.
+    /// When the inner type transfers ownership, the callee receives its own
+    /// reference or copy, since the JS handle keeps the original.
+    fn encode_inout_ptr(&self, value: &value::Value) -> anyhow::Result<*mut c_void> {
+        match &*self.inner_type {
+            Type::GObject(gobject_type) => {
+                let ptr = value
+                    .typed_object_ptr(HandleKind::GObject, gobject_type.type_name.as_deref())?;
+
+                if gobject_type.ownership.is_full() && !ptr.is_null() {
+                    unsafe { glib::gobject_ffi::g_object_ref(ptr.cast()) };
+                }
+
+                Ok(ptr)
+            }
+            Type::Boxed(boxed_type) => {
+                let gtype = boxed_type.gtype();
+                let ptr =
+                    value.typed_object_ptr(HandleKind::Boxed, gtype.map(|gtype| gtype.name()))?;
+
+                if boxed_type.ownership.is_borrowed() || ptr.is_null() {
+                    return Ok(ptr);
+                }
+
+                let Some(gtype) = gtype else {
+                    bail!(
+                        "Cannot transfer boxed {} to an inout Ref without a GType to copy it",
+                        boxed_type.type_name
+                    );
+                };
+
+                Ok(unsafe { glib::gobject_ffi::g_boxed_copy(gtype.into_glib(), ptr) })
+            }
+            Type::Fundamental(fundamental_type) => {
+                let ptr = value.typed_object_ptr(
+                    HandleKind::Fundamental,
+                    fundamental_type.type_name.as_deref(),
+                )?;
+
+                if fundamental_type.ownership.is_borrowed() || ptr.is_null() {
+                    return Ok(ptr);
+                }
+
+                match fundamental_type.lookup_fns()? {
+                    // Copy-based fundamentals return a new pointer from their ref function.
+                    (Some(ref_fn), _) => Ok(unsafe { ref_fn(ptr) }),
+                    (None, _) => Ok(ptr),
+                }
+            }
+            Type::Struct(struct_type) => {
+                let ptr = value.object_ptr(&struct_type.type_name)?;
+
+                if struct_type.ownership.is_borrowed() || ptr.is_null() {
+                    return Ok(ptr);
+                }
+
+                let (value::Value::Object(handle), Some(size)) = (value, struct_type.size) else {
+                    bail!(
+                        "Cannot transfer struct {} to an inout Ref without a size to copy it",
+                        struct_type.type_name
+                    );
+                };
+
+                let source = handle.require_memory()?.field_ptr(0, size)?;
+
+                // SAFETY: The source was bounds-checked for `size` bytes; the
+                // callee frees the copy with `g_free`.
+                unsafe {
+                    let copy = glib::ffi::g_malloc(size);
+                    std::ptr::copy_nonoverlapping(source.cast_const(), copy.cast::<u8>(), size);
+                    Ok(copy)
+                }
+            }
+            _ => bail!("Unsupported inout Ref inner type: {:?}", self.inner_type),
+        }
+    }
+
+    /// Moves the struct the callee filled into a handle. A boxed inner type
+    /// with a GType is copied with `g_boxed_copy`, so its own free function
+    /// later releases memory it allocated; plain structs are copied into a
+    /// `g_malloc` allocation freed with `g_free`.
+    fn decode_caller_allocated(
+        &self,
+        storage: &FfiStorage,
+        size: usize,
+    ) -> anyhow::Result<value::Value> {
+        let gtype = match &*self.inner_type {
+            Type::Boxed(boxed_type) => boxed_type.gtype(),
+            _ => None,
+        };
+
+        if let Some(gtype) = gtype {
+            // SAFETY: The callee initialized a value of this boxed type in
+            // the storage encode allocated.
+            let ptr = unsafe {
+                glib::gobject_ffi::g_boxed_copy(gtype.into_glib(), storage.ptr().cast_const())
+            };
+            if ptr.is_null() {
+                bail!("Failed to copy caller-allocated {gtype}");
+            }
+
+            let boxed = Boxed::from_glib_full(Some(gtype), ptr).with_size(Some(size));
+            return Ok(value::Value::Object(NativeValue::Boxed(boxed).into()));
+        }
+
+        // SAFETY: g_malloc0 is a safe GLib memory allocation function
+        let ptr = unsafe { glib::ffi::g_malloc0(size) };
+        if ptr.is_null() {
+            bail!("Failed to allocate {size} bytes for a caller-allocated Ref");
+        }
+
+        // SAFETY: encode allocated at least `size` bytes behind storage.ptr().
+        unsafe { std::ptr::copy_nonoverlapping(storage.ptr().cast::<u8>(), ptr.cast(), size) };
+
+        let boxed = Boxed::from_glib_full(None, ptr).with_size(Some(size));
+        Ok(value::Value::Object(NativeValue::Boxed(boxed).into()))
     }
 }
 
@@ -48,22 +241,27 @@ impl ffi::FfiEncode for RefType {
             _ => bail!("Expected a Ref for ref type, got {:?}", val),
         };
 
+        if let Some(size) = self.caller_allocates {
+            return self.encode_caller_allocated(ref_val, size);
+        }
+
         match &*self.inner_type {
             Type::Boxed(_) | Type::Struct(_) | Type::GObject(_) | Type::Fundamental(_) => {
-                match &*ref_val.value {
-                    value::Value::Null | value::Value::Undefined => {
-                        let ptr_storage: Box<*mut c_void> = Box::new(std::ptr::null_mut());
-                        let ptr = ptr_storage.as_ref() as *const *mut c_void as *mut c_void;
-                        Ok(ffi::FfiValue::Storage(FfiStorage::new(
-                            ptr,
-                            FfiStorageKind::PtrStorage(ptr_storage),
-                        )))
-                    }
+                let initial = match &*ref_val.value {
+                    value::Value::Null | value::Value::Undefined => std::ptr::null_mut(),
+                    value @ value::Value::Object(_) => self.encode_inout_ptr(value)?,
                     _ => bail!(
-                        "Expected Null for Ref<Boxed/Struct/GObject/Fundamental>, got {:?}",
+                        "Expected an Object or Null for Ref<Boxed/Struct/GObject/Fundamental>, got {:?}",
                         ref_val.value
                     ),
-                }
+                };
+
+                let ptr_storage: Box<*mut c_void> = Box::new(initial);
+                let ptr = ptr_storage.as_ref() as *const *mut c_void as *mut c_void;
+                Ok(ffi::FfiValue::Storage(FfiStorage::new(
+                    ptr,
+                    FfiStorageKind::PtrStorage(ptr_storage),
+                )))
             }
             Type::Array(array_type) => match &*ref_val.value {
                 value::Value::Array(arr) if !arr.is_empty() => {
@@ -87,11 +285,21 @@ impl ffi::FfiEncode for RefType {
                 ),
             },
             Type::String(string_type) => {
-                let (buffer_size, initial_content) = match (&string_type.length, &*ref_val.value) {
-                    (Some(len), value::Value::String(s)) => (*len, Some(s.as_bytes())),
-                    (Some(len), value::Value::Null | value::Value::Undefined) => (*len, None),
-                    (None, value::Value::String(s)) => (s.len() + 1, Some(s.as_bytes())),
-                    (None, value::Value::Null | value::Value::Undefined) => {
+                let initial_content = match &*ref_val.value {
+                    value::Value::Null | value::Value::Undefined => None,
+                    value @ (value::Value::String(_) | value::Value::Bytes(_)) => {
+                        Some(string_type.encode_cstring(value)?)
+                    }
+                    _ => bail!(
+                        "Expected a String, Null, or length for Ref<String>, got {:?}",
+                        ref_val.value
+                    ),
+                };
+
+                let buffer_size = match (string_type.length, &initial_content) {
+                    (Some(len), _) => len,
+                    (None, Some(content)) => content.as_bytes_with_nul().len(),
+                    (None, None) => {
                         let ptr_storage: Box<*mut c_void> = Box::new(std::ptr::null_mut());
                         let ptr = ptr_storage.as_ref() as *const *mut c_void as *mut c_void;
                         return Ok(ffi::FfiValue::Storage(FfiStorage::new(
@@ -99,15 +307,12 @@ impl ffi::FfiEncode for RefType {
                             FfiStorageKind::PtrStorage(ptr_storage),
                         )));
                     }
-                    _ => bail!(
-                        "Expected a String, Null, or length for Ref<String>, got {:?}",
-                        ref_val.value
-                    ),
                 };
 
                 let mut buffer: Vec<u8> = vec![0u8; buffer_size];
 
                 if let Some(content) = initial_content {
+                    let content = content.as_bytes();
                     let copy_len = content.len().min(buffer_size.saturating_sub(1));
                     buffer[..copy_len].copy_from_slice(&content[..copy_len]);
                 }
@@ -143,7 +348,25 @@ impl ffi::FfiDecode for RefType {
             ),
         };
 
+        if let Some(size) = self.caller_allocates {
+            return self.decode_caller_allocated(storage, size);
+        }
+
         match &*self.inner_type {
+            Type::GObject(GObjectType {
+                ownership,
+                converter: Some(converter),
+                ..
+            })
+            | Type::Boxed(BoxedType {
+                ownership,
+                converter: Some(converter),
+                ..
+            }) => {
+                // SAFETY: storage.ptr() points to a pointer slot allocated by encode
+                let actual_ptr = unsafe { *(storage.ptr() as *const *mut c_void) };
+                converter.decode(actual_ptr, *ownership)
+            }
             Type::GObject(gobject_type) => {
                 // SAFETY: storage.ptr() points to a pointer-to-GObject allocated by encode
                 let actual_ptr = unsafe { *(storage.ptr() as *const *mut c_void) };
@@ -192,9 +415,28 @@ impl ffi::FfiDecode for RefType {
                     NativeValue::Fundamental(fundamental).into(),
                 ))
             }
+            Type::Struct(struct_type) => {
+                // SAFETY: storage.ptr() points to a pointer-to-struct allocated by encode
+                let actual_ptr = unsafe { *(storage.ptr() as *const *mut c_void) };
+                if actual_ptr.is_null() {
+                    return Ok(value::Value::Null);
+                }
+
+                let boxed = if struct_type.ownership.is_full() {
+                    Boxed::from_glib_full(None, actual_ptr).with_size(struct_type.size)
+                } else {
+                    Boxed::from_glib_none_with_size(
+                        None,
+                        actual_ptr,
+                        struct_type.size,
+                        Some(&struct_type.type_name),
+                    )?
+                };
+                Ok(value::Value::Object(NativeValue::Boxed(boxed).into()))
+            }
             Type::Integer(int_type) => {
                 let number = int_type.kind.read_ptr(storage.ptr() as *const u8);
-                Ok(value::Value::Number(number))
+                int_type.number_to_value(number)
             }
             Type::Float(float_kind) => {
                 let number = float_kind.read_ptr(storage.ptr() as *const u8);
@@ -237,7 +479,7 @@ impl ffi::FfiDecode for RefType {
             }
 
             let ptr_ffi_value = ffi::FfiValue::Ptr(actual_ptr);
-            let result = array_type.decode_with_context(&ptr_ffi_value, ffi_args, args)?;
+            let result = array_type.decode_with_context(&ptr_ffi_value, ffi_args, args);
 
             if matches!(storage.kind(), FfiStorageKind::PtrStorage(_))
                 && array_type.ownership.is_full()
@@ -245,7 +487,7 @@ impl ffi::FfiDecode for RefType {
                 unsafe { glib::ffi::g_free(actual_ptr) };
             }
 
-            return Ok(result);
+            return result;
         }
 
         self.decode(ffi_value)
@@ -266,8 +508,7 @@ impl RefType {
             FfiStorageKind::Buffer(_) => {
                 // SAFETY: storage.ptr() points to a null-terminated C string in our buffer
                 let c_str = unsafe { CStr::from_ptr(storage.ptr() as *const c_char) };
-                let string = c_str.to_str()?.to_string();
-                Ok(value::Value::String(string))
+                string_type.decode_cstr(c_str)
             }
             _ => {
                 // SAFETY: storage.ptr() points to a pointer-to-string
@@ -275,15 +516,15 @@ impl RefType {
                 if str_ptr.is_null() {
                     return Ok(value::Value::Null);
                 }
-                let c_str = unsafe { CStr::from_ptr(str_ptr) };
-                let string = c_str.to_str()?.to_string();
+                // SAFETY: str_ptr is a null-terminated C string written by the callee
+                let value = unsafe { string_type.decode_ptr(str_ptr) };
 
                 if string_type.ownership.is_full() {
                     // SAFETY: str_ptr was allocated by GLib and we have owned ownership
                     unsafe { glib::ffi::g_free(str_ptr as *mut c_void) };
                 }
 
-                Ok(value::Value::String(string))
+                value
             }
         }
     }
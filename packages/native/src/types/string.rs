@@ -1,17 +1,47 @@
 use std::ffi::{CStr, CString, c_char, c_void};
 
-use anyhow::bail;
-use gtk4::glib;
+use anyhow::{Context as _, bail};
+use gtk4::glib::{self, translate::FromGlibPtrFull as _};
 use libffi::middle as libffi;
 use neon::prelude::*;
 
 use super::Ownership;
 use crate::{ffi, value};
 
+/// How the bytes of a C string map to and from JavaScript values.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StringEncoding {
+    /// UTF-8; invalid data is an error.
+    #[default]
+    Utf8,
+    /// The GLib filename encoding, converted with `g_filename_to_utf8` and
+    /// `g_filename_from_utf8`.
+    Filename,
+    /// Raw bytes, exchanged as a `Uint8Array`.
+    Bytes,
+    /// UTF-8, with invalid sequences replaced by U+FFFD.
+    Lossy,
+}
+
+impl StringEncoding {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "utf8" => Some(Self::Utf8),
+            "filename" => Some(Self::Filename),
+            "bytes" => Some(Self::Bytes),
+            "lossy" => Some(Self::Lossy),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct StringType {
     pub ownership: Ownership,
     pub length: Option<usize>,
+    /// The requested encoding. When unset, strings decode as strict UTF-8,
+    /// except where [`StringType::or_lossy`] applies.
+    pub encoding: Option<StringEncoding>,
 }
 
 impl StringType {
@@ -19,13 +49,14 @@ impl StringType {
         StringType {
             ownership,
             length: None,
+            encoding: None,
         }
     }
 
     pub fn with_length(ownership: Ownership, length: usize) -> Self {
         StringType {
-            ownership,
             length: Some(length),
+            ..Self::new(ownership)
         }
     }
 
@@ -39,10 +70,133 @@ impl StringType {
             .map(|n| n.value(cx) as usize)
             .ok();
 
-        Ok(StringType { ownership, length })
+        let encoding = match obj.get_opt::<JsString, _, _>(cx, "encoding")? {
+            Some(name) => {
+                let name = name.value(cx);
+                match StringEncoding::from_name(&name) {
+                    Some(encoding) => Some(encoding),
+                    None => return cx.throw_type_error(format!("Unknown string encoding: {name}")),
+                }
+            }
+            None => None,
+        };
+
+        Ok(StringType {
+            ownership,
+            length,
+            encoding,
+        })
+    }
+
+    /// Decodes lossily unless an encoding was requested. Callback arguments
+    /// and NULL-terminated string arrays have always tolerated invalid UTF-8,
+    /// so strict decoding there is opt-in.
+    #[must_use]
+    pub fn or_lossy(self) -> Self {
+        StringType {
+            encoding: Some(self.encoding.unwrap_or(StringEncoding::Lossy)),
+            ..self
+        }
+    }
+
+    /// Converts a C string to a JS string, or to bytes for the `bytes`
+    /// encoding.
+    pub fn decode_cstr(&self, c_str: &CStr) -> anyhow::Result<value::Value> {
+        match self.encoding.unwrap_or_default() {
+            StringEncoding::Utf8 => {
+                let string = c_str.to_str().context(
+                    "String is not valid UTF-8; use the 'lossy', 'filename' or 'bytes' encoding",
+                )?;
+                Ok(value::Value::String(string.to_string()))
+            }
+            StringEncoding::Lossy => Ok(value::Value::String(c_str.to_string_lossy().into_owned())),
+            StringEncoding::Bytes => Ok(value::Value::Bytes(c_str.to_bytes().to_vec())),
+            StringEncoding::Filename => filename_to_utf8(c_str).map(value::Value::String),
+        }
+    }
+
+    /// Decodes the string at `ptr`, or returns `Null` for a null pointer.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be null or point to a NUL-terminated string.
+    pub unsafe fn decode_ptr(&self, ptr: *const c_char) -> anyhow::Result<value::Value> {
+        if ptr.is_null() {
+            return Ok(value::Value::Null);
+        }
+
+        // SAFETY: The caller guarantees a NUL-terminated string.
+        self.decode_cstr(unsafe { CStr::from_ptr(ptr) })
+    }
+
+    /// Converts a JS string, or a `Uint8Array` for the `bytes` encoding, to
+    /// the C string to pass to native code.
+    pub fn encode_cstring(&self, value: &value::Value) -> anyhow::Result<CString> {
+        match (self.encoding.unwrap_or_default(), value) {
+            (StringEncoding::Filename, value::Value::String(s)) => filename_from_utf8(s),
+            (_, value::Value::String(s)) => Ok(CString::new(s.as_bytes())?),
+            (StringEncoding::Bytes, value::Value::Bytes(bytes)) => {
+                Ok(CString::new(bytes.as_slice())?)
+            }
+            _ => bail!("Expected a String for string type, got {:?}", value),
+        }
     }
 }
 
+fn filename_to_utf8(c_str: &CStr) -> anyhow::Result<String> {
+    let mut error = std::ptr::null_mut();
+
+    // SAFETY: `c_str` is NUL-terminated, and the result is freed below.
+    let utf8 = unsafe {
+        glib::ffi::g_filename_to_utf8(
+            c_str.as_ptr(),
+            -1,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            &mut error,
+        )
+    };
+
+    if utf8.is_null() {
+        // SAFETY: GLib sets `error` when the conversion fails.
+        let error = unsafe { glib::Error::from_glib_full(error) };
+        bail!("Failed to convert filename to UTF-8: {error}");
+    }
+
+    // SAFETY: GLib returns a valid NUL-terminated UTF-8 string.
+    let string = unsafe { CStr::from_ptr(utf8) }.to_str().map(str::to_string);
+    unsafe { glib::ffi::g_free(utf8.cast()) };
+
+    Ok(string?)
+}
+
+fn filename_from_utf8(string: &str) -> anyhow::Result<CString> {
+    let mut error = std::ptr::null_mut();
+
+    // SAFETY: The length bounds the input, and the result is freed below.
+    let filename = unsafe {
+        glib::ffi::g_filename_from_utf8(
+            string.as_ptr().cast(),
+            string.len() as isize,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            &mut error,
+        )
+    };
+
+    if filename.is_null() {
+        // SAFETY: GLib sets `error` when the conversion fails.
+        let error = unsafe { glib::Error::from_glib_full(error) };
+        bail!("Failed to convert {string:?} to the filename encoding: {error}");
+    }
+
+    // SAFETY: GLib returns a valid NUL-terminated string.
+    let c_string = unsafe { CStr::from_ptr(filename) }.to_owned();
+    unsafe { glib::ffi::g_free(filename.cast()) };
+
+    Ok(c_string)
+}
+
 impl From<&StringType> for libffi::Type {
     fn from(_: &StringType) -> Self {
         libffi::Type::pointer()
@@ -52,18 +206,17 @@ impl From<&StringType> for libffi::Type {
 impl ffi::FfiEncode for StringType {
     fn encode(&self, value: &value::Value, _optional: bool) -> anyhow::Result<ffi::FfiValue> {
         match value {
-            value::Value::String(s) => {
-                let cstring = CString::new(s.as_bytes())?;
+            value::Value::Null | value::Value::Undefined => {
+                Ok(ffi::FfiValue::Ptr(std::ptr::null_mut()))
+            }
+            value => {
+                let cstring = self.encode_cstring(value)?;
                 let ptr = cstring.as_ptr() as *mut c_void;
                 Ok(ffi::FfiValue::Storage(ffi::FfiStorage::new(
                     ptr,
                     ffi::FfiStorageKind::CString(cstring),
                 )))
             }
-            value::Value::Null | value::Value::Undefined => {
-                Ok(ffi::FfiValue::Ptr(std::ptr::null_mut()))
-            }
-            _ => bail!("Expected a String for string type, got {:?}", value),
         }
     }
 }
@@ -74,13 +227,12 @@ impl ffi::FfiDecode for StringType {
             return Ok(value::Value::Null);
         };
 
-        let c_str = unsafe { CStr::from_ptr(str_ptr as *const c_char) };
-        let string = c_str.to_str()?.to_string();
+        let value = unsafe { self.decode_ptr(str_ptr as *const c_char) };
 
         if self.ownership.is_full() {
             unsafe { glib::ffi::g_free(str_ptr) };
         }
 
-        Ok(value::Value::String(string))
+        value
     }
 }
@@ -6,7 +6,7 @@
 //! via the [`ffi`] module.
 //!
 //! The [`Value`] enum supports all types that can be passed through the FFI:
-//! - Primitives: numbers, strings, booleans
+//! - Primitives: numbers, strings, byte strings, booleans, dates, bigints
 //! - Objects: GObjects, boxed types, structs
 //! - Callbacks: JavaScript functions invocable from native code
 //! - Arrays and references
@@ -15,14 +15,21 @@ use std::ffi::c_void;
 use std::sync::Arc;
 
 use anyhow::bail;
+use gtk4::gio;
 use gtk4::glib::{
     self,
     prelude::{ObjectExt as _, ObjectType as _},
     translate::{FromGlibPtrNone as _, ToGlibPtr as _, ToGlibPtrMut as _},
     value::ToValue as _,
 };
-use neon::{handle::Root, object::Object as _, prelude::*};
+use neon::{
+    handle::Root,
+    object::Object as _,
+    prelude::*,
+    types::{JsBigInt, JsDate, JsUint8Array, buffer::TypedArray as _},
+};
 
+use crate::diagnostics::HandleKind;
 use crate::ffi::FfiDecode;
 use crate::managed::{Boxed, Fundamental, NativeHandle, NativeValue};
 use crate::types::*;
@@ -58,6 +65,9 @@ impl Callback {
     }
 }
 
+/// A `Ref` object and the value to store in it once a call returns.
+pub type RefUpdate = (Arc<Root<JsObject>>, Value);
+
 #[derive(Debug, Clone)]
 pub struct Ref {
     pub value: Box<Value>,
@@ -89,12 +99,19 @@ impl Ref {
 pub enum Value {
     Number(f64),
     String(String),
+    /// A `Uint8Array`, for strings with the `bytes` encoding.
+    Bytes(Vec<u8>),
+    /// A `Date`, as milliseconds since the Unix epoch.
+    Date(f64),
+    /// A `bigint` that fits in 64 bits.
+    BigInt(i64),
     Boolean(bool),
     Object(NativeHandle),
     Null,
     Undefined,
     Array(Vec<Value>),
     Callback(Callback),
+    Cancellable(gio::Cancellable),
     Ref(Ref),
 }
 
@@ -103,12 +120,31 @@ impl Value {
         match self {
             Value::Object(handle) => handle
                 .get_ptr()
-                .ok_or_else(|| anyhow::anyhow!("{} has been garbage collected", type_name)),
+                .ok_or_else(|| handle.unavailable_error(type_name)),
             Value::Null | Value::Undefined => Ok(std::ptr::null_mut()),
             _ => anyhow::bail!("Expected an Object for {} type, got {:?}", type_name, self),
         }
     }
 
+    /// Like [`Value::object_ptr`], but checks that the handle holds a value of
+    /// the expected kind and, when `type_name` is given, an instance of that
+    /// GType. Fails with a [`crate::managed::HandleTypeError`] on mismatch.
+    pub fn typed_object_ptr(
+        &self,
+        kind: HandleKind,
+        type_name: Option<&str>,
+    ) -> anyhow::Result<*mut c_void> {
+        match self {
+            Value::Object(handle) => handle.require_typed_ptr(kind, type_name),
+            Value::Null | Value::Undefined => Ok(std::ptr::null_mut()),
+            _ => anyhow::bail!(
+                "Expected an Object for {} type, got {:?}",
+                type_name.unwrap_or(kind.as_str()),
+                self
+            ),
+        }
+    }
+
     pub fn from_ffi_value(ffi_value: &ffi::FfiValue, ty: &Type) -> anyhow::Result<Self> {
         ty.decode(ffi_value)
     }
@@ -125,7 +161,7 @@ impl Value {
     pub fn into_glib_value_with_default(self, return_type: Option<&Type>) -> Option<glib::Value> {
         match &self {
             Value::Undefined => match return_type {
-                Some(Type::Boolean) => Some(false.into()),
+                Some(Type::Boolean | Type::GBoolean) => Some(false.into()),
                 Some(Type::Integer(int_type)) => {
                     if int_type.is_enum_or_flags() {
                         Self::number_to_enum_or_flags_value(0.0, int_type).ok()
@@ -149,6 +185,14 @@ impl Value {
     }
 
     pub fn to_glib_value_typed(self, expected_type: Option<&Type>) -> anyhow::Result<glib::Value> {
+        if let Some(Type::Integer(int_type)) = expected_type
+            && int_type.symbolic
+            && matches!(self, Value::String(_) | Value::Array(_))
+        {
+            let n = int_type.to_number(&self)?;
+            return Self::number_to_enum_or_flags_value(n, int_type);
+        }
+
         match self {
             Value::Number(n) => {
                 if let Some(Type::Integer(int_type)) = expected_type {
@@ -260,6 +304,25 @@ impl Value {
             return Ok(Value::Object(*handle.as_inner()));
         }
 
+        if let Ok(bytes) = value.downcast::<JsUint8Array, _>(cx) {
+            return Ok(Value::Bytes(bytes.as_slice(cx).to_vec()));
+        }
+
+        if let Ok(date) = value.downcast::<JsDate, _>(cx) {
+            let ms = date.value(cx);
+            if !ms.is_finite() {
+                return cx.throw_type_error("Invalid Date cannot be converted to a native value");
+            }
+            return Ok(Value::Date(ms));
+        }
+
+        if let Ok(bigint) = value.downcast::<JsBigInt, _>(cx) {
+            let Ok(n) = bigint.to_i64(cx) else {
+                return cx.throw_range_error("BigInt does not fit in a 64-bit integer");
+            };
+            return Ok(Value::BigInt(n));
+        }
+
         if let Ok(callback) = value.downcast::<JsFunction, _>(cx) {
             return Ok(Value::Callback(Callback::from_js_value(
                 cx,
@@ -288,8 +351,11 @@ impl Value {
         match self {
             Value::Number(n) => Ok(cx.number(*n).upcast()),
             Value::String(s) => Ok(cx.string(s).upcast()),
+            Value::Bytes(bytes) => Ok(JsUint8Array::from_slice(cx, bytes)?.upcast()),
+            Value::Date(ms) => Ok(cx.date(*ms).or_throw(cx)?.upcast()),
+            Value::BigInt(n) => Ok(JsBigInt::from_i64(cx, *n).upcast()),
             Value::Boolean(b) => Ok(cx.boolean(*b).upcast()),
-            Value::Object(handle) => Ok(cx.boxed(*handle).upcast()),
+            Value::Object(handle) => Ok(handle.into_js(cx).upcast()),
             Value::Array(arr) => {
                 let js_array = cx.empty_array();
 
@@ -370,7 +436,7 @@ impl Value {
                         .map_err(|e| anyhow::anyhow!("Failed to get u64 from GValue: {}", e))?
                         as f64,
                 };
-                Ok(Value::Number(number))
+                int_type.number_to_value(number)
             }
             Type::Float(float_kind) => {
                 let number = match float_kind {
@@ -384,18 +450,43 @@ impl Value {
                 };
                 Ok(Value::Number(number))
             }
-            Type::String(_) => {
-                let string: String = gvalue
-                    .get()
-                    .map_err(|e| anyhow::anyhow!("Failed to get String from GValue: {}", e))?;
-                Ok(Value::String(string))
+            Type::String(string_type) => {
+                if !gvalue.type_().is_a(glib::types::Type::STRING) {
+                    bail!("Failed to get String from GValue: holds {}", gvalue.type_());
+                }
+
+                // SAFETY: gvalue holds a string type checked above
+                let str_ptr =
+                    unsafe { glib::gobject_ffi::g_value_get_string(gvalue.to_glib_none().0) };
+                // SAFETY: the GValue's string is null or NUL-terminated
+                unsafe { string_type.or_lossy().decode_ptr(str_ptr) }
             }
-            Type::Boolean => {
+            Type::Boolean | Type::GBoolean => {
                 let boolean: bool = gvalue
                     .get()
                     .map_err(|e| anyhow::anyhow!("Failed to get bool from GValue: {}", e))?;
                 Ok(Value::Boolean(boolean))
             }
+            Type::GObject(GObjectType {
+                converter: Some(converter),
+                ..
+            }) => {
+                // SAFETY: gvalue contains a valid GObject type
+                let ptr = unsafe {
+                    glib::gobject_ffi::g_value_get_object(gvalue.to_glib_none().0 as *const _)
+                };
+                converter.decode(ptr.cast(), Ownership::Borrowed)
+            }
+            Type::Boxed(BoxedType {
+                converter: Some(converter),
+                ..
+            }) => {
+                // SAFETY: gvalue contains a valid boxed type
+                let ptr = unsafe {
+                    glib::gobject_ffi::g_value_get_boxed(gvalue.to_glib_none().0 as *const _)
+                };
+                converter.decode(ptr, Ownership::Borrowed)
+            }
             Type::GObject(_) => Self::from_glib_gobject(gvalue),
             Type::Boxed(boxed_type) => {
                 let gvalue_type = gvalue.type_();
@@ -461,7 +552,11 @@ impl Value {
                 };
                 Ok(Value::Object(NativeValue::Fundamental(fundamental).into()))
             }
-            Type::Array(_) | Type::HashTable(_) | Type::Ref(_) | Type::Callback(_) => {
+            Type::Array(_)
+            | Type::HashTable(_)
+            | Type::Ref(_)
+            | Type::Callback(_)
+            | Type::Cancellable => {
                 bail!(
                     "Type {:?} should not appear in glib value conversion - this indicates a bug in the type mapping",
                     ty
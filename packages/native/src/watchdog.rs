@@ -0,0 +1,325 @@
+//! Deadlock watchdog for the JS ⇄ GTK handoff.
+//!
+//! The JS thread blocks in `wait_for_gtk_result` while the GTK thread runs a
+//! task, and the GTK thread blocks in `invoke_and_wait` while JS runs a
+//! callback. If either side stops pumping the other's queue, both can wait
+//! forever. The watchdog records what each thread is doing as a stack of
+//! [`Frame`]s and, when both threads have been blocked for longer than the
+//! configured timeout, prints the chain of pending calls, signals and depth
+//! counters to stderr, optionally aborting the process.
+//!
+//! A JS wait frame is marked as pumping while `process_pending` runs callbacks
+//! for the GTK thread. The GTK side is then waiting on work that is actively
+//! running, so pumping frames are never reported as stalls.
+//!
+//! ## Configuration
+//!
+//! - `GTKX_WATCHDOG_MS`: Timeout in milliseconds; unset or `0` disables the watchdog
+//! - `GTKX_WATCHDOG_ABORT`: When set to a non-empty value other than `0`, abort
+//!   after reporting instead of continuing to wait
+//!
+//! Both can also be changed at runtime through the `configureWatchdog` export.
+//! Frames are only recorded while the watchdog is enabled.
+
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::{gtk_dispatch::GtkDispatcher, js_dispatch::JsDispatcher};
+
+const TIMEOUT_ENV: &str = "GTKX_WATCHDOG_MS";
+const ABORT_ENV: &str = "GTKX_WATCHDOG_ABORT";
+
+/// How often the monitor thread checks for stalls while enabled.
+const MIN_POLL_INTERVAL: Duration = Duration::from_millis(50);
+/// How often the monitor thread checks whether it has been enabled.
+const IDLE_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WaitThread {
+    Js,
+    Gtk,
+}
+
+impl WaitThread {
+    fn as_str(self) -> &'static str {
+        match self {
+            WaitThread::Js => "JS",
+            WaitThread::Gtk => "GTK",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameKind {
+    /// Work in progress on the thread, e.g. an FFI call or a signal emission.
+    Activity,
+    /// The thread is blocked waiting for the other thread.
+    Wait,
+}
+
+#[derive(Debug, Clone)]
+pub struct Frame {
+    id: usize,
+    pub thread: WaitThread,
+    pub kind: FrameKind,
+    pub label: String,
+    pub since: Instant,
+    /// Whether the waiting thread is currently running the other thread's
+    /// queued work.
+    pub pumping: bool,
+}
+
+/// Removes its frame from the watchdog when dropped.
+#[must_use]
+pub struct FrameGuard {
+    id: Option<usize>,
+}
+
+impl Drop for FrameGuard {
+    fn drop(&mut self) {
+        if let Some(id) = self.id {
+            Watchdog::global()
+                .frames
+                .lock()
+                .unwrap()
+                .retain(|frame| frame.id != id);
+        }
+    }
+}
+
+impl FrameGuard {
+    /// Marks the frame as pumping until the returned guard is dropped.
+    pub fn pump(&self) -> PumpGuard {
+        if let Some(id) = self.id {
+            Watchdog::global().update_frame(id, |frame| frame.pumping = true);
+        }
+
+        PumpGuard { id: self.id }
+    }
+}
+
+/// Clears the pumping mark when dropped and restarts the frame's wait time.
+#[must_use]
+pub struct PumpGuard {
+    id: Option<usize>,
+}
+
+impl Drop for PumpGuard {
+    fn drop(&mut self) {
+        if let Some(id) = self.id {
+            Watchdog::global().update_frame(id, |frame| {
+                frame.pumping = false;
+                frame.since = Instant::now();
+            });
+        }
+    }
+}
+
+pub struct Watchdog {
+    timeout_ms: AtomicU64,
+    abort: AtomicBool,
+    frames: Mutex<Vec<Frame>>,
+    next_frame_id: AtomicUsize,
+    monitor_started: AtomicBool,
+    /// The pair of wait frames last reported, so one stall is reported once.
+    last_report: Mutex<Option<(usize, usize)>>,
+}
+
+static WATCHDOG: LazyLock<Watchdog> = LazyLock::new(Watchdog::from_env);
+
+fn env_flag(name: &str) -> bool {
+    std::env::var(name).is_ok_and(|value| !value.is_empty() && value != "0")
+}
+
+impl Watchdog {
+    pub fn global() -> &'static Watchdog {
+        &WATCHDOG
+    }
+
+    fn from_env() -> Self {
+        let timeout_ms = std::env::var(TIMEOUT_ENV)
+            .ok()
+            .and_then(|value| value.parse::<u64>().ok())
+            .unwrap_or(0);
+
+        Self {
+            timeout_ms: AtomicU64::new(timeout_ms),
+            abort: AtomicBool::new(env_flag(ABORT_ENV)),
+            frames: Mutex::new(Vec::new()),
+            next_frame_id: AtomicUsize::new(1),
+            monitor_started: AtomicBool::new(false),
+            last_report: Mutex::new(None),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.timeout_ms.load(Ordering::Acquire) > 0
+    }
+
+    /// Sets the stall timeout (`None` disables the watchdog) and whether to
+    /// abort after reporting.
+    pub fn configure(&self, timeout: Option<Duration>, abort: bool) {
+        let timeout_ms = timeout.map_or(0, |timeout| timeout.as_millis().max(1) as u64);
+
+        self.abort.store(abort, Ordering::Release);
+        self.timeout_ms.store(timeout_ms, Ordering::Release);
+
+        if timeout_ms > 0 {
+            self.ensure_monitor();
+        } else {
+            self.frames.lock().unwrap().clear();
+        }
+    }
+
+    /// Records a frame for the current operation until the guard is dropped.
+    /// The label is only built when the watchdog is enabled.
+    pub fn track<F>(&self, thread: WaitThread, kind: FrameKind, label: F) -> FrameGuard
+    where
+        F: FnOnce() -> String,
+    {
+        if !self.is_enabled() {
+            return FrameGuard { id: None };
+        }
+
+        self.ensure_monitor();
+        let id = self.next_frame_id.fetch_add(1, Ordering::Relaxed);
+
+        self.frames.lock().unwrap().push(Frame {
+            id,
+            thread,
+            kind,
+            label: label(),
+            since: Instant::now(),
+            pumping: false,
+        });
+
+        FrameGuard { id: Some(id) }
+    }
+
+    fn update_frame<F>(&self, id: usize, update: F)
+    where
+        F: FnOnce(&mut Frame),
+    {
+        if let Some(frame) = self
+            .frames
+            .lock()
+            .unwrap()
+            .iter_mut()
+            .find(|frame| frame.id == id)
+        {
+            update(frame);
+        }
+    }
+
+    /// Returns the currently recorded frames.
+    pub fn frames(&self) -> Vec<Frame> {
+        self.frames.lock().unwrap().clone()
+    }
+
+    fn ensure_monitor(&self) {
+        if self.monitor_started.load(Ordering::Acquire)
+            || self
+                .monitor_started
+                .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+                .is_err()
+        {
+            return;
+        }
+
+        std::thread::Builder::new()
+            .name("gtkx-watchdog".into())
+            .spawn(|| {
+                loop {
+                    let watchdog = &*WATCHDOG;
+                    let timeout_ms = watchdog.timeout_ms.load(Ordering::Acquire);
+
+                    if timeout_ms == 0 {
+                        std::thread::sleep(IDLE_POLL_INTERVAL);
+                        continue;
+                    }
+
+                    let timeout = Duration::from_millis(timeout_ms);
+                    std::thread::sleep((timeout / 4).max(MIN_POLL_INTERVAL));
+                    let _ = watchdog.check(timeout);
+                }
+            })
+            .expect("Failed to spawn watchdog thread");
+    }
+
+    fn oldest_wait(frames: &[Frame], thread: WaitThread) -> Option<&Frame> {
+        frames
+            .iter()
+            .filter(|frame| {
+                frame.thread == thread && frame.kind == FrameKind::Wait && !frame.pumping
+            })
+            .min_by_key(|frame| frame.since)
+    }
+
+    /// Reports a stall when both threads have a non-pumping wait frame older
+    /// than `timeout`. Returns whether a new stall was reported.
+    pub fn check(&self, timeout: Duration) -> bool {
+        let frames = self.frames();
+
+        let (Some(js_wait), Some(gtk_wait)) = (
+            Self::oldest_wait(&frames, WaitThread::Js),
+            Self::oldest_wait(&frames, WaitThread::Gtk),
+        ) else {
+            return false;
+        };
+
+        if js_wait.since.elapsed() < timeout || gtk_wait.since.elapsed() < timeout {
+            return false;
+        }
+
+        let stall = (js_wait.id, gtk_wait.id);
+        {
+            let mut last_report = self.last_report.lock().unwrap();
+            if *last_report == Some(stall) {
+                return false;
+            }
+            *last_report = Some(stall);
+        }
+
+        eprintln!("{}", Self::report(&frames, timeout));
+
+        if self.abort.load(Ordering::Acquire) {
+            eprintln!("[gtkx] WATCHDOG: aborting ({ABORT_ENV} is set)");
+            std::process::abort();
+        }
+
+        true
+    }
+
+    /// Formats the pending call chain and dispatcher state.
+    pub fn report(frames: &[Frame], timeout: Duration) -> String {
+        let dispatcher = GtkDispatcher::global();
+        let [high, default, low] = dispatcher.pending_task_counts();
+
+        let mut report = format!(
+            "[gtkx] WATCHDOG: JS and GTK threads have both been waiting for over {} ms (possible deadlock)\n\
+             \x20 js_wait_depth={} callback_depth={}\n\
+             \x20 pending GTK tasks: high={high} default={default} low={low}\n\
+             \x20 pending JS callbacks: {}\n\
+             \x20 chain (oldest first):",
+            timeout.as_millis(),
+            dispatcher.js_wait_depth(),
+            dispatcher.callback_depth(),
+            JsDispatcher::global().pending_count(),
+        );
+
+        let mut frames = frames.to_vec();
+        frames.sort_by_key(|frame| frame.since);
+
+        for frame in frames {
+            report.push_str(&format!(
+                "\n    [{:<3} {:>7} ms] {}",
+                frame.thread.as_str(),
+                frame.since.elapsed().as_millis(),
+                frame.label
+            ));
+        }
+
+        report
+    }
+}
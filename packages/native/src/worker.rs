@@ -0,0 +1,66 @@
+//! Native worker thread pool.
+//!
+//! Thread-safe GLib/GIO functions (checksums, `GRegex`, `GKeyFile`, file loading)
+//! can run off the GTK thread so CPU-heavy work does not stall the main loop.
+//! [`WorkerPool`] owns a fixed set of threads that are spawned lazily on first
+//! use and pull jobs from a shared queue.
+//!
+//! Jobs never touch [`crate::state::GtkThreadState`]; anything that needs the
+//! handle map is prepared and finished on the GTK thread around the job.
+
+use std::sync::{Arc, LazyLock, Mutex, mpsc};
+use std::thread;
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+/// Upper bound on worker threads regardless of available parallelism.
+const MAX_WORKERS: usize = 4;
+
+pub struct WorkerPool {
+    sender: Mutex<mpsc::Sender<Job>>,
+}
+
+static POOL: LazyLock<WorkerPool> = LazyLock::new(WorkerPool::new);
+
+impl WorkerPool {
+    pub fn global() -> &'static WorkerPool {
+        &POOL
+    }
+
+    fn new() -> Self {
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+
+        let size = thread::available_parallelism()
+            .map_or(1, |n| n.get())
+            .min(MAX_WORKERS);
+
+        for index in 0..size {
+            let receiver = receiver.clone();
+
+            thread::Builder::new()
+                .name(format!("gtkx-worker-{index}"))
+                .spawn(move || {
+                    loop {
+                        let job = receiver.lock().unwrap().recv();
+                        match job {
+                            Ok(job) => job(),
+                            Err(_) => break,
+                        }
+                    }
+                })
+                .expect("Failed to spawn worker thread");
+        }
+
+        Self {
+            sender: Mutex::new(sender),
+        }
+    }
+
+    pub fn execute<F>(&self, job: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        let _ = self.sender.lock().unwrap().send(Box::new(job));
+    }
+}
@@ -366,3 +366,19 @@ fn clone_owned_without_gtype_panics() {
 
     let _cloned = boxed.clone();
 }
+
+#[test]
+fn clone_owned_without_gtype_copies_known_size() {
+    common::ensure_gtk_init();
+
+    let ptr = unsafe { glib::ffi::g_malloc0(16) };
+    unsafe { (ptr as *mut u8).write(42) };
+    let boxed = Boxed::from_glib_full(None, ptr).with_size(Some(16));
+
+    let cloned = boxed.clone();
+
+    assert!(cloned.is_owned());
+    assert_ne!(cloned.as_ptr(), ptr);
+    assert_eq!(cloned.size(), Some(16));
+    assert_eq!(unsafe { (cloned.as_ptr() as *const u8).read() }, 42);
+}
@@ -181,6 +181,7 @@ fn try_from_string() {
         Type::String(StringType {
             ownership: Ownership::Full,
             length: None,
+            encoding: None,
         }),
         value::Value::String("hello world".to_string()),
     );
@@ -203,6 +204,7 @@ fn try_from_string_null() {
         Type::String(StringType {
             ownership: Ownership::Full,
             length: None,
+            encoding: None,
         }),
         value::Value::Null,
     );
@@ -356,6 +358,7 @@ fn try_from_array_string() {
             item_type: Box::new(Type::String(StringType {
                 ownership: Ownership::Full,
                 length: None,
+                encoding: None,
             })),
             kind: ArrayKind::Array,
             ownership: Ownership::Full,
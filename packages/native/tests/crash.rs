@@ -0,0 +1,27 @@
+use native::crash;
+
+#[test]
+fn crash_report_lists_in_flight_scopes_while_enabled() {
+    crash::configure(false);
+    {
+        let _callback = crash::enter_callback("tick_callback");
+        assert!(!crash::report(libc::SIGSEGV).contains("tick_callback"));
+    }
+
+    crash::configure(true);
+    {
+        let _access = crash::enter_access(|| "read Boolean at offset 8".to_string());
+        let _callback = crash::enter_callback("draw_func");
+
+        let report = crash::report(libc::SIGSEGV);
+        let access = report.find("access read Boolean at offset 8").unwrap();
+        let callback = report.find("callback draw_func").unwrap();
+
+        assert!(access < callback);
+        assert!(report.starts_with("[gtkx] CRASH: fatal signal 11\n"));
+    }
+
+    assert!(crash::report(libc::SIGSEGV).contains("no native call in flight"));
+
+    crash::configure(false);
+}
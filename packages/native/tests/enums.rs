@@ -0,0 +1,126 @@
+mod common;
+
+use gtk4::glib;
+use gtk4::prelude::StaticType as _;
+use native::enums::EnumInfo;
+use native::value::Value;
+
+fn align() -> EnumInfo {
+    common::ensure_gtk_init();
+    EnumInfo::lookup(gtk4::Align::static_type()).unwrap()
+}
+
+fn binding_flags() -> EnumInfo {
+    common::ensure_gtk_init();
+    EnumInfo::lookup(glib::BindingFlags::static_type()).unwrap()
+}
+
+#[test]
+fn lookup_reads_enum_members() {
+    let info = align();
+
+    assert_eq!(info.type_name, "GtkAlign");
+    assert!(!info.is_flags);
+    let center = info.members.iter().find(|m| m.nick == "center").unwrap();
+    assert_eq!(center.name, "GTK_ALIGN_CENTER");
+    assert_eq!(center.value, 3);
+}
+
+#[test]
+fn lookup_reads_flags_members() {
+    let info = binding_flags();
+
+    assert_eq!(info.type_name, "GBindingFlags");
+    assert!(info.is_flags);
+}
+
+#[test]
+fn lookup_rejects_non_enum_types() {
+    common::ensure_gtk_init();
+    let err = EnumInfo::lookup(glib::Type::STRING).unwrap_err();
+    assert!(err.to_string().contains("not an enum or flags type"));
+}
+
+#[test]
+fn enum_nick_and_name_convert_to_number() {
+    let info = align();
+
+    assert_eq!(
+        info.to_number(&Value::String("center".into())).unwrap(),
+        3.0
+    );
+    assert_eq!(
+        info.to_number(&Value::String("GTK_ALIGN_END".into()))
+            .unwrap(),
+        2.0
+    );
+    assert_eq!(info.to_number(&Value::Number(1.0)).unwrap(), 1.0);
+}
+
+#[test]
+fn unknown_nick_lists_valid_ones() {
+    let err = align()
+        .to_number(&Value::String("middle".into()))
+        .unwrap_err()
+        .to_string();
+
+    assert!(err.contains("Unknown GtkAlign value 'middle'"));
+    assert!(err.contains("center"));
+}
+
+#[test]
+fn enum_rejects_arrays() {
+    assert!(align().to_number(&Value::Array(vec![])).is_err());
+}
+
+#[test]
+fn enum_number_converts_to_nick() {
+    let info = align();
+
+    assert!(matches!(info.to_symbolic(3.0), Value::String(nick) if nick == "center"));
+    assert!(matches!(info.to_symbolic(99.0), Value::Number(n) if n == 99.0));
+}
+
+#[test]
+fn flags_names_combine() {
+    let number = binding_flags()
+        .to_number(&Value::Array(vec![
+            Value::String("bidirectional".into()),
+            Value::String("sync-create".into()),
+        ]))
+        .unwrap();
+
+    assert_eq!(number, 3.0);
+}
+
+#[test]
+fn flags_number_converts_to_nicks() {
+    let info = binding_flags();
+
+    let Value::Array(nicks) = info.to_symbolic(3.0) else {
+        panic!("Expected an Array of nicks");
+    };
+    let nicks: Vec<_> = nicks
+        .iter()
+        .map(|nick| match nick {
+            Value::String(nick) => nick.as_str(),
+            other => panic!("Expected a String nick, got {other:?}"),
+        })
+        .collect();
+    assert_eq!(nicks, ["bidirectional", "sync-create"]);
+
+    assert!(matches!(info.to_symbolic(0.0), Value::Array(nicks) if nicks.is_empty()));
+    assert!(matches!(info.to_symbolic(1024.0), Value::Number(n) if n == 1024.0));
+}
+
+#[test]
+fn cached_reuses_members_for_the_same_type() {
+    common::ensure_gtk_init();
+    let library = "libgtk-4.so.1";
+
+    let first = EnumInfo::cached(library, "gtk_align_get_type").unwrap();
+    let second = EnumInfo::cached(library, "gtk_align_get_type").unwrap();
+
+    assert!(std::rc::Rc::ptr_eq(&first, &second));
+    assert_eq!(first.type_name, "GtkAlign");
+}
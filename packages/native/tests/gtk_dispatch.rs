@@ -1,11 +1,11 @@
 mod common;
 
 use std::sync::{
-    Arc,
+    Arc, Mutex,
     atomic::{AtomicUsize, Ordering},
 };
 
-use native::gtk_dispatch::GtkDispatcher;
+use native::gtk_dispatch::{GtkDispatcher, TaskPriority};
 
 #[test]
 fn js_wait_depth_starts_at_zero() {
@@ -64,3 +64,32 @@ fn drop_tracker_works() {
 
     assert_eq!(drop_counter.load(Ordering::SeqCst), 1);
 }
+
+#[test]
+fn dispatch_pending_runs_higher_priority_tasks_first() {
+    common::ensure_gtk_init();
+
+    let order = Arc::new(Mutex::new(Vec::new()));
+
+    for priority in [TaskPriority::Low, TaskPriority::Default, TaskPriority::High] {
+        let order = order.clone();
+        GtkDispatcher::global().schedule_with_priority(priority, move || {
+            order.lock().unwrap().push(priority);
+        });
+    }
+
+    while GtkDispatcher::global().dispatch_pending() {}
+
+    assert_eq!(
+        *order.lock().unwrap(),
+        vec![TaskPriority::High, TaskPriority::Default, TaskPriority::Low]
+    );
+}
+
+#[test]
+fn task_priority_parses_from_str() {
+    assert_eq!("high".parse::<TaskPriority>(), Ok(TaskPriority::High));
+    assert_eq!("default".parse::<TaskPriority>(), Ok(TaskPriority::Default));
+    assert_eq!("low".parse::<TaskPriority>(), Ok(TaskPriority::Low));
+    assert!("urgent".parse::<TaskPriority>().is_err());
+}
@@ -1,5 +1,5 @@
 use native::ffi;
-use native::types::IntegerKind;
+use native::types::{BitField, IntegerKind};
 
 #[test]
 fn read_u8() {
@@ -220,3 +220,78 @@ fn vec_to_f64_wrong_type_fails() {
     let result = IntegerKind::U8.vec_to_f64(&storage);
     assert!(result.is_err());
 }
+
+#[test]
+fn read_bits_unsigned() {
+    let value: u32 = 0b1011_0100;
+    let ptr = &value as *const u32 as *const u8;
+    let bits = BitField {
+        offset: 2,
+        width: 4,
+    };
+    assert_eq!(
+        IntegerKind::U32.read_bits(ptr, bits).unwrap(),
+        0b1101 as f64
+    );
+}
+
+#[test]
+fn read_bits_sign_extends() {
+    let value: u8 = 0b0111_0000;
+    let ptr = &value as *const u8;
+    let bits = BitField {
+        offset: 4,
+        width: 3,
+    };
+    assert_eq!(IntegerKind::I8.read_bits(ptr, bits).unwrap(), -1.0);
+}
+
+#[test]
+fn write_bits_preserves_other_bits() {
+    let mut value: u16 = 0xFFFF;
+    let ptr = &mut value as *mut u16 as *mut u8;
+    let bits = BitField {
+        offset: 4,
+        width: 8,
+    };
+    IntegerKind::U16.write_bits(ptr, bits, 0x5A).unwrap();
+    assert_eq!(value, 0xF5AF);
+}
+
+#[test]
+fn write_bits_signed() {
+    let mut value: i32 = 0;
+    let ptr = &mut value as *mut i32 as *mut u8;
+    let bits = BitField {
+        offset: 1,
+        width: 3,
+    };
+    IntegerKind::I32.write_bits(ptr, bits, -4.0).unwrap();
+    assert_eq!(value, 0b1000);
+    assert_eq!(IntegerKind::I32.read_bits(ptr, bits).unwrap(), -4.0);
+}
+
+#[test]
+fn write_bits_rejects_out_of_range_values() {
+    let mut value: u8 = 0;
+    let ptr = &mut value as *mut u8;
+    let bits = BitField {
+        offset: 0,
+        width: 3,
+    };
+    assert!(IntegerKind::U8.write_bits(ptr, bits, 8.0).is_err());
+    assert!(IntegerKind::U8.write_bits(ptr, bits, -1.0).is_err());
+    assert!(IntegerKind::I8.write_bits(ptr, bits, 4.0).is_err());
+    assert_eq!(value, 0);
+}
+
+#[test]
+fn bits_must_fit_in_storage_unit() {
+    let value: u8 = 0;
+    let ptr = &value as *const u8;
+    let bits = BitField {
+        offset: 6,
+        width: 3,
+    };
+    assert!(IntegerKind::U8.read_bits(ptr, bits).is_err());
+}
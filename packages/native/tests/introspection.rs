@@ -0,0 +1,127 @@
+mod common;
+
+use native::introspection::{Descriptor, Direction, FunctionSignature, LengthOf};
+
+fn lookup(namespace: &str, name: &str) -> FunctionSignature {
+    common::ensure_gtk_init();
+    FunctionSignature::lookup(namespace, name).unwrap()
+}
+
+fn string(descriptor: &Descriptor, key: &str) -> String {
+    match descriptor.get(key) {
+        Some(Descriptor::String(s)) => s.clone(),
+        other => panic!("Expected a string for '{key}', got {other:?}"),
+    }
+}
+
+#[test]
+fn method_has_instance_parameter() {
+    let signature = lookup("Gtk-4.0", "Widget.set_halign");
+
+    assert_eq!(signature.symbol, "gtk_widget_set_halign");
+    assert!(signature.library.contains("libgtk-4"));
+    assert!(!signature.throws);
+    assert_eq!(signature.parameters.len(), 2);
+
+    let instance = &signature.parameters[0];
+    assert_eq!(instance.name, "self");
+    assert_eq!(string(&instance.arg_type, "type"), "gobject");
+    assert_eq!(string(&instance.arg_type, "ownership"), "borrowed");
+
+    let align = &signature.parameters[1].arg_type;
+    assert_eq!(string(align, "type"), "int");
+    assert_eq!(align.get("unsigned"), Some(&Descriptor::Boolean(false)));
+    assert_eq!(string(align, "getTypeFn"), "gtk_align_get_type");
+    assert_eq!(string(&signature.return_type, "type"), "undefined");
+}
+
+#[test]
+fn lookup_accepts_c_symbols() {
+    let signature = lookup("Gtk", "gtk_widget_get_halign");
+
+    assert_eq!(signature.symbol, "gtk_widget_get_halign");
+    assert_eq!(signature.parameters.len(), 1);
+}
+
+#[test]
+fn lookup_accepts_namespace_functions() {
+    let signature = lookup("GLib", "get_user_name");
+
+    assert_eq!(signature.symbol, "g_get_user_name");
+    assert!(signature.parameters.is_empty());
+    assert_eq!(string(&signature.return_type, "type"), "string");
+}
+
+#[test]
+fn transfer_full_return_is_owned() {
+    let signature = lookup("GLib", "strdup");
+
+    assert_eq!(string(&signature.return_type, "ownership"), "full");
+    assert_eq!(
+        string(&signature.parameters[0].arg_type, "ownership"),
+        "borrowed"
+    );
+}
+
+#[test]
+fn out_parameters_are_refs_with_array_lengths() {
+    let signature = lookup("GLib", "file_get_contents");
+
+    assert!(signature.throws);
+    let [filename, contents, length] = &signature.parameters[..] else {
+        panic!("Unexpected parameters {:?}", signature.parameters);
+    };
+
+    assert_eq!(filename.direction, Direction::In);
+    assert_eq!(contents.direction, Direction::Out);
+    assert_eq!(string(&contents.arg_type, "type"), "ref");
+
+    let array = contents.arg_type.get("innerType").unwrap();
+    assert_eq!(string(array, "kind"), "sized");
+    assert_eq!(array.get("sizeParamIndex"), Some(&Descriptor::Number(2.0)));
+
+    assert_eq!(length.direction, Direction::Out);
+    assert_eq!(length.length_of, Some(LengthOf::Parameter(1)));
+}
+
+#[test]
+fn boxed_types_use_get_type_functions() {
+    let signature = lookup("GLib", "DateTime.new_now_utc");
+
+    assert_eq!(string(&signature.return_type, "type"), "boxed");
+    assert_eq!(string(&signature.return_type, "innerType"), "GDateTime");
+    assert_eq!(
+        string(&signature.return_type, "getTypeFn"),
+        "g_date_time_get_type"
+    );
+    assert_eq!(string(&signature.return_type, "ownership"), "full");
+}
+
+#[test]
+fn gpointer_is_pointer_sized() {
+    let signature = lookup("GLib", "free");
+
+    let mem = &signature.parameters[0].arg_type;
+    assert_eq!(string(mem, "type"), "int");
+    assert_eq!(
+        mem.get("size"),
+        Some(&Descriptor::Number((size_of::<usize>() * 8) as f64))
+    );
+    assert_eq!(mem.get("unsigned"), Some(&Descriptor::Boolean(true)));
+}
+
+#[test]
+fn unknown_function_is_an_error() {
+    common::ensure_gtk_init();
+    let err = FunctionSignature::lookup("Gtk", "Widget.no_such_method").unwrap_err();
+
+    assert!(err.to_string().contains("not found"));
+}
+
+#[test]
+fn unknown_namespace_is_an_error() {
+    common::ensure_gtk_init();
+    let err = FunctionSignature::lookup("NoSuchNamespace", "init").unwrap_err();
+
+    assert!(err.to_string().contains("NoSuchNamespace"));
+}
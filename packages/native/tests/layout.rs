@@ -0,0 +1,249 @@
+use native::layout::{FieldSpec, StructLayout};
+use native::types::{
+    ArrayKind, ArrayType, BitField, FloatKind, IntegerKind, Ownership, StringType, StructType, Type,
+};
+
+const POINTER_SIZE: usize = std::mem::size_of::<*mut std::ffi::c_void>();
+
+fn field(name: &str, field_type: Type) -> FieldSpec {
+    FieldSpec::new(name, field_type)
+}
+
+fn bits(name: &str, kind: IntegerKind, width: u32) -> FieldSpec {
+    FieldSpec::bitfield(name, Type::Integer(kind.into()), width)
+}
+
+fn offsets(layout: &StructLayout) -> Vec<usize> {
+    layout.fields.iter().map(|field| field.offset).collect()
+}
+
+#[test]
+fn packs_fields_of_the_same_alignment() {
+    let layout = StructLayout::compute(&[
+        field("red", Type::Float(FloatKind::F32)),
+        field("green", Type::Float(FloatKind::F32)),
+        field("blue", Type::Float(FloatKind::F32)),
+        field("alpha", Type::Float(FloatKind::F32)),
+    ])
+    .unwrap();
+
+    assert_eq!(offsets(&layout), vec![0, 4, 8, 12]);
+    assert_eq!(layout.size, 16);
+    assert_eq!(layout.alignment, 4);
+}
+
+#[test]
+fn pads_fields_to_their_alignment() {
+    let layout = StructLayout::compute(&[
+        field("flag", Type::Integer(IntegerKind::U8.into())),
+        field("count", Type::Integer(IntegerKind::U32.into())),
+        field("total", Type::Integer(IntegerKind::U64.into())),
+    ])
+    .unwrap();
+
+    assert_eq!(offsets(&layout), vec![0, 4, 8]);
+    assert_eq!(layout.size, 16);
+    assert_eq!(layout.alignment, 8);
+}
+
+#[test]
+fn adds_trailing_padding() {
+    let layout = StructLayout::compute(&[
+        field("name", Type::String(StringType::new(Ownership::Full))),
+        field("flag", Type::Boolean),
+    ])
+    .unwrap();
+
+    assert_eq!(offsets(&layout), vec![0, POINTER_SIZE]);
+    assert_eq!(layout.size, 2 * POINTER_SIZE);
+}
+
+#[test]
+fn lays_out_fixed_arrays_inline() {
+    let matrix = Type::Array(ArrayType::new(
+        Type::Float(FloatKind::F32),
+        ArrayKind::Fixed { size: 16 },
+        Ownership::Borrowed,
+    ));
+
+    let layout = StructLayout::compute(&[
+        field("tag", Type::Integer(IntegerKind::U8.into())),
+        field("matrix", matrix),
+    ])
+    .unwrap();
+
+    assert_eq!(layout.field("matrix").unwrap().offset, 4);
+    assert_eq!(layout.field("matrix").unwrap().size, 64);
+    assert_eq!(layout.size, 68);
+}
+
+#[test]
+fn embeds_structs_with_their_alignment() {
+    let mut point = StructType::new(Ownership::Borrowed, "Point".to_string(), Some(16));
+    point.alignment = Some(8);
+
+    let layout = StructLayout::compute(&[
+        field("id", Type::Integer(IntegerKind::I32.into())),
+        field("origin", Type::Struct(point)),
+    ])
+    .unwrap();
+
+    assert_eq!(offsets(&layout), vec![0, 8]);
+    assert_eq!(layout.size, 24);
+}
+
+#[test]
+fn embeds_structs_aligned_to_16_bytes() {
+    let mut vector = StructType::new(Ownership::Borrowed, "Vec4".to_string(), Some(16));
+    vector.alignment = Some(16);
+
+    let layout = StructLayout::compute(&[
+        field("id", Type::Integer(IntegerKind::I32.into())),
+        field("value", Type::Struct(vector)),
+    ])
+    .unwrap();
+
+    assert_eq!(offsets(&layout), vec![0, 16]);
+    assert_eq!(layout.size, 32);
+    assert_eq!(layout.alignment, 16);
+}
+
+#[test]
+fn lays_out_gbooleans_as_int() {
+    let layout = StructLayout::compute(&[
+        field("flag", Type::GBoolean),
+        field("count", Type::Integer(IntegerKind::I32.into())),
+    ])
+    .unwrap();
+
+    assert_eq!(offsets(&layout), vec![0, 4]);
+    assert_eq!(layout.field("flag").unwrap().size, 4);
+    assert_eq!(layout.alignment, 4);
+
+    let layout = StructLayout::compute(&[field("flag", Type::Boolean)]).unwrap();
+    assert_eq!(layout.size, 1);
+}
+
+#[test]
+fn rejects_embedded_structs_without_alignment() {
+    let point = StructType::new(Ownership::Borrowed, "Point".to_string(), Some(16));
+
+    let err = StructLayout::compute(&[field("origin", Type::Struct(point))]).unwrap_err();
+
+    assert!(format!("{err:#}").contains("requires a size and alignment"));
+}
+
+#[test]
+fn rejects_duplicate_fields() {
+    let err = StructLayout::compute(&[
+        field("x", Type::Float(FloatKind::F64)),
+        field("x", Type::Float(FloatKind::F64)),
+    ])
+    .unwrap_err();
+
+    assert!(err.to_string().contains("Duplicate field 'x'"));
+}
+
+#[test]
+fn rejects_empty_structs() {
+    assert!(StructLayout::compute(&[]).is_err());
+}
+
+#[test]
+fn packs_bitfields_into_one_storage_unit() {
+    let layout = StructLayout::compute(&[
+        bits("is_cluster_start", IntegerKind::U32, 1),
+        bits("is_color", IntegerKind::U32, 1),
+        bits("level", IntegerKind::U32, 6),
+        field("next", Type::Integer(IntegerKind::U8.into())),
+    ])
+    .unwrap();
+
+    assert_eq!(offsets(&layout), vec![0, 0, 0, 1]);
+    assert_eq!(
+        layout.field("level").unwrap().bit_field,
+        Some(BitField {
+            offset: 2,
+            width: 6
+        })
+    );
+    assert_eq!(layout.size, 4);
+    assert_eq!(layout.alignment, 4);
+}
+
+#[test]
+fn starts_a_new_unit_when_a_bitfield_would_straddle() {
+    let layout = StructLayout::compute(&[
+        bits("low", IntegerKind::U16, 10),
+        bits("high", IntegerKind::U16, 10),
+    ])
+    .unwrap();
+
+    assert_eq!(offsets(&layout), vec![0, 2]);
+    assert_eq!(layout.field("high").unwrap().bit_field.unwrap().offset, 0);
+    assert_eq!(layout.size, 4);
+}
+
+#[test]
+fn places_bitfields_after_smaller_fields_in_the_same_unit() {
+    let layout = StructLayout::compute(&[
+        field("tag", Type::Integer(IntegerKind::U8.into())),
+        bits("flags", IntegerKind::U32, 4),
+    ])
+    .unwrap();
+
+    let flags = layout.field("flags").unwrap();
+    assert_eq!(flags.offset, 0);
+    assert_eq!(flags.bit_field.unwrap().offset, 8);
+    assert_eq!(layout.size, 4);
+}
+
+#[test]
+fn rejects_invalid_bitfields() {
+    let too_wide = StructLayout::compute(&[bits("x", IntegerKind::U8, 9)]).unwrap_err();
+    assert!(too_wide.to_string().contains("between 1 and 8 bits"));
+
+    let not_integer =
+        StructLayout::compute(&[FieldSpec::bitfield("x", Type::Float(FloatKind::F32), 3)])
+            .unwrap_err();
+    assert!(
+        not_integer
+            .to_string()
+            .contains("must have an integer type")
+    );
+}
+
+#[test]
+fn overlaps_union_variants_at_offset_zero() {
+    let layout = StructLayout::compute_union(&[
+        field("v_int", Type::Integer(IntegerKind::I32.into())),
+        field("v_double", Type::Float(FloatKind::F64)),
+        field(
+            "v_pointer",
+            Type::String(StringType::new(Ownership::Borrowed)),
+        ),
+    ])
+    .unwrap();
+
+    assert_eq!(offsets(&layout), vec![0, 0, 0]);
+    assert_eq!(layout.size, 8);
+    assert_eq!(layout.alignment, 8);
+}
+
+#[test]
+fn rounds_union_size_up_to_its_alignment() {
+    let bytes = Type::Array(ArrayType::new(
+        Type::Integer(IntegerKind::U8.into()),
+        ArrayKind::Fixed { size: 5 },
+        Ownership::Borrowed,
+    ));
+
+    let layout = StructLayout::compute_union(&[
+        field("bytes", bytes),
+        field("word", Type::Integer(IntegerKind::U32.into())),
+    ])
+    .unwrap();
+
+    assert_eq!(layout.size, 8);
+    assert_eq!(layout.alignment, 4);
+}
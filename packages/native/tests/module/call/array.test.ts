@@ -1,14 +1,23 @@
+import { mkdtempSync, writeFileSync } from "node:fs";
+import { tmpdir } from "node:os";
+import { join } from "node:path";
 import { describe, expect, it } from "vitest";
-import { call } from "../../../index.js";
+import { call, createRef } from "../../../index.js";
 import {
+    BOOLEAN,
     createLabel,
     forceGC,
+    GLIB_LIB,
     GOBJECT_BORROWED,
     GTK_LIB,
     getRefCount,
+    INT32,
+    NULL,
     STRING,
     STRING_ARRAY,
     startMemoryMeasurement,
+    UINT8,
+    UINT64,
     UNDEFINED,
 } from "../utils.js";
 
@@ -216,6 +225,95 @@ describe("call - array types", () => {
         });
     });
 
+    describe("sized out-parameters", () => {
+        it("reads a byte array whose length is in another ref", () => {
+            const path = join(mkdtempSync(join(tmpdir(), "gtkx-")), "contents.bin");
+            writeFileSync(path, Buffer.from([1, 2, 3, 250]));
+            const contentsRef = createRef<number[]>([]);
+            const lengthRef = createRef(0);
+
+            const ok = call(
+                GLIB_LIB,
+                "g_file_get_contents",
+                [
+                    { type: STRING, value: path },
+                    {
+                        type: {
+                            type: "ref",
+                            innerType: {
+                                type: "array",
+                                itemType: UINT8,
+                                kind: "sized",
+                                sizeParamIndex: 2,
+                                ownership: "full",
+                            },
+                        },
+                        value: contentsRef,
+                    },
+                    { type: { type: "ref", innerType: UINT64 }, value: lengthRef },
+                    { type: NULL, value: null },
+                ],
+                BOOLEAN,
+            );
+
+            expect(ok).toBe(true);
+            expect(lengthRef.value).toBe(4);
+            expect(contentsRef.value).toEqual([1, 2, 3, 250]);
+        });
+
+        it("reads a string array whose length ref comes first", () => {
+            const argcRef = createRef(0);
+            const argvRef = createRef<string[]>([]);
+
+            const ok = call(
+                GLIB_LIB,
+                "g_shell_parse_argv",
+                [
+                    { type: STRING, value: "app --flag 'quoted value'" },
+                    { type: { type: "ref", innerType: INT32 }, value: argcRef },
+                    {
+                        type: {
+                            type: "ref",
+                            innerType: {
+                                type: "array",
+                                itemType: STRING,
+                                kind: "sized",
+                                sizeParamIndex: 1,
+                                ownership: "full",
+                            },
+                        },
+                        value: argvRef,
+                    },
+                    { type: NULL, value: null },
+                ],
+                BOOLEAN,
+            );
+
+            expect(ok).toBe(true);
+            expect(argcRef.value).toBe(3);
+            expect(argvRef.value).toEqual(["app", "--flag", "quoted value"]);
+        });
+    });
+
+    describe("sized return values", () => {
+        it("reads and frees a returned array whose length is in a ref", () => {
+            const lengthRef = createRef(0);
+
+            const bytes = call(
+                GLIB_LIB,
+                "g_base64_decode",
+                [
+                    { type: STRING, value: "AQID+g==" },
+                    { type: { type: "ref", innerType: UINT64 }, value: lengthRef },
+                ],
+                { type: "array", itemType: UINT8, kind: "sized", sizeParamIndex: 1, ownership: "full" },
+            );
+
+            expect(lengthRef.value).toBe(4);
+            expect(bytes).toEqual([1, 2, 3, 250]);
+        });
+    });
+
     describe("ownership", () => {
         it("handles owned arrays (caller frees)", () => {
             const label = createLabel("Test");
@@ -0,0 +1,155 @@
+import { getEventListeners } from "node:events";
+import { mkdtempSync, writeFileSync } from "node:fs";
+import { tmpdir } from "node:os";
+import { join } from "node:path";
+import { describe, expect, it } from "vitest";
+import { call, createRef } from "../../../index.js";
+import {
+    BOOLEAN,
+    GIO_LIB,
+    GOBJECT,
+    GOBJECT_BORROWED,
+    INT32,
+    NULL,
+    STRING,
+    STRING_BORROWED,
+    UINT8,
+    UINT32,
+    UINT64,
+    UNDEFINED,
+} from "../utils.js";
+
+const G_PRIORITY_DEFAULT = 0;
+const G_FILE_QUERY_INFO_NONE = 0;
+
+function createFile(path: string): unknown {
+    return call(GIO_LIB, "g_file_new_for_path", [{ type: STRING_BORROWED, value: path }], GOBJECT);
+}
+
+function queryInfo(file: unknown, signal?: AbortSignal): Promise<unknown> {
+    return call(
+        GIO_LIB,
+        "g_file_query_info_async",
+        [
+            { type: GOBJECT_BORROWED, value: file },
+            { type: STRING_BORROWED, value: "standard::name" },
+            { type: UINT32, value: G_FILE_QUERY_INFO_NONE },
+            { type: INT32, value: G_PRIORITY_DEFAULT },
+        ],
+        UNDEFINED,
+        { finish: { symbol: "g_file_query_info_finish", resultType: GOBJECT }, signal },
+    ) as Promise<unknown>;
+}
+
+describe("call - async", () => {
+    it("resolves with the _finish result", async () => {
+        const info = await queryInfo(createFile("/"));
+
+        const name = call(
+            GIO_LIB,
+            "g_file_info_get_name",
+            [{ type: GOBJECT_BORROWED, value: info }],
+            STRING_BORROWED,
+        );
+
+        expect(name).toBe("/");
+    });
+
+    it("rejects with the GError from _finish", async () => {
+        const file = createFile("/nonexistent/gtkx/path");
+
+        await expect(queryInfo(file)).rejects.toMatchObject({ domain: "g-io-error-quark" });
+    });
+
+    it("rejects with an AbortError when the signal is already aborted", async () => {
+        const controller = new AbortController();
+        controller.abort();
+
+        await expect(queryInfo(createFile("/"), controller.signal)).rejects.toMatchObject({ name: "AbortError" });
+    });
+
+    it("removes its abort listener once the call settles", async () => {
+        const controller = new AbortController();
+
+        await queryInfo(createFile("/"), controller.signal);
+
+        expect(getEventListeners(controller.signal, "abort")).toHaveLength(0);
+    });
+
+    it("passes declared out-parameters to _finish", async () => {
+        const path = join(mkdtempSync(join(tmpdir(), "gtkx-")), "contents.txt");
+        writeFileSync(path, "hello");
+        const contents = createRef<number[]>([]);
+        const length = createRef(0);
+
+        const ok = await call(
+            GIO_LIB,
+            "g_file_load_contents_async",
+            [{ type: GOBJECT_BORROWED, value: createFile(path) }],
+            UNDEFINED,
+            {
+                finish: {
+                    symbol: "g_file_load_contents_finish",
+                    resultType: BOOLEAN,
+                    args: [
+                        {
+                            type: {
+                                type: "ref",
+                                innerType: {
+                                    type: "array",
+                                    itemType: UINT8,
+                                    kind: "sized",
+                                    sizeParamIndex: 1,
+                                    ownership: "full",
+                                },
+                            },
+                            value: contents,
+                        },
+                        { type: { type: "ref", innerType: UINT64 }, value: length },
+                        { type: NULL, value: null },
+                    ],
+                },
+            },
+        );
+
+        expect(ok).toBe(true);
+        expect(length.value).toBe(5);
+        expect(Buffer.from(contents.value).toString()).toBe("hello");
+    });
+
+    it("calls static _finish functions without a source object", async () => {
+        const result = await call(
+            GIO_LIB,
+            "g_dbus_address_get_stream",
+            [{ type: STRING_BORROWED, value: "unix:path=/nonexistent/gtkx.sock" }],
+            UNDEFINED,
+            {
+                finish: {
+                    symbol: "g_dbus_address_get_stream_finish",
+                    resultType: GOBJECT,
+                    instance: false,
+                    args: [{ type: NULL, value: null }],
+                },
+            },
+        ).catch((error: unknown) => error);
+
+        expect(result).toMatchObject({ domain: "g-io-error-quark" });
+    });
+
+    it("requires finish when a signal is given", () => {
+        expect(() =>
+            call(GIO_LIB, "g_file_new_for_path", [{ type: STRING_BORROWED, value: "/" }], GOBJECT, {
+                signal: new AbortController().signal,
+            }),
+        ).toThrow("'signal' requires the 'finish' call option");
+    });
+
+    it("refuses worker threads", () => {
+        expect(() =>
+            call(GIO_LIB, "g_file_query_info_async", [], UNDEFINED, {
+                thread: "worker",
+                finish: { symbol: "g_file_query_info_finish", resultType: STRING },
+            }),
+        ).toThrow("Async calls cannot run on a worker thread");
+    });
+});
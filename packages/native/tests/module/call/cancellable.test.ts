@@ -0,0 +1,46 @@
+import { getEventListeners } from "node:events";
+import { describe, expect, it } from "vitest";
+import { call } from "../../../index.js";
+import { BOOLEAN, createCancellable, GIO_LIB } from "../utils.js";
+
+const CANCELLABLE = { type: "cancellable" as const };
+
+function isCancelled(value: unknown): boolean {
+    return call(GIO_LIB, "g_cancellable_is_cancelled", [{ type: CANCELLABLE, value }], BOOLEAN) as boolean;
+}
+
+describe("call - cancellable types", () => {
+    it("creates a cancellable from a live signal", () => {
+        expect(isCancelled(new AbortController().signal)).toBe(false);
+    });
+
+    it("creates a cancelled cancellable from an aborted signal", () => {
+        const controller = new AbortController();
+        controller.abort();
+
+        expect(isCancelled(controller.signal)).toBe(true);
+    });
+
+    it("removes its abort listener once the call returns", () => {
+        const controller = new AbortController();
+
+        isCancelled(controller.signal);
+
+        expect(getEventListeners(controller.signal, "abort")).toHaveLength(0);
+    });
+
+    it("passes null for null and undefined", () => {
+        expect(isCancelled(null)).toBe(false);
+        expect(isCancelled(undefined)).toBe(false);
+    });
+
+    it("accepts an existing GCancellable handle", () => {
+        const cancellable = createCancellable();
+
+        expect(isCancelled(cancellable)).toBe(false);
+    });
+
+    it("throws for values that are not signals", () => {
+        expect(() => isCancelled(42)).toThrow("Expected an AbortSignal for cancellable type");
+    });
+});
@@ -0,0 +1,175 @@
+import { describe, expect, it } from "vitest";
+import { call } from "../../../index.js";
+import { GIO_LIB, GLIB_LIB, INT32, INT64, NULL, STRING, STRING_BORROWED, UINT64 } from "../utils.js";
+
+const DATE_FULL = {
+    type: "boxed" as const,
+    innerType: "GDateTime",
+    ownership: "full" as const,
+    convert: "date" as const,
+};
+const DATE_BORROWED = { ...DATE_FULL, ownership: "borrowed" as const };
+const MICROS_FULL = { ...DATE_FULL, convert: "bigint" as const };
+const MICROS_BORROWED = { ...MICROS_FULL, ownership: "borrowed" as const };
+const BYTES_FULL = {
+    type: "boxed" as const,
+    innerType: "GBytes",
+    ownership: "full" as const,
+    convert: "bytes" as const,
+};
+const BYTES_BORROWED = { ...BYTES_FULL, ownership: "borrowed" as const };
+const PATH_FULL = { type: "gobject" as const, ownership: "full" as const, convert: "path" as const };
+const PATH_BORROWED = { ...PATH_FULL, ownership: "borrowed" as const };
+const URI_FULL = { ...PATH_FULL, convert: "uri" as const };
+const ICON_FULL = { type: "gobject" as const, ownership: "full" as const, convert: "string" as const };
+const ICON_BORROWED = { ...ICON_FULL, ownership: "borrowed" as const };
+
+describe("call - converted types", () => {
+    describe("GDateTime", () => {
+        it("returns a Date", () => {
+            const date = call(
+                GLIB_LIB,
+                "g_date_time_new_from_unix_utc",
+                [{ type: INT64, value: 1_700_000_000 }],
+                DATE_FULL,
+            );
+
+            expect(date).toBeInstanceOf(Date);
+            expect((date as Date).toISOString()).toBe("2023-11-14T22:13:20.000Z");
+        });
+
+        it("passes a Date", () => {
+            const seconds = call(
+                GLIB_LIB,
+                "g_date_time_to_unix",
+                [{ type: DATE_BORROWED, value: new Date("2023-11-14T22:13:20.500Z") }],
+                INT64,
+            );
+
+            expect(seconds).toBe(1_700_000_000);
+        });
+
+        it("rejects an Invalid Date", () => {
+            expect(() =>
+                call(GLIB_LIB, "g_date_time_to_unix", [{ type: DATE_BORROWED, value: new Date(Number.NaN) }], INT64),
+            ).toThrow(TypeError);
+        });
+
+        it("keeps microseconds as a BigInt", () => {
+            const micros = 1_700_000_000_123_456n;
+
+            const microsecond = call(
+                GLIB_LIB,
+                "g_date_time_get_microsecond",
+                [{ type: MICROS_BORROWED, value: micros }],
+                INT32,
+            );
+            const copy = call(
+                GLIB_LIB,
+                "g_date_time_add",
+                [
+                    { type: MICROS_BORROWED, value: micros },
+                    { type: INT64, value: 0 },
+                ],
+                MICROS_FULL,
+            );
+
+            expect(microsecond).toBe(123_456);
+            expect(copy).toBe(micros);
+        });
+
+        it("still accepts handles", () => {
+            const handle = call(GLIB_LIB, "g_date_time_new_now_utc", [], {
+                type: "boxed",
+                innerType: "GDateTime",
+                ownership: "full",
+            });
+
+            const date = call(GLIB_LIB, "g_date_time_to_utc", [{ type: DATE_BORROWED, value: handle }], DATE_FULL);
+
+            expect(date).toBeInstanceOf(Date);
+        });
+    });
+
+    describe("GBytes", () => {
+        it("passes and returns a Uint8Array", () => {
+            const data = new Uint8Array([0, 1, 2, 255]);
+
+            const size = call(GLIB_LIB, "g_bytes_get_size", [{ type: BYTES_BORROWED, value: data }], UINT64);
+            const copy = call(
+                GLIB_LIB,
+                "g_bytes_new_from_bytes",
+                [
+                    { type: BYTES_BORROWED, value: data },
+                    { type: UINT64, value: 1 },
+                    { type: UINT64, value: 3 },
+                ],
+                BYTES_FULL,
+            );
+
+            expect(size).toBe(4);
+            expect(copy).toEqual(new Uint8Array([1, 2, 255]));
+        });
+    });
+
+    describe("GFile", () => {
+        it("passes a path and returns a path or URI", () => {
+            const basename = call(
+                GIO_LIB,
+                "g_file_get_basename",
+                [{ type: PATH_BORROWED, value: "/tmp/gtkx/notes.txt" }],
+                STRING,
+            );
+            const parent = call(
+                GIO_LIB,
+                "g_file_get_parent",
+                [{ type: PATH_BORROWED, value: "/tmp/gtkx/notes.txt" }],
+                PATH_FULL,
+            );
+            const uri = call(GIO_LIB, "g_file_new_for_path", [{ type: STRING_BORROWED, value: "/tmp/gtkx" }], URI_FULL);
+
+            expect(basename).toBe("notes.txt");
+            expect(parent).toBe("/tmp/gtkx");
+            expect(uri).toBe("file:///tmp/gtkx");
+        });
+
+        it("rejects files without a local path", () => {
+            expect(() =>
+                call(
+                    GIO_LIB,
+                    "g_file_new_for_uri",
+                    [{ type: STRING_BORROWED, value: "https://example.com/a" }],
+                    PATH_FULL,
+                ),
+            ).toThrow(/no local path/);
+        });
+    });
+
+    describe("GIcon", () => {
+        it("round-trips the string form", () => {
+            const icon = call(
+                GIO_LIB,
+                "g_icon_new_for_string",
+                [
+                    { type: STRING_BORROWED, value: "/tmp/gtkx/icon.png" },
+                    { type: NULL, value: null },
+                ],
+                ICON_FULL,
+            );
+            const serialized = call(GIO_LIB, "g_icon_to_string", [{ type: ICON_BORROWED, value: icon }], STRING);
+
+            expect(icon).toBe(serialized);
+            expect(icon).toContain("icon.png");
+        });
+    });
+
+    it("rejects converters for the wrong kind of type", () => {
+        expect(() =>
+            call(GLIB_LIB, "g_date_time_new_now_utc", [], {
+                type: "gobject",
+                ownership: "full",
+                convert: "date",
+            } as never),
+        ).toThrow(/'date' converter applies to boxed types/);
+    });
+});
@@ -1,10 +1,11 @@
 import { describe, expect, it } from "vitest";
-import { call } from "../../../index.js";
+import { alloc, call } from "../../../index.js";
 import {
     createBox,
     createButton,
     createLabel,
     forceGC,
+    GDK_LIB,
     GOBJECT,
     GOBJECT_BORROWED,
     GTK_LIB,
@@ -592,4 +593,44 @@ describe("call - gobject types", () => {
             expect(current).toBeDefined();
         });
     });
+
+    describe("type checks", () => {
+        it("accepts instances of the expected type or a subtype", () => {
+            const label = createLabel("Checked");
+
+            const text = call(
+                GTK_LIB,
+                "gtk_label_get_text",
+                [{ type: { ...GOBJECT_BORROWED, typeName: "GtkWidget" }, value: label }],
+                STRING_BORROWED,
+            );
+
+            expect(text).toBe("Checked");
+        });
+
+        it("rejects instances of an unrelated type", () => {
+            const label = createLabel();
+            const child = createLabel();
+
+            expect(() =>
+                call(
+                    GTK_LIB,
+                    "gtk_box_append",
+                    [
+                        { type: { ...GOBJECT_BORROWED, typeName: "GtkBox" }, value: label },
+                        { type: GOBJECT_BORROWED, value: child },
+                    ],
+                    UNDEFINED,
+                ),
+            ).toThrow("Expected gobject GtkBox, got gobject GtkLabel");
+        });
+
+        it("rejects boxed handles passed as GObjects", () => {
+            const rgba = alloc(16, "GdkRGBA", GDK_LIB);
+
+            expect(() =>
+                call(GTK_LIB, "gtk_label_get_text", [{ type: GOBJECT_BORROWED, value: rgba }], STRING_BORROWED),
+            ).toThrow("Expected gobject, got boxed GdkRGBA");
+        });
+    });
 });
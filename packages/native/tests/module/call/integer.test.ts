@@ -1,6 +1,7 @@
 import { describe, expect, it } from "vitest";
 import { call } from "../../../index.js";
 import {
+    BOOLEAN,
     createBox,
     createButton,
     createGrid,
@@ -495,4 +496,104 @@ describe("call - integer types", () => {
             expect(spacing).toBe(15);
         });
     });
+
+    describe("symbolic enums and flags", () => {
+        const ALIGN = { ...INT32, library: GTK_LIB, getTypeFn: "gtk_align_get_type", symbolic: true };
+        const STATE_FLAGS = { ...UINT32, library: GTK_LIB, getTypeFn: "gtk_state_flags_get_type", symbolic: true };
+
+        it("passes and returns enum values as nicknames", () => {
+            const label = createLabel();
+
+            call(
+                GTK_LIB,
+                "gtk_widget_set_halign",
+                [
+                    { type: GOBJECT_BORROWED, value: label },
+                    { type: ALIGN, value: "center" },
+                ],
+                UNDEFINED,
+            );
+
+            expect(call(GTK_LIB, "gtk_widget_get_halign", [{ type: GOBJECT_BORROWED, value: label }], ALIGN)).toBe(
+                "center",
+            );
+            expect(call(GTK_LIB, "gtk_widget_get_halign", [{ type: GOBJECT_BORROWED, value: label }], INT32)).toBe(3);
+        });
+
+        it("accepts full names and numbers for symbolic enums", () => {
+            const label = createLabel();
+
+            call(
+                GTK_LIB,
+                "gtk_widget_set_valign",
+                [
+                    { type: GOBJECT_BORROWED, value: label },
+                    { type: ALIGN, value: "GTK_ALIGN_END" },
+                ],
+                UNDEFINED,
+            );
+            expect(call(GTK_LIB, "gtk_widget_get_valign", [{ type: GOBJECT_BORROWED, value: label }], ALIGN)).toBe(
+                "end",
+            );
+
+            call(
+                GTK_LIB,
+                "gtk_widget_set_valign",
+                [
+                    { type: GOBJECT_BORROWED, value: label },
+                    { type: ALIGN, value: 1 },
+                ],
+                UNDEFINED,
+            );
+            expect(call(GTK_LIB, "gtk_widget_get_valign", [{ type: GOBJECT_BORROWED, value: label }], ALIGN)).toBe(
+                "start",
+            );
+        });
+
+        it("rejects unknown nicknames", () => {
+            const label = createLabel();
+
+            expect(() =>
+                call(
+                    GTK_LIB,
+                    "gtk_widget_set_halign",
+                    [
+                        { type: GOBJECT_BORROWED, value: label },
+                        { type: ALIGN, value: "middle" },
+                    ],
+                    UNDEFINED,
+                ),
+            ).toThrow(/Unknown GtkAlign value 'middle'/);
+        });
+
+        it("passes and returns flags as arrays of nicknames", () => {
+            const label = createLabel();
+
+            call(
+                GTK_LIB,
+                "gtk_widget_set_state_flags",
+                [
+                    { type: GOBJECT_BORROWED, value: label },
+                    { type: STATE_FLAGS, value: ["active", "selected"] },
+                    { type: BOOLEAN, value: false },
+                ],
+                UNDEFINED,
+            );
+
+            const flags = call(
+                GTK_LIB,
+                "gtk_widget_get_state_flags",
+                [{ type: GOBJECT_BORROWED, value: label }],
+                STATE_FLAGS,
+            );
+
+            expect(flags).toEqual(expect.arrayContaining(["active", "selected"]));
+        });
+
+        it("requires a library and get-type function", () => {
+            expect(() => call(GTK_LIB, "gtk_get_major_version", [], { ...UINT32, symbolic: true })).toThrow(
+                /'symbolic' requires 'library' and 'getTypeFn'/,
+            );
+        });
+    });
 });
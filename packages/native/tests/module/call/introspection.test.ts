@@ -0,0 +1,72 @@
+import { mkdtempSync, writeFileSync } from "node:fs";
+import { tmpdir } from "node:os";
+import { join } from "node:path";
+import { describe, expect, it } from "vitest";
+import { call, describeFunction } from "../../../index.js";
+import { createLabel } from "../utils.js";
+
+describe("call - introspected signatures", () => {
+    describe("describeFunction", () => {
+        it("describes a method with its instance parameter", () => {
+            const signature = describeFunction("Gtk-4.0", "Widget.set_halign");
+
+            expect(signature.symbol).toBe("gtk_widget_set_halign");
+            expect(signature.throws).toBe(false);
+            expect(signature.parameters.map((parameter) => parameter.name)).toEqual(["self", "align"]);
+            expect(signature.parameters[0]?.type).toMatchObject({ type: "gobject", ownership: "borrowed" });
+            expect(signature.parameters[1]?.type).toMatchObject({ type: "int", getTypeFn: "gtk_align_get_type" });
+            expect(signature.returnType).toEqual({ type: "undefined" });
+        });
+
+        it("accepts a C symbol", () => {
+            expect(describeFunction("Gtk", "gtk_widget_get_halign").symbol).toBe("gtk_widget_get_halign");
+        });
+
+        it("marks out-parameters and array lengths", () => {
+            const signature = describeFunction("GLib", "file_get_contents");
+
+            expect(signature.throws).toBe(true);
+            expect(signature.parameters.map((parameter) => parameter.direction)).toEqual(["in", "out", "out"]);
+            expect(signature.parameters[2]?.lengthOf).toBe(1);
+        });
+
+        it("throws for an unknown function", () => {
+            expect(() => describeFunction("Gtk", "Widget.no_such_method")).toThrow(/not found/);
+        });
+    });
+
+    describe("call", () => {
+        it("calls a method by name", () => {
+            const label = createLabel("Introspected");
+
+            call("Gtk-4.0", "Widget.set_halign", [label, 3]);
+
+            expect(call("Gtk-4.0", "Widget.get_halign", [label])).toBe(3);
+        });
+
+        it("returns owned strings", () => {
+            expect(call("GLib", "strdup", ["hello"])).toBe("hello");
+        });
+
+        it("fills in array lengths", () => {
+            expect(call("GLib", "base64_encode", [[104, 105]])).toBe("aGk=");
+        });
+
+        it("returns out-parameters after the return value", () => {
+            const path = join(mkdtempSync(join(tmpdir(), "gtkx-")), "contents.bin");
+            writeFileSync(path, Buffer.from([1, 2, 3, 250]));
+
+            expect(call("GLib", "file_get_contents", [path])).toEqual([true, [1, 2, 3, 250]]);
+        });
+
+        it("throws GErrors", () => {
+            expect(() => call("GLib", "file_get_contents", ["/nonexistent/gtkx"])).toThrow(/nonexistent/);
+        });
+
+        it("sets the domain and code of thrown GErrors", () => {
+            expect(() => call("GLib", "file_get_contents", ["/nonexistent/gtkx"])).toThrow(
+                expect.objectContaining({ domain: "g-file-error-quark", code: 4 }),
+            );
+        });
+    });
+});
@@ -0,0 +1,23 @@
+import { describe, expect, it } from "vitest";
+import { call } from "../../../index.js";
+import { createLabel, GOBJECT_BORROWED, GTK_LIB, STRING_BORROWED } from "../utils.js";
+
+function getText(label: unknown, priority: "high" | "default" | "low"): unknown {
+    return call(GTK_LIB, "gtk_label_get_text", [{ type: GOBJECT_BORROWED, value: label }], STRING_BORROWED, {
+        priority,
+    });
+}
+
+describe("call - priority", () => {
+    it.each(["high", "default", "low"] as const)("runs calls at %s priority", (priority) => {
+        const label = createLabel("Prioritized");
+
+        expect(getText(label, priority)).toBe("Prioritized");
+    });
+
+    it("rejects unknown priorities", () => {
+        const label = createLabel();
+
+        expect(() => getText(label, "urgent" as never)).toThrow("'priority' must be one of");
+    });
+});
@@ -1,17 +1,30 @@
 import { describe, expect, it } from "vitest";
-import { call } from "../../../index.js";
+import { call, read } from "../../../index.js";
 import {
+    BOOLEAN,
     createLabel,
     createRef,
+    FLOAT32,
+    GDK_LIB,
+    GOBJECT,
     GOBJECT_BORROWED,
+    GOBJECT_LIB,
     GTK_LIB,
     getRefCount,
     INT32,
     NULL,
+    STRING,
     startMemoryMeasurement,
     UNDEFINED,
 } from "../utils.js";
 
+const CALLER_ALLOCATED_RGBA = {
+    type: "ref" as const,
+    innerType: { type: "boxed" as const, ownership: "full" as const, innerType: "GdkRGBA", library: GDK_LIB },
+    callerAllocates: true,
+    size: 16,
+};
+
 describe("call - ref types", () => {
     describe("integer refs", () => {
         it("populates 32-bit signed integer ref", () => {
@@ -201,6 +214,167 @@ describe("call - ref types", () => {
         });
     });
 
+    describe("caller-allocated refs", () => {
+        it("allocates the struct and returns it as a handle", () => {
+            const rgbaRef = createRef<unknown>(null);
+
+            const parsed = call(
+                GTK_LIB,
+                "gdk_rgba_parse",
+                [
+                    { type: CALLER_ALLOCATED_RGBA, value: rgbaRef },
+                    { type: STRING, value: "rgba(255, 0, 0, 0.5)" },
+                ],
+                BOOLEAN,
+            );
+
+            expect(parsed).toBe(true);
+            expect(rgbaRef.value).not.toBeNull();
+            expect(read(rgbaRef.value, FLOAT32, 0)).toBe(1);
+            expect(read(rgbaRef.value, FLOAT32, 12)).toBe(0.5);
+        });
+
+        it("bounds-checks reads with the allocation size", () => {
+            const rgbaRef = createRef<unknown>(null);
+
+            call(
+                GTK_LIB,
+                "gdk_rgba_parse",
+                [
+                    { type: CALLER_ALLOCATED_RGBA, value: rgbaRef },
+                    { type: STRING, value: "blue" },
+                ],
+                BOOLEAN,
+            );
+
+            expect(() => read(rgbaRef.value, FLOAT32, 16)).toThrow();
+        });
+
+        it("takes the size of an embedded struct type", () => {
+            const rgbaRef = createRef<unknown>(null);
+
+            call(
+                GTK_LIB,
+                "gdk_rgba_parse",
+                [
+                    {
+                        type: {
+                            type: "ref",
+                            innerType: { type: "struct", ownership: "full", innerType: "GdkRGBA", size: 16 },
+                            callerAllocates: true,
+                        },
+                        value: rgbaRef,
+                    },
+                    { type: STRING, value: "#00ff00" },
+                ],
+                BOOLEAN,
+            );
+
+            expect(read(rgbaRef.value, FLOAT32, 4)).toBe(1);
+        });
+
+        it("requires a size", () => {
+            expect(() =>
+                call(
+                    GTK_LIB,
+                    "gdk_rgba_parse",
+                    [
+                        { type: { ...CALLER_ALLOCATED_RGBA, size: undefined }, value: createRef(null) },
+                        { type: STRING, value: "red" },
+                    ],
+                    BOOLEAN,
+                ),
+            ).toThrow("'callerAllocates' refs require a positive 'size'");
+        });
+    });
+
+    describe("inout refs", () => {
+        const TEXT_ITER = {
+            type: "ref" as const,
+            innerType: {
+                type: "boxed" as const,
+                ownership: "full" as const,
+                innerType: "GtkTextIter",
+                library: GTK_LIB,
+                getTypeFn: "gtk_text_iter_get_type",
+            },
+            callerAllocates: true,
+            size: 80,
+        };
+
+        const createTextBuffer = (text: string) => {
+            const buffer = call(GTK_LIB, "gtk_text_buffer_new", [{ type: NULL, value: null }], GOBJECT);
+            call(
+                GTK_LIB,
+                "gtk_text_buffer_set_text",
+                [
+                    { type: GOBJECT_BORROWED, value: buffer },
+                    { type: STRING, value: text },
+                    { type: INT32, value: -1 },
+                ],
+                UNDEFINED,
+            );
+            return buffer;
+        };
+
+        const iterOffset = (iter: unknown) =>
+            call(
+                GTK_LIB,
+                "gtk_text_iter_get_offset",
+                [{ type: { type: "boxed", ownership: "borrowed", innerType: "GtkTextIter" }, value: iter }],
+                INT32,
+            );
+
+        it("passes the current object and writes back the updated one", () => {
+            const label = createLabel("Inout");
+            const objectRef = createRef<unknown>(label);
+            const initialRefCount = getRefCount(label);
+
+            call(
+                GOBJECT_LIB,
+                "g_clear_object",
+                [{ type: { type: "ref", innerType: GOBJECT }, value: objectRef }],
+                UNDEFINED,
+            );
+
+            expect(objectRef.value).toBeNull();
+            expect(getRefCount(label)).toBe(initialRefCount);
+        });
+
+        it("copies a caller-allocated struct in and out", () => {
+            const buffer = createTextBuffer("hello");
+            const iterRef = createRef<unknown>(null);
+
+            call(
+                GTK_LIB,
+                "gtk_text_buffer_get_start_iter",
+                [
+                    { type: GOBJECT_BORROWED, value: buffer },
+                    { type: TEXT_ITER, value: iterRef },
+                ],
+                UNDEFINED,
+            );
+            const start = iterRef.value;
+
+            const moved = call(GTK_LIB, "gtk_text_iter_forward_char", [{ type: TEXT_ITER, value: iterRef }], BOOLEAN);
+
+            expect(moved).toBe(true);
+            expect(iterOffset(iterRef.value)).toBe(1);
+            expect(iterOffset(start)).toBe(0);
+        });
+
+        it("rejects non-object values for pointer refs", () => {
+            expect(() =>
+                call(
+                    GOBJECT_LIB,
+                    "g_clear_object",
+                    [{ type: { type: "ref", innerType: GOBJECT }, value: createRef(42) }],
+                    UNDEFINED,
+                ),
+            ).toThrow("Expected an Object or Null");
+        });
+    });
+
     describe("memory leaks", () => {
         it("does not leak when using many refs in loop", () => {
             const label = createLabel("Test Label for Memory Leak Check");
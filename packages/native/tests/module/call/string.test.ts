@@ -6,6 +6,7 @@ import {
     forceGC,
     GOBJECT,
     GOBJECT_BORROWED,
+    GLIB_LIB,
     GTK_LIB,
     getRefCount,
     STRING,
@@ -363,4 +364,50 @@ describe("call - string types", () => {
             expect(text).toBe("Bold and italic");
         });
     });
+
+    describe("encodings", () => {
+        const INVALID_UTF8 = new Uint8Array([0x66, 0xff, 0x6f]);
+
+        const strdup = (value: unknown, encoding: "utf8" | "filename" | "bytes" | "lossy") =>
+            call(
+                GLIB_LIB,
+                "g_strdup",
+                [{ type: { type: "string", ownership: "borrowed", encoding: "bytes" }, value }],
+                { type: "string", ownership: "full", encoding },
+            );
+
+        it("rejects invalid UTF-8 by default", () => {
+            expect(() => strdup(INVALID_UTF8, "utf8")).toThrow("not valid UTF-8");
+        });
+
+        it("replaces invalid UTF-8 with the lossy encoding", () => {
+            expect(strdup(INVALID_UTF8, "lossy")).toBe("f\uFFFDo");
+        });
+
+        it("returns raw bytes with the bytes encoding", () => {
+            const result = strdup(INVALID_UTF8, "bytes");
+
+            expect(result).toBeInstanceOf(Uint8Array);
+            expect(Array.from(result as Uint8Array)).toEqual([0x66, 0xff, 0x6f]);
+        });
+
+        it("passes strings as bytes with the bytes encoding", () => {
+            expect(Array.from(strdup("hé", "bytes") as Uint8Array)).toEqual([0x68, 0xc3, 0xa9]);
+        });
+
+        it("converts filenames to and from UTF-8", () => {
+            const result = call(
+                GLIB_LIB,
+                "g_strdup",
+                [{ type: { type: "string", ownership: "borrowed", encoding: "filename" }, value: "/tmp/ünïcode" }],
+                { type: "string", ownership: "full", encoding: "filename" },
+            );
+
+            expect(result).toBe("/tmp/ünïcode");
+        });
+
+        it("rejects unknown encodings", () => {
+            expect(() => strdup("text", "latin1" as "utf8")).toThrow("Unknown string encoding: latin1");
+        });
+    });
 });
@@ -0,0 +1,87 @@
+import { afterEach, beforeEach, describe, expect, it } from "vitest";
+import { call, clearTrace, configureTracing, dumpTrace } from "../../../index.js";
+import { createLabel, GOBJECT_BORROWED, GTK_LIB, STRING_BORROWED } from "../utils.js";
+
+type TraceEntry = {
+    library: string;
+    symbol: string;
+    args: string[];
+    result?: string;
+    error?: string;
+    thread: string;
+    queueWaitUs: number;
+    durationUs: number;
+};
+
+function getText(label: unknown): unknown {
+    return call(GTK_LIB, "gtk_label_get_text", [{ type: GOBJECT_BORROWED, value: label }], STRING_BORROWED);
+}
+
+function tracedSymbol(symbol: string): TraceEntry | undefined {
+    const entries = JSON.parse(dumpTrace()) as TraceEntry[];
+    return entries.findLast((entry) => entry.symbol === symbol);
+}
+
+describe("call - tracing", () => {
+    beforeEach(() => {
+        clearTrace();
+        configureTracing({ enabled: true, capacity: 16 });
+    });
+
+    afterEach(() => {
+        configureTracing({ enabled: false });
+        clearTrace();
+    });
+
+    it("records the library, symbol, arguments, result and timings", () => {
+        const label = createLabel("Traced");
+
+        getText(label);
+
+        const entry = tracedSymbol("gtk_label_get_text");
+        expect(entry?.library).toBe(GTK_LIB);
+        expect(entry?.args).toHaveLength(1);
+        expect(entry?.result).toBe('"Traced"');
+        expect(entry?.thread).toBe("gtk");
+        expect(entry?.queueWaitUs).toBeGreaterThanOrEqual(0);
+        expect(entry?.durationUs).toBeGreaterThanOrEqual(0);
+    });
+
+    it("records failed calls with their error", () => {
+        expect(() => call(GTK_LIB, "gtk_label_does_not_exist", [], STRING_BORROWED)).toThrow();
+
+        expect(tracedSymbol("gtk_label_does_not_exist")?.error).toBeDefined();
+    });
+
+    it("keeps only the most recent calls", () => {
+        const label = createLabel("Ring");
+
+        for (let i = 0; i < 20; i++) {
+            getText(label);
+        }
+
+        expect(JSON.parse(dumpTrace())).toHaveLength(16);
+    });
+
+    it("does not record calls while disabled", () => {
+        const label = createLabel("Untraced");
+        configureTracing({ enabled: false });
+        clearTrace();
+
+        getText(label);
+
+        expect(JSON.parse(dumpTrace())).toHaveLength(0);
+    });
+
+    it("exports complete events in Chrome trace format", () => {
+        const label = createLabel("Chrome");
+
+        getText(label);
+
+        const trace = JSON.parse(dumpTrace("chrome"));
+        const event = trace.traceEvents.findLast((event: { name: string }) => event.name === "gtk_label_get_text");
+        expect(event.ph).toBe("X");
+        expect(event.cat).toBe(GTK_LIB);
+        expect(event.args.result).toBe('"Chrome"');
+    });
+});
@@ -0,0 +1,106 @@
+import { describe, expect, it } from "vitest";
+import { alloc, call } from "../../../index.js";
+import {
+    BOOLEAN,
+    createBox,
+    createCancellable,
+    GIO_LIB,
+    GOBJECT_BORROWED,
+    GTK_LIB,
+    INT32,
+    INT64,
+    STRING,
+    STRING_BORROWED,
+    UNDEFINED,
+} from "../utils.js";
+
+const GLIB_LIB = "libglib-2.0.so.0";
+const G_CHECKSUM_MD5 = 0;
+const DATE_TIME = { type: "boxed" as const, ownership: "full" as const, innerType: "GDateTime", library: GLIB_LIB };
+const DATE_TIME_BORROWED = { ...DATE_TIME, ownership: "borrowed" as const };
+
+describe("call - worker thread", () => {
+    it("runs thread-safe functions on a worker thread", () => {
+        const checksum = call(
+            GLIB_LIB,
+            "g_compute_checksum_for_string",
+            [
+                { type: INT32, value: G_CHECKSUM_MD5 },
+                { type: STRING_BORROWED, value: "hello" },
+                { type: INT64, value: -1 },
+            ],
+            STRING,
+            { thread: "worker" },
+        );
+
+        expect(checksum).toBe("5d41402abc4b2a76b9719d911017c592");
+    });
+
+    it("accepts thread-safe GObjects", () => {
+        const cancellable = createCancellable();
+
+        const cancelled = call(
+            GIO_LIB,
+            "g_cancellable_is_cancelled",
+            [{ type: GOBJECT_BORROWED, value: cancellable }],
+            BOOLEAN,
+            { thread: "worker" },
+        );
+
+        expect(cancelled).toBe(false);
+    });
+
+    it("refuses GObjects bound to the GTK thread", () => {
+        const box = createBox();
+
+        expect(() =>
+            call(GTK_LIB, "gtk_widget_show", [{ type: GOBJECT_BORROWED, value: box }], UNDEFINED, {
+                thread: "worker",
+            }),
+        ).toThrow("not thread-safe");
+    });
+
+    it("accepts thread-safe boxed types", () => {
+        const dateTime = call(GLIB_LIB, "g_date_time_new_from_unix_utc", [{ type: INT64, value: 0 }], DATE_TIME);
+
+        const year = call(GLIB_LIB, "g_date_time_get_year", [{ type: DATE_TIME_BORROWED, value: dateTime }], INT32, {
+            thread: "worker",
+        });
+
+        expect(year).toBe(1970);
+    });
+
+    it("refuses mutable boxed types", () => {
+        const iter = alloc(80, "GtkTextIter", GTK_LIB);
+        const iterType = { type: "boxed" as const, ownership: "borrowed" as const, innerType: "GtkTextIter" };
+
+        expect(() =>
+            call(GTK_LIB, "gtk_text_iter_get_offset", [{ type: iterType, value: iter }], INT32, { thread: "worker" }),
+        ).toThrow("not thread-safe");
+    });
+
+    it("refuses callbacks", () => {
+        expect(() =>
+            call(
+                GLIB_LIB,
+                "g_idle_add",
+                [
+                    {
+                        type: { type: "callback", kind: "closure", argTypes: [], returnType: BOOLEAN },
+                        value: () => false,
+                    },
+                ],
+                INT32,
+                { thread: "worker" },
+            ),
+        ).toThrow("Callbacks cannot be passed to worker thread calls");
+    });
+
+    it("rejects unknown thread names", () => {
+        expect(() =>
+            call(GLIB_LIB, "g_get_prgname", [], STRING_BORROWED, {
+                thread: "main" as never,
+            }),
+        ).toThrow("Invalid call thread");
+    });
+});
@@ -0,0 +1,55 @@
+import { describe, expect, it } from "vitest";
+import { alloc, debugHandles, diffHandles, getNativeId, release } from "../../index.js";
+import { createLabel, GDK_LIB } from "./utils.js";
+
+describe("debugHandles", () => {
+    it("reports live GObject handles by type name", () => {
+        const label = createLabel("Snapshot");
+
+        const snapshot = debugHandles();
+        const info = snapshot.handles.find((handle) => handle.type === "GtkLabel");
+
+        expect(snapshot.total).toBe(snapshot.handles.length);
+        expect(snapshot.counts.gobject.GtkLabel).toBeGreaterThanOrEqual(1);
+        expect(info?.kind).toBe("gobject");
+        expect(info?.refCount).toBeGreaterThanOrEqual(1);
+        expect(info?.ageMs).toBeGreaterThanOrEqual(0);
+
+        release(label);
+    });
+
+    it("reports boxed handles by type name", () => {
+        const rgba = alloc(16, "GdkRGBA", GDK_LIB);
+
+        const snapshot = debugHandles();
+
+        expect(snapshot.counts.boxed.GdkRGBA).toBeGreaterThanOrEqual(1);
+
+        release(rgba);
+    });
+
+    it("diffs snapshots taken before and after creating handles", () => {
+        const before = debugHandles();
+        const label = createLabel("Added");
+        const after = debugHandles();
+
+        const diff = diffHandles(before, after);
+
+        expect(diff.added.some((handle) => handle.type === "GtkLabel")).toBe(true);
+        expect(diff.counts.gobject.GtkLabel).toBe(1);
+
+        release(label);
+        const diffAfterRelease = diffHandles(after, debugHandles());
+
+        expect(diffAfterRelease.removed.some((handle) => handle.type === "GtkLabel")).toBe(true);
+        expect(diffAfterRelease.counts.gobject.GtkLabel).toBe(-1);
+    });
+
+    it("omits stacks when GTKX_DEBUG_HANDLES is not set", () => {
+        const rgba = alloc(16, "GdkRGBA", GDK_LIB);
+        const snapshot = debugHandles();
+
+        expect(snapshot.handles.every((handle) => handle.stack === undefined)).toBe(true);
+        expect(getNativeId(rgba)).toBeGreaterThan(0);
+    });
+});
@@ -0,0 +1,203 @@
+import { describe, expect, it } from "vitest";
+import { alloc, defineStruct, defineUnion, read, write } from "../../index.js";
+import { FLOAT32, FLOAT64, INT8, INT32, STRING, UINT8, UINT32, UINT64 } from "./utils.js";
+
+describe("defineStruct", () => {
+    it("computes offsets, padding and size", () => {
+        const layout = defineStruct([
+            { name: "flag", type: UINT8 },
+            { name: "count", type: INT32 },
+            { name: "value", type: FLOAT64 },
+        ]);
+
+        expect(layout.fields.flag?.offset).toBe(0);
+        expect(layout.fields.count?.offset).toBe(4);
+        expect(layout.fields.flag?.type).toEqual({ type: "boolean", size: 32 });
+        expect(layout.fields.value?.offset).toBe(8);
+        expect(layout.size).toBe(16);
+        expect(layout.alignment).toBe(8);
+    });
+
+    it("matches the layout of GdkRGBA", () => {
+        const layout = defineStruct(
+            ["red", "green", "blue", "alpha"].map((name) => ({ name, type: FLOAT32 })),
+            "GdkRGBA",
+        );
+
+        expect(layout.size).toBe(16);
+        expect(layout.fields.alpha?.offset).toBe(12);
+        expect(layout.type).toMatchObject({ type: "struct", innerType: "GdkRGBA", size: 16, alignment: 4 });
+    });
+
+    it("reads and writes fields by name", () => {
+        const layout = defineStruct([
+            { name: "id", type: INT32 },
+            { name: "label", type: STRING },
+        ]);
+        const record = alloc(layout.size);
+
+        write(record, layout, "id", 42);
+        write(record, layout, "label", "Named");
+
+        expect(read(record, layout, "id")).toBe(42);
+        expect(read(record, layout, "label")).toBe("Named");
+
+        write(record, layout, "label", null);
+    });
+
+    it("embeds a previously defined struct", () => {
+        const point = defineStruct([
+            { name: "x", type: FLOAT64 },
+            { name: "y", type: FLOAT64 },
+        ]);
+        const line = defineStruct([
+            { name: "id", type: INT32 },
+            { name: "start", type: point.type },
+            { name: "end", type: point.type },
+        ]);
+
+        expect(line.fields.start?.offset).toBe(8);
+        expect(line.fields.end?.offset).toBe(24);
+        expect(line.size).toBe(40);
+
+        const record = alloc(line.size);
+        const end = read(record, line, "end");
+        write(end, point, "y", 2.5, { allowBorrowed: true });
+
+        expect(read(record, FLOAT64, 32)).toBe(2.5);
+    });
+
+    it("writes through views of structs embedded in owned memory", () => {
+        const point = defineStruct([
+            { name: "x", type: INT32 },
+            { name: "y", type: INT32 },
+        ]);
+        const line = defineStruct([
+            { name: "start", type: point.type },
+            { name: "end", type: point.type },
+        ]);
+        const record = alloc(line.size);
+
+        write(read(record, line, "end"), point, "y", 7);
+
+        expect(read(record, INT32, 12)).toBe(7);
+    });
+
+    it("lays out fixed arrays inline", () => {
+        const layout = defineStruct([
+            { name: "tag", type: UINT8 },
+            {
+                name: "data",
+                type: { type: "array", itemType: FLOAT32, kind: "fixed", ownership: "borrowed", fixedSize: 4 },
+            },
+        ]);
+
+        expect(layout.fields.data?.offset).toBe(4);
+        expect(layout.fields.data?.size).toBe(16);
+        expect(layout.size).toBe(20);
+    });
+
+    it("rejects unknown field names", () => {
+        const layout = defineStruct([{ name: "id", type: INT32 }]);
+        const record = alloc(layout.size);
+
+        expect(() => read(record, layout, "missing")).toThrow("Unknown struct field 'missing'");
+        expect(() => read(record, layout, "constructor")).toThrow("Unknown struct field 'constructor'");
+        expect(() => read(record, layout, "__proto__")).toThrow("Unknown struct field '__proto__'");
+    });
+
+    it("reads and writes boolean fields as gboolean", () => {
+        const layout = defineStruct([
+            { name: "flag", type: { type: "boolean" } },
+            { name: "count", type: INT32 },
+        ]);
+        const record = alloc(layout.size);
+
+        write(record, INT32, 0, 0x100);
+        expect(read(record, layout, "flag")).toBe(true);
+
+        write(record, layout, "flag", false);
+        expect(read(record, INT32, 0)).toBe(0);
+        expect(layout.fields.count?.offset).toBe(4);
+    });
+
+    it("rejects duplicate fields", () => {
+        expect(() =>
+            defineStruct([
+                { name: "id", type: INT32 },
+                { name: "id", type: INT32 },
+            ]),
+        ).toThrow("Duplicate field 'id'");
+    });
+
+    it("packs bitfields like PangoGlyphVisAttr", () => {
+        const layout = defineStruct([
+            { name: "is_cluster_start", type: UINT32, bits: 1 },
+            { name: "is_color", type: UINT32, bits: 1 },
+        ]);
+
+        expect(layout.size).toBe(4);
+        expect(layout.fields.is_color).toMatchObject({ offset: 0, type: { bitOffset: 1, bitWidth: 1 } });
+    });
+
+    it("reads and writes bitfields without touching their neighbors", () => {
+        const layout = defineStruct([
+            { name: "low", type: UINT32, bits: 3 },
+            { name: "mid", type: UINT32, bits: 5 },
+            { name: "signed", type: INT8, bits: 4 },
+        ]);
+        const record = alloc(layout.size);
+
+        write(record, layout, "low", 5);
+        write(record, layout, "mid", 31);
+        write(record, layout, "signed", -3);
+
+        expect(read(record, layout, "low")).toBe(5);
+        expect(read(record, layout, "mid")).toBe(31);
+        expect(read(record, layout, "signed")).toBe(-3);
+        expect(read(record, UINT8, 0)).toBe(0b1111_1101);
+
+        write(record, layout, "mid", 0);
+        expect(read(record, layout, "low")).toBe(5);
+    });
+
+    it("rejects values that do not fit in a bitfield", () => {
+        const layout = defineStruct([{ name: "flags", type: UINT32, bits: 2 }]);
+        const record = alloc(layout.size);
+
+        expect(() => write(record, layout, "flags", 4)).toThrow("does not fit in a 2-bit unsigned bitfield");
+    });
+
+    it("overlaps union variants at offset 0", () => {
+        const data = defineUnion(
+            [
+                { name: "v_int", type: INT32 },
+                { name: "v_uint64", type: UINT64 },
+                { name: "v_double", type: FLOAT64 },
+            ],
+            "GValueData",
+        );
+
+        expect(data.size).toBe(8);
+        expect(Object.values(data.fields).map((field) => field.offset)).toEqual([0, 0, 0]);
+        expect(data.type).toMatchObject({ innerType: "GValueData", size: 8, alignment: 8 });
+    });
+
+    it("accesses union variants embedded in a struct", () => {
+        const data = defineUnion([
+            { name: "v_int", type: INT32 },
+            { name: "v_double", type: FLOAT64 },
+        ]);
+        const value = defineStruct([
+            { name: "g_type", type: UINT64 },
+            { name: "data", type: data.type },
+        ]);
+        const record = alloc(value.size);
+
+        const view = read(record, value, "data");
+        write(view, data, "v_double", 1.5, { allowBorrowed: true });
+
+        expect(read(view, data, "v_double")).toBe(1.5);
+        expect(read(record, FLOAT64, 8)).toBe(1.5);
+    });
+});
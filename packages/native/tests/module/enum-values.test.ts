@@ -0,0 +1,38 @@
+import { describe, expect, it } from "vitest";
+import { enumValues } from "../../index.js";
+import { GOBJECT_LIB, GTK_LIB } from "./utils.js";
+
+describe("enumValues", () => {
+    it("lists the members of an enum type in declaration order", () => {
+        const align = enumValues(GTK_LIB, "gtk_align_get_type");
+
+        expect(align.typeName).toBe("GtkAlign");
+        expect(align.flags).toBe(false);
+        expect(align.values.slice(0, 4)).toEqual([
+            { name: "GTK_ALIGN_FILL", nick: "fill", value: 0 },
+            { name: "GTK_ALIGN_START", nick: "start", value: 1 },
+            { name: "GTK_ALIGN_END", nick: "end", value: 2 },
+            { name: "GTK_ALIGN_CENTER", nick: "center", value: 3 },
+        ]);
+    });
+
+    it("marks flags types", () => {
+        const bindingFlags = enumValues(GOBJECT_LIB, "g_binding_flags_get_type");
+
+        expect(bindingFlags.typeName).toBe("GBindingFlags");
+        expect(bindingFlags.flags).toBe(true);
+        expect(bindingFlags.values).toContainEqual({
+            name: "G_BINDING_SYNC_CREATE",
+            nick: "sync-create",
+            value: 2,
+        });
+    });
+
+    it("throws for a type that is not an enum or flags type", () => {
+        expect(() => enumValues(GTK_LIB, "gtk_widget_get_type")).toThrow(/not an enum or flags type/);
+    });
+
+    it("throws for a missing get-type function", () => {
+        expect(() => enumValues(GTK_LIB, "gtk_no_such_enum_get_type")).toThrow(/Failed to find symbol/);
+    });
+});
@@ -1,6 +1,28 @@
 import { describe, expect, it } from "vitest";
-import { alloc, read, write } from "../../index.js";
-import { GDK_LIB } from "./utils.js";
+import { alloc, call, read, readPointer, release, write, writePointer } from "../../index.js";
+import {
+    BOOLEAN,
+    createLabel,
+    FLOAT32,
+    GDK_LIB,
+    GOBJECT,
+    GOBJECT_BORROWED,
+    GTK_LIB,
+    getRefCount,
+    INT32,
+    INT64,
+    STRING,
+    STRING_BORROWED,
+    UINT8,
+} from "./utils.js";
+
+const RGBA_FULL = { type: "boxed" as const, ownership: "full" as const, innerType: "GdkRGBA", library: GDK_LIB };
+const RGBA_BORROWED = { ...RGBA_FULL, ownership: "borrowed" as const };
+const RGBA_STRUCT = { type: "struct" as const, ownership: "borrowed" as const, innerType: "GdkRGBA", size: 16 };
+
+function fixedArray(itemType: typeof FLOAT32 | typeof UINT8, fixedSize: number) {
+    return { type: "array" as const, itemType, kind: "fixed" as const, ownership: "borrowed" as const, fixedSize };
+}
 
 describe("read and write", () => {
     describe("float fields", () => {
@@ -80,6 +102,19 @@ describe("read and write", () => {
         });
     });
 
+    describe("boolean fields", () => {
+        it("reads and writes one byte unless the size says gboolean", () => {
+            const rgba = alloc(16, "GdkRGBA", GDK_LIB);
+
+            write(rgba, BOOLEAN, 15, true);
+            expect(read(rgba, UINT8, 15)).toBe(1);
+
+            write(rgba, INT32, 0, 0x100);
+            expect(read(rgba, BOOLEAN, 0)).toBe(false);
+            expect(read(rgba, { type: "boolean", size: 32 }, 0)).toBe(true);
+        });
+    });
+
     describe("edge cases", () => {
         it("overwrites existing values", () => {
             const rgba = alloc(16, "GdkRGBA", GDK_LIB);
@@ -99,4 +134,242 @@ describe("read and write", () => {
             expect(result).toBe(0.0);
         });
     });
+
+    describe("pointer fields", () => {
+        it("writes and reads an owned string", () => {
+            const record = alloc(16);
+
+            write(record, STRING, 0, "first");
+            write(record, STRING, 0, "second");
+
+            expect(read(record, STRING_BORROWED, 0)).toBe("second");
+
+            write(record, STRING, 0, null);
+            expect(read(record, STRING_BORROWED, 0)).toBeNull();
+        });
+
+        it("rejects borrowed strings other than null", () => {
+            const record = alloc(16);
+
+            expect(() => write(record, STRING_BORROWED, 8, "borrowed")).toThrow("Use 'full' ownership");
+            expect(read(record, STRING_BORROWED, 8)).toBeNull();
+
+            write(record, STRING_BORROWED, 8, null);
+            expect(read(record, STRING_BORROWED, 8)).toBeNull();
+        });
+
+        it("references a GObject written with full ownership", () => {
+            const record = alloc(16);
+            const label = createLabel("Field");
+            const initial = getRefCount(label);
+
+            write(record, GOBJECT, 0, label);
+            expect(getRefCount(label)).toBe(initial + 1);
+
+            write(record, GOBJECT, 0, null);
+            expect(getRefCount(label)).toBe(initial);
+        });
+
+        it("does not reference a borrowed GObject", () => {
+            const record = alloc(16);
+            const label = createLabel("Field");
+            const initial = getRefCount(label);
+
+            write(record, GOBJECT_BORROWED, 0, label);
+
+            expect(getRefCount(label)).toBe(initial);
+            expect(read(record, GOBJECT_BORROWED, 0)).toBeDefined();
+        });
+
+        it("copies a boxed value written with full ownership", () => {
+            const record = alloc(16);
+            const rgba = alloc(16, "GdkRGBA", GDK_LIB);
+            write(rgba, FLOAT32, 12, 0.5);
+
+            write(record, RGBA_FULL, 0, rgba);
+            write(rgba, FLOAT32, 12, 1.0);
+
+            const copy = read(record, RGBA_BORROWED, 0);
+            expect(read(copy, FLOAT32, 12)).toBeCloseTo(0.5);
+
+            write(record, RGBA_FULL, 0, null);
+        });
+
+        it("rejects values of the wrong type", () => {
+            const record = alloc(16);
+            const rgba = alloc(16, "GdkRGBA", GDK_LIB);
+
+            expect(() => write(record, GOBJECT, 0, rgba)).toThrow("Expected");
+            expect(() => write(record, STRING, 0, 42)).toThrow("Expected a String");
+        });
+    });
+
+    describe("embedded structs", () => {
+        it("copies the struct into the field", () => {
+            const record = alloc(32);
+            const rgba = alloc(16, "GdkRGBA", GDK_LIB);
+            write(rgba, FLOAT32, 4, 0.25);
+
+            write(record, RGBA_STRUCT, 16, rgba);
+
+            expect(read(record, FLOAT32, 20)).toBeCloseTo(0.25);
+        });
+
+        it("reads the struct as a view into the parent", () => {
+            const record = alloc(32);
+            write(record, FLOAT32, 20, 0.75);
+
+            const view = read(record, RGBA_STRUCT, 16);
+
+            expect(read(view, FLOAT32, 4)).toBeCloseTo(0.75);
+            expect(() => read(view, FLOAT32, 16)).toThrow("out of bounds");
+        });
+
+        it("keeps the parent alive while the view exists", () => {
+            const record = alloc(32);
+            write(record, FLOAT32, 16, 0.5);
+            const view = read(record, RGBA_STRUCT, 16);
+
+            release(record);
+
+            expect(read(view, FLOAT32, 0)).toBeCloseTo(0.5);
+        });
+
+        it("keeps the parent alive while a readPointer view exists", () => {
+            const record = alloc(32);
+            write(record, FLOAT32, 20, 0.25);
+
+            const target = read(record, RGBA_STRUCT, 16);
+            write(record, RGBA_BORROWED, 0, target);
+            release(target);
+
+            const element = readPointer(record, 0, 4);
+            release(record);
+
+            expect(read(element, FLOAT32, 0)).toBeCloseTo(0.25);
+        });
+
+        it("requires a size", () => {
+            const record = alloc(32);
+            const rgba = alloc(16, "GdkRGBA", GDK_LIB);
+
+            const unsized = { type: "struct" as const, ownership: "borrowed" as const, innerType: "GdkRGBA" };
+
+            expect(() => write(record, unsized, 0, rgba)).toThrow("requires a size");
+        });
+    });
+
+    describe("fixed arrays", () => {
+        it("writes and reads an inline float array", () => {
+            const matrix = alloc(64);
+
+            write(matrix, fixedArray(FLOAT32, 4), 16, [1, 2, 3, 4]);
+
+            expect(read(matrix, fixedArray(FLOAT32, 4), 16)).toEqual([1, 2, 3, 4]);
+            expect(read(matrix, FLOAT32, 28)).toBe(4);
+        });
+
+        it("writes and reads an inline byte array", () => {
+            const record = alloc(8);
+
+            write(record, fixedArray(UINT8, 4), 0, [1, 2, 254, 255]);
+
+            expect(read(record, fixedArray(UINT8, 4), 0)).toEqual([1, 2, 254, 255]);
+        });
+
+        it("requires exactly fixedSize elements", () => {
+            const record = alloc(16);
+
+            expect(() => write(record, fixedArray(FLOAT32, 4), 0, [1, 2])).toThrow("Expected 4 elements");
+        });
+
+        it("rejects arrays that extend past the allocation", () => {
+            const record = alloc(16);
+
+            expect(() => read(record, fixedArray(FLOAT32, 4), 4)).toThrow("out of bounds");
+        });
+
+        it("rejects arrays that are not fixed-size", () => {
+            const record = alloc(16);
+            const array = { ...fixedArray(FLOAT32, 4), kind: "array" as const };
+
+            expect(() => read(record, array, 0)).toThrow("Only fixed-size arrays");
+        });
+    });
+
+    describe("safety checks", () => {
+        it("rejects reads past the end of the allocation", () => {
+            const rgba = alloc(16, "GdkRGBA", GDK_LIB);
+
+            expect(() => read(rgba, FLOAT32, 16)).toThrow("out of bounds");
+            expect(() => read(rgba, INT64, 12)).toThrow("out of bounds");
+        });
+
+        it("rejects writes past the end of the allocation", () => {
+            const rgba = alloc(16, "GdkRGBA", GDK_LIB);
+
+            expect(() => write(rgba, INT32, 14, 1)).toThrow("out of bounds");
+            expect(read(rgba, FLOAT32, 12)).toBe(0.0);
+        });
+
+        it("rejects pointer fields past the end of the allocation", () => {
+            const rect = alloc(16, "GdkRectangle", GDK_LIB);
+            const source = alloc(16, "GdkRectangle", GDK_LIB);
+
+            expect(() => readPointer(rect, 12, 0)).toThrow("out of bounds");
+            expect(() => writePointer(rect, 12, 0, source, 16)).toThrow("out of bounds");
+        });
+
+        it("rejects copying more bytes than the source holds", () => {
+            const rect = alloc(16, "GdkRectangle", GDK_LIB);
+            const source = alloc(16, "GdkRectangle", GDK_LIB);
+
+            expect(() => writePointer(rect, 0, 0, source, 32)).toThrow("out of bounds");
+        });
+
+        it("refuses writes through borrowed handles", () => {
+            const language = call(GTK_LIB, "gtk_get_default_language", [], {
+                type: "struct",
+                ownership: "borrowed",
+                innerType: "PangoLanguage",
+                size: 8,
+            });
+
+            expect(() => write(language, INT32, 0, 0)).toThrow("borrowed handle");
+        });
+
+        it("refuses writes through views of structs embedded in borrowed memory", () => {
+            const language = call(GTK_LIB, "gtk_get_default_language", [], {
+                type: "struct",
+                ownership: "borrowed",
+                innerType: "PangoLanguage",
+                size: 8,
+            });
+            const view = read(language, { type: "struct", ownership: "borrowed", innerType: "Head", size: 4 }, 0);
+
+            expect(() => write(view, INT32, 0, 0)).toThrow("borrowed handle");
+        });
+
+        it("writes through borrowed handles with allowBorrowed", () => {
+            const language = call(GTK_LIB, "gtk_get_default_language", [], {
+                type: "struct",
+                ownership: "borrowed",
+                innerType: "PangoLanguage",
+                size: 8,
+            });
+            const value = read(language, INT32, 0);
+
+            write(language, INT32, 0, value, { allowBorrowed: true });
+
+            expect(read(language, INT32, 0)).toBe(value);
+        });
+
+        it("allows writes through owned handles", () => {
+            const rgba = alloc(16, "GdkRGBA", GDK_LIB);
+
+            write(rgba, FLOAT32, 12, 1.0, { allowBorrowed: false });
+
+            expect(read(rgba, FLOAT32, 12)).toBeCloseTo(1.0);
+        });
+    });
 });
@@ -0,0 +1,88 @@
+import { describe, expect, it } from "vitest";
+import { alloc, call, disposable, getNativeId, read, release } from "../../index.js";
+import {
+    createBox,
+    createLabel,
+    GDK_LIB,
+    GOBJECT_BORROWED,
+    GTK_LIB,
+    getRefCount,
+    STRING_BORROWED,
+    UNDEFINED,
+} from "./utils.js";
+
+describe("release", () => {
+    it("returns true when releasing a live handle", () => {
+        const label = createLabel("Release me");
+
+        expect(release(label)).toBe(true);
+    });
+
+    it("returns false when releasing the same handle twice", () => {
+        const rgba = alloc(16, "GdkRGBA", GDK_LIB);
+
+        expect(release(rgba)).toBe(true);
+        expect(release(rgba)).toBe(false);
+    });
+
+    it("drops the GObject reference held by the handle", () => {
+        const box = createBox();
+        const label = createLabel("Child");
+
+        call(
+            GTK_LIB,
+            "gtk_box_append",
+            [
+                { type: GOBJECT_BORROWED, value: box },
+                { type: GOBJECT_BORROWED, value: label },
+            ],
+            UNDEFINED,
+        );
+
+        const child = call(GTK_LIB, "gtk_widget_get_first_child", [{ type: GOBJECT_BORROWED, value: box }], {
+            type: "gobject",
+            ownership: "borrowed",
+        });
+        const before = getRefCount(child);
+
+        release(label);
+
+        expect(getRefCount(child)).toBe(before - 1);
+    });
+
+    it("throws a released error when a released handle is used in a call", () => {
+        const label = createLabel("Gone");
+        release(label);
+
+        expect(() =>
+            call(
+                GTK_LIB,
+                "gtk_label_set_text",
+                [
+                    { type: GOBJECT_BORROWED, value: label },
+                    { type: STRING_BORROWED, value: "Again" },
+                ],
+                UNDEFINED,
+            ),
+        ).toThrow(/has been released/);
+    });
+
+    it("throws a released error when reading from a released handle", () => {
+        const rgba = alloc(16, "GdkRGBA", GDK_LIB);
+        release(rgba);
+
+        expect(() => read(rgba, { type: "float", size: 32 }, 0)).toThrow(/has been released/);
+        expect(() => getNativeId(rgba)).toThrow(/has been released/);
+    });
+
+    it("releases the handle at the end of a using block", () => {
+        const rgba = alloc(16, "GdkRGBA", GDK_LIB);
+
+        {
+            using wrapped = disposable(rgba);
+            expect(wrapped.handle).toBe(rgba);
+        }
+
+        expect(release(rgba)).toBe(false);
+    });
+});
@@ -0,0 +1,96 @@
+import { describe, expect, it } from "vitest";
+import { addIdle, addTimeout, addUnixFdWatch, call, removeSource } from "../../index.js";
+import { BOOLEAN, GLIB_LIB, UINT32 } from "./utils.js";
+
+const G_IO_OUT = 4;
+const STDOUT_FD = 1;
+
+/** Waits for `promise`, failing if it has not settled within `ms`. */
+async function within<T>(promise: Promise<T>, ms = 1000): Promise<T> {
+    let timer: NodeJS.Timeout | undefined;
+    const timeout = new Promise<never>((_, reject) => {
+        timer = setTimeout(() => reject(new Error(`Source callback did not run within ${ms}ms`)), ms);
+    });
+
+    try {
+        return await Promise.race([promise, timeout]);
+    } finally {
+        clearTimeout(timer);
+    }
+}
+
+describe("main loop sources", () => {
+    it("runs a timeout once when the callback does not return true", async () => {
+        let calls = 0;
+        let id = 0;
+
+        const ran = new Promise<void>((resolve) => {
+            id = addTimeout(5, () => {
+                calls++;
+                resolve();
+                return false;
+            });
+        });
+
+        expect(id).toBeGreaterThan(0);
+        await within(ran);
+        expect(calls).toBe(1);
+        expect(removeSource(id)).toBe(false);
+    });
+
+    it("keeps a timeout installed while the callback returns true", async () => {
+        let calls = 0;
+        let id = 0;
+
+        const ranTwice = new Promise<void>((resolve) => {
+            id = addTimeout(5, () => {
+                calls++;
+                if (calls === 2) {
+                    resolve();
+                }
+                return true;
+            });
+        });
+
+        await within(ranTwice);
+        expect(removeSource(id)).toBe(true);
+        expect(removeSource(id)).toBe(false);
+        expect(calls).toBeGreaterThan(1);
+    });
+
+    it("treats a source removed outside removeSource as already gone", () => {
+        const id = addTimeout(1000, () => true);
+
+        call(GLIB_LIB, "g_source_remove", [{ type: UINT32, value: id }], BOOLEAN);
+
+        expect(removeSource(id)).toBe(false);
+    });
+
+    it("runs idle callbacks", async () => {
+        const done = new Promise<void>((resolve) => {
+            addIdle(() => {
+                resolve();
+                return false;
+            });
+        });
+
+        await expect(within(done)).resolves.toBeUndefined();
+    });
+
+    it("reports ready conditions for fd watches", async () => {
+        const ready = new Promise<[number, number]>((resolve) => {
+            addUnixFdWatch(STDOUT_FD, G_IO_OUT, (fd, conditions) => {
+                resolve([fd, conditions]);
+                return false;
+            });
+        });
+
+        const [fd, conditions] = await within(ready);
+        expect(fd).toBe(STDOUT_FD);
+        expect(conditions & G_IO_OUT).toBe(G_IO_OUT);
+    });
+
+    it("returns false when removing an unknown source", () => {
+        expect(removeSource(0xffffff)).toBe(false);
+    });
+});
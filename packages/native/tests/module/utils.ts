@@ -6,6 +6,7 @@ const GOBJECT_REF_COUNT_OFFSET = 8;
 
 export const GTK_LIB = "libgtk-4.so.1";
 export const GDK_LIB = "libgtk-4.so.1";
+export const GLIB_LIB = "libglib-2.0.so.0";
 export const GOBJECT_LIB = "libgobject-2.0.so.0";
 export const GIO_LIB = "libgio-2.0.so.0";
 export const PANGO_LIB = "libpango-1.0.so.0";
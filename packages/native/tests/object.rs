@@ -7,7 +7,8 @@ use gtk4::glib;
 use gtk4::prelude::{ObjectType as _, StaticType as _};
 
 use native::Boxed;
-use native::managed::{NativeHandle, NativeValue};
+use native::diagnostics::HandleKind;
+use native::managed::{HandleTypeError, NativeHandle, NativeValue};
 use native::state::GtkThreadState;
 
 fn create_test_gobject() -> glib::Object {
@@ -163,3 +164,88 @@ fn multiple_handles_independent() {
     assert_eq!(handle1.get_ptr(), None);
     assert!(handle2.get_ptr().is_some());
 }
+
+#[test]
+fn handle_release_removes_from_map() {
+    let obj = create_test_gobject();
+    let object = NativeValue::GObject(obj);
+    let handle: NativeHandle = object.into();
+
+    assert!(handle.release());
+    assert_eq!(handle.get_ptr(), None);
+    assert!(handle.is_released());
+}
+
+#[test]
+fn handle_release_is_idempotent() {
+    let obj = create_test_gobject();
+    let object = NativeValue::GObject(obj);
+    let handle: NativeHandle = object.into();
+
+    assert!(handle.release());
+    assert!(!handle.release());
+}
+
+#[test]
+fn handle_release_drops_gobject_reference() {
+    let obj = create_test_gobject();
+    let obj_ptr = obj.as_ptr();
+    let object = NativeValue::GObject(obj.clone());
+    let handle: NativeHandle = object.into();
+
+    let before = common::get_gobject_refcount(obj_ptr);
+    handle.release();
+    let after = common::get_gobject_refcount(obj_ptr);
+
+    assert_eq!(after, before - 1);
+}
+
+#[test]
+fn check_type_accepts_expected_type_and_subtypes() {
+    let object = NativeValue::GObject(create_test_gobject());
+
+    assert!(object.check_type(HandleKind::GObject, None).is_ok());
+    assert!(
+        object
+            .check_type(HandleKind::GObject, Some("GObject"))
+            .is_ok()
+    );
+    assert!(
+        object
+            .check_type(HandleKind::GObject, Some("GInitiallyUnowned"))
+            .is_err()
+    );
+}
+
+#[test]
+fn check_type_reports_kind_and_type_mismatches() {
+    let object = NativeValue::GObject(create_test_gobject());
+
+    let err = object
+        .check_type(HandleKind::Boxed, Some("GdkRGBA"))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        HandleTypeError {
+            expected_kind: HandleKind::Boxed,
+            expected_type: Some("GdkRGBA".to_string()),
+            actual_kind: HandleKind::GObject,
+            actual_type: Some("GObject".to_string()),
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "Expected boxed GdkRGBA, got gobject GObject"
+    );
+}
+
+#[test]
+fn check_type_rejects_unknown_type_names() {
+    let object = NativeValue::GObject(create_test_gobject());
+
+    assert!(
+        object
+            .check_type(HandleKind::GObject, Some("NoSuchType"))
+            .is_err()
+    );
+}
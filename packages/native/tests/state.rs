@@ -1,5 +1,7 @@
 mod common;
 
+use gtk4::glib;
+use native::managed::{Boxed, HandlePin, NativeValue, ParentRef};
 use native::state::GtkThreadState;
 
 #[test]
@@ -88,3 +90,82 @@ fn get_library_tries_comma_separated_names() {
 
     assert!(success);
 }
+
+#[test]
+fn drain_handles_for_teardown_empties_map_and_marks_released() {
+    common::ensure_gtk_init();
+
+    let (object_id, other_id) = GtkThreadState::with(|state| {
+        let object_id =
+            state.insert_handle(NativeValue::GObject(glib::Object::new::<glib::Object>()));
+        let other_id =
+            state.insert_handle(NativeValue::GObject(glib::Object::new::<glib::Object>()));
+        (object_id, other_id)
+    });
+
+    let drained = GtkThreadState::with(|state| state.drain_handles_for_teardown());
+
+    assert!(drained.len() >= 2);
+    drop(drained);
+
+    GtkThreadState::with(|state| {
+        assert!(state.handle_map.is_empty());
+        assert!(state.released_handles.contains(&object_id));
+        assert!(state.released_handles.contains(&other_id));
+    });
+}
+
+#[test]
+fn pinned_handle_outlives_removal_until_unpinned() {
+    common::ensure_gtk_init();
+
+    let ptr = unsafe { glib::ffi::g_malloc0(16) };
+    let boxed = Boxed::from_glib_full(None, ptr).with_size(Some(16));
+    let id = GtkThreadState::with(|state| state.insert_handle(NativeValue::Boxed(boxed)));
+    let pin = GtkThreadState::with(|state| HandlePin::new(state, id));
+
+    let removed = GtkThreadState::with(|state| state.remove_handle(id));
+
+    assert!(removed.is_none());
+    GtkThreadState::with(|state| {
+        assert!(!state.handle_map.contains_key(&id));
+        assert!(state.orphaned_handles.contains_key(&id));
+    });
+
+    drop(pin);
+
+    GtkThreadState::with(|state| {
+        assert!(!state.orphaned_handles.contains_key(&id));
+        assert!(!state.handle_pins.contains_key(&id));
+    });
+}
+
+#[test]
+fn parent_ref_reports_parent_freed_after_teardown() {
+    common::ensure_gtk_init();
+
+    let ptr = unsafe { glib::ffi::g_malloc0(16) };
+    let boxed = Boxed::from_glib_full(None, ptr).with_size(Some(16));
+    let id = GtkThreadState::with(|state| state.insert_handle(NativeValue::Boxed(boxed)));
+    let parent = GtkThreadState::with(|state| ParentRef::Handle(HandlePin::new(state, id)));
+
+    assert!(!GtkThreadState::with(|state| parent.is_freed(state)));
+
+    let drained = GtkThreadState::with(|state| state.drain_handles_for_teardown());
+    drop(drained);
+
+    assert!(GtkThreadState::with(|state| parent.is_freed(state)));
+}
+
+#[test]
+fn take_libraries_for_teardown_keeps_libraries_without_teardown() {
+    common::ensure_gtk_init();
+
+    let taken = GtkThreadState::with(|state| {
+        let _ = state.library("libglib-2.0.so.0");
+        state.take_libraries_for_teardown()
+    });
+
+    assert!(taken.is_empty());
+    GtkThreadState::with(|state| assert!(state.libraries.contains_key("libglib-2.0.so.0")));
+}
@@ -3,13 +3,18 @@ mod common;
 use std::ffi::c_void;
 
 use gtk4::gdk;
+use gtk4::gio;
 use gtk4::glib;
-use gtk4::glib::translate::IntoGlib as _;
+use gtk4::glib::translate::{IntoGlib as _, ToGlibPtr as _};
 use gtk4::prelude::ObjectType as _;
 use gtk4::prelude::StaticType as _;
 
+use native::arg::Arg;
 use native::ffi;
-use native::types::{ArrayKind, ArrayType, BoxedType, GObjectType, Ownership, StringType, Type};
+use native::types::{
+    ArrayKind, ArrayType, BoxedType, Converter, GObjectType, Ownership, StringEncoding, StringType,
+    Type,
+};
 use native::value::Value;
 
 use common::get_gobject_refcount;
@@ -25,6 +30,8 @@ fn gobject_transfer_none_does_not_take_ownership() {
 
     let gobject_type = GObjectType {
         ownership: Ownership::Borrowed,
+        type_name: None,
+        converter: None,
     };
     let type_ = Type::GObject(gobject_type);
 
@@ -53,6 +60,8 @@ fn gobject_full_transfer_takes_ownership() {
 
     let gobject_type = GObjectType {
         ownership: Ownership::Full,
+        type_name: None,
+        converter: None,
     };
     let type_ = Type::GObject(gobject_type);
 
@@ -72,6 +81,8 @@ fn gobject_null_returns_null_value() {
 
     let gobject_type = GObjectType {
         ownership: Ownership::Full,
+        type_name: None,
+        converter: None,
     };
     let type_ = Type::GObject(gobject_type);
 
@@ -99,6 +110,8 @@ fn gobject_floating_ref_gets_sunk() {
 
     let gobject_type = GObjectType {
         ownership: Ownership::Full,
+        type_name: None,
+        converter: None,
     };
     let type_ = Type::GObject(gobject_type);
 
@@ -122,6 +135,7 @@ fn string_transfer_none_does_not_free() {
     let string_type = StringType {
         ownership: Ownership::Borrowed,
         length: None,
+        encoding: None,
     };
     let type_ = Type::String(string_type);
 
@@ -139,6 +153,41 @@ fn string_transfer_none_does_not_free() {
     assert_eq!(still_valid.to_str().unwrap(), test_string);
 }
 
+#[test]
+fn string_encodings_decode_invalid_utf8() {
+    let c_string = std::ffi::CString::new(vec![0x66, 0xff, 0x6f]).unwrap();
+    let decode = |encoding| {
+        let string_type = StringType {
+            encoding: Some(encoding),
+            ..StringType::new(Ownership::Borrowed)
+        };
+        string_type.decode_cstr(&c_string)
+    };
+
+    assert!(decode(StringEncoding::Utf8).is_err());
+    assert!(matches!(decode(StringEncoding::Lossy), Ok(Value::String(s)) if s == "f\u{fffd}o"));
+    assert!(matches!(
+        decode(StringEncoding::Bytes),
+        Ok(Value::Bytes(bytes)) if bytes == [0x66, 0xff, 0x6f]
+    ));
+}
+
+#[test]
+fn string_bytes_encoding_accepts_byte_values() {
+    let string_type = StringType {
+        encoding: Some(StringEncoding::Bytes),
+        ..StringType::new(Ownership::Borrowed)
+    };
+
+    let c_string = string_type
+        .encode_cstring(&Value::Bytes(vec![0x66, 0xff]))
+        .unwrap();
+    assert_eq!(c_string.as_bytes(), [0x66, 0xff]);
+
+    let utf8_type = StringType::new(Ownership::Borrowed);
+    assert!(utf8_type.encode_cstring(&Value::Bytes(vec![0x66])).is_err());
+}
+
 #[test]
 fn string_full_transfer_frees_memory() {
     common::ensure_gtk_init();
@@ -150,6 +199,7 @@ fn string_full_transfer_frees_memory() {
     let string_type = StringType {
         ownership: Ownership::Full,
         length: None,
+        encoding: None,
     };
     let type_ = Type::String(string_type);
 
@@ -171,6 +221,7 @@ fn string_null_returns_null_value() {
     let string_type = StringType {
         ownership: Ownership::Full,
         length: None,
+        encoding: None,
     };
     let type_ = Type::String(string_type);
 
@@ -193,6 +244,7 @@ fn boxed_transfer_none_creates_copy() {
         type_name: "GdkRGBA".to_string(),
         library: None,
         get_type_fn: None,
+        converter: None,
     };
     let type_ = Type::Boxed(boxed_type);
 
@@ -220,6 +272,7 @@ fn boxed_full_transfer_takes_ownership() {
         type_name: "GdkRGBA".to_string(),
         library: None,
         get_type_fn: None,
+        converter: None,
     };
     let type_ = Type::Boxed(boxed_type);
 
@@ -238,6 +291,7 @@ fn boxed_null_returns_null_value() {
         type_name: "GdkRGBA".to_string(),
         library: None,
         get_type_fn: None,
+        converter: None,
     };
     let type_ = Type::Boxed(boxed_type);
 
@@ -264,6 +318,8 @@ fn glist_transfer_none_does_not_free_list() {
 
     let gobject_type = GObjectType {
         ownership: Ownership::Borrowed,
+        type_name: None,
+        converter: None,
     };
     let array_type = ArrayType {
         item_type: Box::new(Type::GObject(gobject_type)),
@@ -316,6 +372,8 @@ fn glist_full_transfer_frees_list() {
 
     let gobject_type = GObjectType {
         ownership: Ownership::Borrowed,
+        type_name: None,
+        converter: None,
     };
     let array_type = ArrayType {
         item_type: Box::new(Type::GObject(gobject_type)),
@@ -342,6 +400,8 @@ fn glist_null_returns_empty_array() {
 
     let gobject_type = GObjectType {
         ownership: Ownership::Borrowed,
+        type_name: None,
+        converter: None,
     };
     let array_type = ArrayType {
         item_type: Box::new(Type::GObject(gobject_type)),
@@ -378,6 +438,7 @@ fn strv_transfer_none_does_not_free() {
     let string_type = StringType {
         ownership: Ownership::Borrowed,
         length: None,
+        encoding: None,
     };
     let array_type = ArrayType {
         item_type: Box::new(Type::String(string_type)),
@@ -411,6 +472,31 @@ fn strv_transfer_none_does_not_free() {
     );
 }
 
+#[test]
+fn strv_decodes_invalid_utf8_lossily_unless_strict() {
+    let string = std::ffi::CString::new(vec![0x66, 0xff]).unwrap();
+    let strv = [string.as_ptr(), std::ptr::null()];
+    let decode = |encoding| {
+        let array_type = ArrayType {
+            item_type: Box::new(Type::String(StringType {
+                encoding,
+                ..StringType::new(Ownership::Borrowed)
+            })),
+            kind: ArrayKind::Array,
+            ownership: Ownership::Borrowed,
+            element_size: None,
+        };
+        let cif_value = ffi::FfiValue::Ptr(strv.as_ptr() as *mut c_void);
+        Value::from_ffi_value(&cif_value, &Type::Array(array_type))
+    };
+
+    assert!(matches!(
+        decode(None),
+        Ok(Value::Array(items)) if matches!(&items[..], [Value::String(s)] if s == "f\u{fffd}")
+    ));
+    assert!(decode(Some(StringEncoding::Utf8)).is_err());
+}
+
 #[test]
 fn strv_full_transfer_frees_strings() {
     common::ensure_gtk_init();
@@ -429,6 +515,7 @@ fn strv_full_transfer_frees_strings() {
     let string_type = StringType {
         ownership: Ownership::Full,
         length: None,
+        encoding: None,
     };
     let array_type = ArrayType {
         item_type: Box::new(Type::String(string_type)),
@@ -461,6 +548,8 @@ fn from_glib_value_gobject_transfer_none() {
 
     let gobject_type = GObjectType {
         ownership: Ownership::Borrowed,
+        type_name: None,
+        converter: None,
     };
     let type_ = Type::GObject(gobject_type);
 
@@ -482,6 +571,7 @@ fn from_glib_value_string() {
     let string_type = StringType {
         ownership: Ownership::Borrowed,
         length: None,
+        encoding: None,
     };
     let type_ = Type::String(string_type);
 
@@ -761,6 +851,32 @@ fn from_cif_value_ref_float() {
     }
 }
 
+#[test]
+fn from_cif_value_caller_allocated_ref_copies_struct() {
+    common::ensure_gtk_init();
+
+    let struct_type = native::types::StructType::new(Ownership::Full, "Pair".to_string(), Some(8));
+    let ref_type = native::types::RefType::caller_allocates(Type::Struct(struct_type), 8);
+    let type_ = Type::Ref(ref_type);
+
+    let filled: Vec<u64> = vec![u64::from_ne_bytes([1, 2, 3, 4, 5, 6, 7, 8])];
+    let cif_value = ffi::FfiValue::Storage(filled.into());
+    let result = Value::from_ffi_value(&cif_value, &type_).unwrap();
+
+    let Value::Object(handle) = result else {
+        panic!("Expected Value::Object");
+    };
+
+    let ptr = handle.get_ptr().unwrap();
+    let ffi::FfiValue::Storage(storage) = &cif_value else {
+        unreachable!();
+    };
+    assert_ne!(ptr, storage.ptr());
+
+    let bytes = unsafe { std::slice::from_raw_parts(ptr.cast::<u8>(), 8) };
+    assert_eq!(bytes, [1, 2, 3, 4, 5, 6, 7, 8]);
+}
+
 #[test]
 fn value_to_glib_value_number() {
     common::ensure_gtk_init();
@@ -852,6 +968,7 @@ fn glist_with_string_items() {
     let string_type = StringType {
         ownership: Ownership::Borrowed,
         length: None,
+        encoding: None,
     };
     let array_type = ArrayType {
         item_type: Box::new(Type::String(string_type)),
@@ -999,3 +1116,74 @@ fn from_cif_value_struct_owned_without_size() {
         panic!("Expected Value::Object for struct");
     }
 }
+
+fn converted_boxed(type_name: &str, ownership: Ownership, converter: Converter) -> Type {
+    Type::Boxed(BoxedType {
+        converter: Some(converter),
+        ..BoxedType::new(ownership, type_name.to_string(), None, None)
+    })
+}
+
+#[test]
+fn converted_date_time_full_transfer_decodes_to_date() {
+    common::ensure_gtk_init();
+
+    let date_time = glib::DateTime::from_unix_utc(1_700_000_000).unwrap();
+    let date_time = date_time.add(glib::TimeSpan(250_999)).unwrap();
+    let ptr: *mut glib::ffi::GDateTime = date_time.to_glib_full();
+
+    let type_ = converted_boxed("GDateTime", Ownership::Full, Converter::Date);
+    let ffi_value = ffi::FfiValue::Ptr(ptr as *mut c_void);
+    let result = Value::from_ffi_value(&ffi_value, &type_).unwrap();
+    assert!(matches!(result, Value::Date(ms) if ms == 1_700_000_000_250.0));
+
+    let borrowed: *mut glib::ffi::GDateTime = date_time.to_glib_none().0;
+    let type_ = converted_boxed("GDateTime", Ownership::Borrowed, Converter::BigInt);
+    let ffi_value = ffi::FfiValue::Ptr(borrowed as *mut c_void);
+    let result = Value::from_ffi_value(&ffi_value, &type_).unwrap();
+    assert!(matches!(result, Value::BigInt(micros) if micros == 1_700_000_000_250_999));
+}
+
+#[test]
+fn converted_gobject_borrowed_keeps_reference() {
+    common::ensure_gtk_init();
+
+    let file = gio::File::for_path("/tmp/gtkx/notes.txt");
+    let file_ptr = file.as_ptr() as *mut glib::gobject_ffi::GObject;
+    let initial_ref = get_gobject_refcount(file_ptr);
+
+    let type_ = Type::GObject(GObjectType {
+        converter: Some(Converter::Uri),
+        ..GObjectType::new(Ownership::Borrowed)
+    });
+    let result =
+        Value::from_ffi_value(&ffi::FfiValue::Ptr(file_ptr as *mut c_void), &type_).unwrap();
+
+    assert!(matches!(result, Value::String(uri) if uri == "file:///tmp/gtkx/notes.txt"));
+    assert_eq!(get_gobject_refcount(file_ptr), initial_ref);
+}
+
+#[test]
+fn converted_argument_lives_until_storage_is_dropped() {
+    common::ensure_gtk_init();
+
+    let type_ = converted_boxed("GBytes", Ownership::Borrowed, Converter::Bytes);
+    let arg = Arg::new(type_, Value::Bytes(vec![1, 2, 3]));
+    let encoded = ffi::FfiValue::try_from(arg).unwrap();
+
+    let ptr = encoded.as_ptr("GBytes").unwrap() as *mut glib::ffi::GBytes;
+    assert_eq!(unsafe { glib::ffi::g_bytes_get_size(ptr) }, 3);
+}
+
+#[test]
+fn converter_rejects_mismatched_values() {
+    common::ensure_gtk_init();
+
+    let type_ = converted_boxed("GDateTime", Ownership::Borrowed, Converter::BigInt);
+    let err = ffi::FfiValue::try_from(Arg::new(type_, Value::String("now".into()))).unwrap_err();
+
+    assert!(
+        err.to_string()
+            .contains("Expected a BigInt for the 'bigint' converter")
+    );
+}
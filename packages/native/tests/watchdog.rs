@@ -0,0 +1,59 @@
+use std::sync::Mutex;
+use std::time::Duration;
+
+use native::watchdog::{FrameKind, WaitThread, Watchdog};
+
+/// Tests reconfigure the global watchdog, so they must not overlap.
+static WATCHDOG_LOCK: Mutex<()> = Mutex::new(());
+
+fn has_frame(label: &str) -> bool {
+    Watchdog::global()
+        .frames()
+        .iter()
+        .any(|frame| frame.label == label)
+}
+
+#[test]
+fn watchdog_tracks_frames_only_while_enabled() {
+    let _lock = WATCHDOG_LOCK.lock().unwrap();
+    let watchdog = Watchdog::global();
+
+    watchdog.configure(None, false);
+    let untracked = watchdog.track(WaitThread::Js, FrameKind::Wait, || "untracked".into());
+    assert!(!has_frame("untracked"));
+    drop(untracked);
+
+    watchdog.configure(Some(Duration::from_secs(60)), false);
+    let tracked = watchdog.track(WaitThread::Gtk, FrameKind::Activity, || "tracked".into());
+    assert!(has_frame("tracked"));
+
+    let report = Watchdog::report(&watchdog.frames(), Duration::from_secs(60));
+    assert!(report.contains("js_wait_depth="));
+    assert!(report.contains("callback_depth="));
+    assert!(report.contains("tracked"));
+
+    drop(tracked);
+    assert!(!has_frame("tracked"));
+
+    watchdog.configure(None, false);
+}
+
+#[test]
+fn watchdog_skips_pumping_wait_frames() {
+    let _lock = WATCHDOG_LOCK.lock().unwrap();
+    let watchdog = Watchdog::global();
+    watchdog.configure(Some(Duration::from_secs(60)), false);
+
+    let js_wait = watchdog.track(WaitThread::Js, FrameKind::Wait, || "js wait".into());
+    let gtk_wait = watchdog.track(WaitThread::Gtk, FrameKind::Wait, || "gtk wait".into());
+
+    let pumping = js_wait.pump();
+    assert!(!watchdog.check(Duration::ZERO));
+    drop(pumping);
+
+    assert!(watchdog.check(Duration::ZERO));
+
+    drop(gtk_wait);
+    drop(js_wait);
+    watchdog.configure(None, false);
+}
@@ -6,21 +6,70 @@
  */
 export type NativeHandle = { readonly __brand: "NativeHandle" };
 
-type IntegerType = { type: "int"; size: 8 | 16 | 32 | 64; unsigned: boolean; library?: string; getTypeFn?: string };
+type IntegerType = {
+    type: "int";
+    size: 8 | 16 | 32 | 64;
+    unsigned: boolean;
+    library?: string;
+    getTypeFn?: string;
+    /** For bitfield fields: first bit within the storage unit, from the least significant bit (default 0) */
+    bitOffset?: number;
+    /** For bitfield fields: number of bits; makes `read`/`write` access only these bits */
+    bitWidth?: number;
+    /**
+     * Exchange enum values as nicknames (`"center"`) and flags as arrays of nicknames (`["bold", "italic"]`).
+     * Requires `library` and `getTypeFn`. Numbers are still accepted, and values without a name read as numbers.
+     */
+    symbolic?: boolean;
+};
 
 type FloatType = { type: "float"; size: 32 | 64 };
 
-type BooleanType = { type: "boolean" };
+/**
+ * A one-byte `bool`, or with `size: 32` a C `gboolean`. `defineStruct` lays out booleans without
+ * a size as `gboolean`.
+ */
+type BooleanType = { type: "boolean"; size?: 8 | 32 };
 
 type Ownership = "full" | "borrowed";
 
-type StringType = { type: "string"; ownership: Ownership; length?: number };
+/**
+ * How a C string converts to JavaScript: `utf8` rejects invalid UTF-8, `filename` converts
+ * from the GLib filename encoding, `bytes` exchanges a `Uint8Array` and `lossy` replaces
+ * invalid sequences with U+FFFD. Without an encoding, callback arguments and NULL-terminated
+ * string arrays decode as `lossy` and everything else as `utf8`.
+ */
+type StringEncoding = "utf8" | "filename" | "bytes" | "lossy";
+
+type StringType = { type: "string"; ownership: Ownership; length?: number; encoding?: StringEncoding };
+
+/**
+ * Exchanges boxed instances as JavaScript values instead of handles, which are still accepted:
+ * `date` converts a `GDateTime` to a `Date`, `bigint` to microseconds since the Unix epoch,
+ * and `bytes` a `GBytes` to a `Uint8Array`.
+ */
+type BoxedConverter = "date" | "bigint" | "bytes";
+
+/**
+ * Exchanges objects as strings instead of handles, which are still accepted:
+ * `path` and `uri` convert a `GFile`, and `string` a `GIcon` in its `g_icon_to_string` form.
+ */
+type GObjectConverter = "path" | "uri" | "string";
 
-type GObjectType = { type: "gobject"; ownership: Ownership };
+/** `typeName`, if set, is the GType the argument must be an instance of */
+type GObjectType = { type: "gobject"; ownership: Ownership; typeName?: string; convert?: GObjectConverter };
 
-type BoxedType = { type: "boxed"; ownership: Ownership; innerType: string; library?: string; getTypeFn?: string };
+type BoxedType = {
+    type: "boxed";
+    ownership: Ownership;
+    innerType: string;
+    library?: string;
+    getTypeFn?: string;
+    convert?: BoxedConverter;
+};
 
-type StructType = { type: "struct"; ownership: Ownership; innerType: string; size?: number };
+/** `alignment` is only needed to embed the struct in a `defineStruct` layout */
+type StructType = { type: "struct"; ownership: Ownership; innerType: string; size?: number; alignment?: number };
 
 type FundamentalType = {
     type: "fundamental";
@@ -28,6 +77,8 @@ type FundamentalType = {
     library: string;
     refFn: string;
     unrefFn: string;
+    /** GType the argument must be an instance of; only for `GTypeInstance`-based fundamentals */
+    typeName?: string;
 };
 
 type ArrayType = {
@@ -36,6 +87,7 @@ type ArrayType = {
     kind: "array" | "glist" | "gslist" | "gptrarray" | "garray" | "sized" | "fixed";
     ownership: Ownership;
     elementSize?: number;
+    /** Index of the argument holding the length of a `sized` array; may be an out-parameter `Ref` */
     sizeParamIndex?: number;
     fixedSize?: number;
 };
@@ -47,10 +99,22 @@ type HashTableType = {
     ownership: Ownership;
 };
 
-type RefType = { type: "ref"; innerType: Type };
+/**
+ * An out or inout parameter. For object, boxed, struct and fundamental inner types, the Ref's current
+ * value (or `null`) is passed in the pointer slot, referenced or copied for the callee when ownership is
+ * `full`, and the value the callee leaves there is written back.
+ *
+ * With `callerAllocates`, the call allocates zeroed storage of `size` bytes (default: the `size` of a
+ * `struct` inner type) for a boxed or struct inner type, copies in the struct the Ref holds, if any,
+ * passes it to the callee, and sets the Ref's `value` to an owned handle to it.
+ */
+type RefType = { type: "ref"; innerType: Type; callerAllocates?: boolean; size?: number };
 
 type NullType = { type: "null" };
 
+/** A `GCancellable*` argument whose value is an `AbortSignal` */
+type CancellableType = { type: "cancellable" };
+
 type UndefinedType = { type: "undefined" };
 
 export type CallbackType = {
@@ -91,6 +155,7 @@ export type Type =
     | HashTableType
     | RefType
     | CallbackType
+    | CancellableType
     | NullType
     | UndefinedType;
 
@@ -117,3 +182,206 @@ export type Ref<T> = {
     /** The current value */
     value: T;
 };
+
+/**
+ * A live native handle as reported by `debugHandles`.
+ */
+export type HandleInfo = {
+    /** Internal handle ID */
+    id: number;
+    /** Kind of managed value */
+    kind: "gobject" | "boxed" | "fundamental";
+    /** GType name, or null when unknown */
+    type: string | null;
+    /** GObject reference count, or null for non-GObject handles */
+    refCount: number | null;
+    /** Whether the handle owns its native memory */
+    owned: boolean;
+    /** Milliseconds since the handle was created */
+    ageMs: number;
+    /** JS stack that created the handle, when `GTKX_DEBUG_HANDLES` is set */
+    stack?: string;
+};
+
+/**
+ * Snapshot of the native handle map.
+ */
+export type HandleSnapshot = {
+    /** Number of live handles */
+    total: number;
+    /** Live handle counts per kind */
+    counts: {
+        gobject: Record<string, number>;
+        boxed: Record<string, number>;
+        fundamental: number;
+    };
+    /** Every live handle, ordered by ID */
+    handles: HandleInfo[];
+};
+
+/**
+ * Difference between two handle snapshots.
+ */
+export type HandleDiff = {
+    /** Handles present only in the later snapshot */
+    added: HandleInfo[];
+    /** Handles present only in the earlier snapshot */
+    removed: HandleInfo[];
+    /** Per-kind count changes, omitting unchanged types */
+    counts: {
+        gobject: Record<string, number>;
+        boxed: Record<string, number>;
+        fundamental: number;
+    };
+};
+
+/**
+ * Options for stopping the GTK runtime.
+ */
+export type StopOptions = {
+    /** Release all handles and close libraries instead of leaking them at exit */
+    teardown?: boolean;
+    /** Library name fragments that must not be closed during teardown */
+    keepLibraries?: string[];
+};
+
+/**
+ * Options for a single FFI call.
+ */
+export type CallOptions = {
+    /**
+     * Thread the native function runs on. `"worker"` runs it on a native thread
+     * pool and is only valid for thread-safe functions; GObjects that are not
+     * known to be thread-safe and callbacks are refused.
+     */
+    thread?: "gtk" | "worker";
+    /**
+     * Scheduling priority on the GTK thread. `"high"` runs alongside input
+     * handling, ahead of redraws; `"default"` runs after redraws; `"low"` runs
+     * after all other idle work. Defaults to `"default"`.
+     */
+    priority?: "high" | "default" | "low";
+    /**
+     * Pairs a GIO `_async` function with its `_finish` function. The trailing
+     * cancellable, callback and user data arguments are supplied natively, and
+     * the call returns a Promise resolved with the decoded `_finish` result or
+     * rejected with its `GError`.
+     */
+    finish?: {
+        /** The `_finish` function symbol, taking `(source, result, ...args, error)` */
+        symbol: string;
+        /** Return type of the `_finish` function */
+        resultType: Type;
+        /** Whether `_finish` takes the source object first; `false` for static functions. Defaults to `true`. */
+        instance?: boolean;
+        /**
+         * Arguments between the result and the error, usually `ref` out-parameters that are
+         * updated before the Promise resolves. A `sizeParamIndex` counts from the first of them.
+         */
+        args?: Arg[];
+    };
+    /** Cancels an async call through a `GCancellable`. Requires `finish`. */
+    signal?: AbortSignal;
+};
+
+/**
+ * A named field passed to `defineStruct` or `defineUnion`.
+ */
+export type StructField = {
+    name: string;
+    type: Type;
+    /** Makes the field a bitfield of this many bits; requires an `int` type */
+    bits?: number;
+};
+
+/**
+ * The C layout of a struct or union, computed by `defineStruct` or `defineUnion` for the current platform.
+ */
+export type StructLayout = {
+    /** Total size in bytes, including trailing padding */
+    size: number;
+    /** Alignment in bytes */
+    alignment: number;
+    /** Offset, size and type of each field, by name */
+    fields: Record<string, { offset: number; size: number; type: Type }>;
+    /** Type for embedding this struct in another layout or reading it as a field */
+    type: StructType;
+};
+
+/**
+ * The members of an enum or flags type, returned by `enumValues`.
+ */
+export type EnumValues = {
+    /** GType name, such as `GtkAlign` */
+    typeName: string;
+    /** Whether the type is a flags type, whose values combine as bits */
+    flags: boolean;
+    /** Members in declaration order */
+    values: { name: string; nick: string; value: number }[];
+};
+
+/**
+ * A parameter of a function described by `describeFunction`.
+ */
+export type FunctionParameter = {
+    /** Parameter name from the typelib; `self` for the instance of a method */
+    name: string;
+    /** Whether the function reads the parameter, writes it, or both */
+    direction: "in" | "out" | "inout";
+    /** Argument type; a `ref` type for out and inout parameters */
+    type: Type;
+    /** Whether the parameter accepts `null` */
+    nullable: boolean;
+    /** Index of the array parameter whose length this parameter holds, or -1 for the return value */
+    lengthOf?: number;
+};
+
+/**
+ * A function signature read from a GObject Introspection typelib, returned by
+ * `describeFunction`.
+ */
+export type FunctionSignature = {
+    /** Shared library defining the function */
+    library: string;
+    /** C symbol of the function */
+    symbol: string;
+    /** Parameters in C order, excluding the trailing `GError **` of throwing functions */
+    parameters: FunctionParameter[];
+    /** Return type */
+    returnType: Type;
+    /** Whether the function reports failures through a `GError **` parameter */
+    throws: boolean;
+};
+
+/**
+ * Options for `write` and `writePointer`.
+ */
+export type WriteOptions = {
+    /** Allow writing through a handle that borrows memory owned elsewhere */
+    allowBorrowed?: boolean;
+};
+
+/**
+ * Options for the JS ⇄ GTK deadlock watchdog.
+ */
+export type WatchdogOptions = {
+    /** How long both threads may wait on each other before reporting; 0 disables */
+    timeoutMs?: number;
+    /** Abort the process after reporting a stall */
+    abort?: boolean;
+};
+
+/**
+ * Options for FFI call tracing.
+ */
+export type TracingOptions = {
+    /** Whether calls are recorded; disabling keeps the recorded calls */
+    enabled?: boolean;
+    /** Maximum number of calls kept, oldest discarded first (default 1024) */
+    capacity?: number;
+};
+
+/**
+ * Serialization format for recorded FFI calls.
+ */
+export type TraceFormat = "json" | "chrome";